@@ -0,0 +1,321 @@
+/// Marker of a missing state or pattern.
+const NIL: u32 = u32::MAX;
+
+/// An Aho–Corasick automaton matching a set of byte patterns.
+///
+/// Given patterns `p_0, ..., p_{k-1}` of total length `m`, this data structure supports:
+/// - Search: `find_iter(h)` iterates over every occurrence of every pattern in `h`, and
+///   `count_matches(h)` returns the number of occurrences of each pattern
+/// - Automaton: `next_state(s, b)`, `link(s)`, `depth(s)` and `patterns_at(s)` expose the
+///   states, for dynamic programming over them
+///
+/// Construction runs in O(m σ) time and space, for the number `σ` of distinct bytes in the
+/// patterns, and a search in O(n + number of matches) time. Bytes that appear in no pattern
+/// share a single column of the transition table, leading back to the root. Text can be
+/// matched as the bytes of its UTF-8 encoding.
+///
+/// State 0 is the root, the empty string, and the states are numbered in breadth-first
+/// order, so `depth(s)` is nondecreasing in `s` and `link(s) < s` for every state `s > 0`.
+#[derive(Clone, Debug)]
+pub struct AhoCorasick {
+    /// Column of every byte in `next`, or `sigma` if it appears in no pattern.
+    class: [u16; 256],
+    sigma: usize,
+    /// Transitions of every state, `sigma` per state, completed by the suffix links.
+    next: Vec<u32>,
+    /// Suffix link: the state of the longest proper suffix of the state.
+    link: Vec<u32>,
+    /// Closest state along the suffix links at which a pattern ends, or `NIL`.
+    dict: Vec<u32>,
+    depth: Vec<u32>,
+    /// First pattern ending at every state, or `NIL`.
+    head: Vec<u32>,
+    /// Next pattern ending at the same state, or `NIL`.
+    next_pattern: Vec<u32>,
+    /// Length of every pattern.
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Creates a new automaton matching the given patterns, numbered in order from 0.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m σ)
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let patterns: Vec<P> = patterns.into_iter().collect();
+        let mut class = [u16::MAX; 256];
+        let mut sigma = 0;
+        for &b in patterns.iter().flat_map(|p| p.as_ref()) {
+            if class[b as usize] == u16::MAX {
+                class[b as usize] = sigma as u16;
+                sigma += 1;
+            }
+        }
+        for c in &mut class {
+            if *c == u16::MAX {
+                *c = sigma as u16;
+            }
+        }
+        // Build the trie of the patterns.
+        let mut goto = vec![NIL; sigma];
+        let mut ends: Vec<Vec<u32>> = vec![Vec::new()];
+        let mut lens = Vec::with_capacity(patterns.len());
+        for (i, p) in patterns.iter().enumerate() {
+            let mut t = 0;
+            for &b in p.as_ref() {
+                let e = t * sigma + class[b as usize] as usize;
+                if goto[e] == NIL {
+                    goto[e] = ends.len() as u32;
+                    goto.resize(goto.len() + sigma, NIL);
+                    ends.push(Vec::new());
+                }
+                t = goto[e] as usize;
+            }
+            ends[t].push(i as u32);
+            lens.push(p.as_ref().len());
+        }
+        // Renumber the states in breadth-first order.
+        let n = ends.len();
+        let mut order = Vec::with_capacity(n);
+        order.push(0);
+        let mut id = vec![0; n];
+        let mut i = 0;
+        while i < order.len() {
+            let t = order[i];
+            for c in 0..sigma {
+                let u = goto[t * sigma + c];
+                if u != NIL {
+                    id[u as usize] = order.len() as u32;
+                    order.push(u as usize);
+                }
+            }
+            i += 1;
+        }
+        let mut res = Self {
+            class,
+            sigma,
+            next: vec![0; n * sigma],
+            link: vec![0; n],
+            dict: vec![NIL; n],
+            depth: vec![0; n],
+            head: vec![NIL; n],
+            next_pattern: vec![NIL; patterns.len()],
+            lens,
+        };
+        for (s, &t) in order.iter().enumerate() {
+            for &p in ends[t].iter().rev() {
+                res.next_pattern[p as usize] = res.head[s];
+                res.head[s] = p;
+            }
+            let link = res.link[s] as usize;
+            for c in 0..sigma {
+                let u = goto[t * sigma + c];
+                let fallback = if s == 0 {
+                    0
+                } else {
+                    res.next[link * sigma + c]
+                };
+                if u == NIL {
+                    res.next[s * sigma + c] = fallback;
+                } else {
+                    let v = id[u as usize] as usize;
+                    res.next[s * sigma + c] = v as u32;
+                    res.link[v] = fallback;
+                    res.depth[v] = res.depth[s] + 1;
+                }
+            }
+        }
+        // The heads of the states are known, so dictionary links follow in order.
+        for s in 1..n {
+            let link = res.link[s] as usize;
+            res.dict[s] = if res.head[link] != NIL {
+                link as u32
+            } else {
+                res.dict[link]
+            };
+        }
+        res
+    }
+
+    /// Returns an iterator over every occurrence of every pattern in `haystack`, as the index
+    /// of the pattern and the range it occupies.
+    ///
+    /// Occurrences are ordered by their end, and those with the same end from the longest.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n + number of matches) for a full traversal
+    pub fn find_iter<'a>(
+        &'a self,
+        haystack: &'a [u8],
+    ) -> impl Iterator<Item = (usize, std::ops::Range<usize>)> + 'a {
+        let (mut i, mut state) = (0, 0);
+        // Current state along the dictionary links, and current pattern ending at it.
+        let mut s = self.first_match(0);
+        let mut p = self.head_of(s);
+        std::iter::from_fn(move || {
+            loop {
+                if p != NIL {
+                    let id = p as usize;
+                    p = self.next_pattern[id];
+                    return Some((id, i - self.lens[id]..i));
+                }
+                if s != NIL {
+                    s = self.dict[s as usize];
+                    p = self.head_of(s);
+                    continue;
+                }
+                let &b = haystack.get(i)?;
+                i += 1;
+                state = self.next_state(state, b);
+                s = self.first_match(state);
+                p = self.head_of(s);
+            }
+        })
+    }
+
+    /// Returns the number of occurrences of every pattern in `haystack`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n + m)
+    pub fn count_matches(&self, haystack: &[u8]) -> Vec<usize> {
+        let mut visits = vec![0; self.num_states()];
+        let mut state = 0;
+        visits[0] += 1;
+        for &b in haystack {
+            state = self.next_state(state, b);
+            visits[state] += 1;
+        }
+        // A state occurs wherever one of the states linking to it is visited.
+        for s in (1..self.num_states()).rev() {
+            visits[self.link[s] as usize] += visits[s];
+        }
+        let mut res = vec![0; self.num_patterns()];
+        for (s, &v) in visits.iter().enumerate() {
+            let mut p = self.head[s];
+            while p != NIL {
+                res[p as usize] = v;
+                p = self.next_pattern[p as usize];
+            }
+        }
+        res
+    }
+
+    /// Returns the state reached from `state` by reading byte `b`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn next_state(&self, state: usize, b: u8) -> usize {
+        let c = self.class[b as usize] as usize;
+        if c == self.sigma {
+            0
+        } else {
+            self.next[state * self.sigma + c] as usize
+        }
+    }
+
+    /// Returns the suffix link of `state`, the state of its longest proper suffix, or 0 for
+    /// the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn link(&self, state: usize) -> usize {
+        self.link[state] as usize
+    }
+
+    /// Returns the length of the string of `state`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn depth(&self, state: usize) -> usize {
+        self.depth[state] as usize
+    }
+
+    /// Returns an iterator over the patterns that are suffixes of the string of `state`, from
+    /// the longest.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1 + number of patterns) for a full traversal
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    pub fn patterns_at(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut s = self.first_match(state);
+        let mut p = self.head_of(s);
+        std::iter::from_fn(move || {
+            while p == NIL {
+                if s == NIL {
+                    return None;
+                }
+                s = self.dict[s as usize];
+                p = self.head_of(s);
+            }
+            let id = p as usize;
+            p = self.next_pattern[id];
+            Some(id)
+        })
+    }
+
+    /// Returns the number of states, including the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn num_states(&self) -> usize {
+        self.link.len()
+    }
+
+    /// Returns the number of patterns.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn num_patterns(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// Returns the first state along the suffix links of `state`, itself included, at which
+    /// a pattern ends, or `NIL`.
+    #[inline(always)]
+    fn first_match(&self, state: usize) -> u32 {
+        if self.head[state] != NIL {
+            state as u32
+        } else {
+            self.dict[state]
+        }
+    }
+
+    /// Returns the first pattern ending at state `s`, or `NIL` if `s` is `NIL`.
+    #[inline(always)]
+    fn head_of(&self, s: u32) -> u32 {
+        if s == NIL { NIL } else { self.head[s as usize] }
+    }
+}
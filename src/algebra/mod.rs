@@ -0,0 +1,141 @@
+//! Algebraic traits shared by every data structure of this crate.
+//!
+//! A type implementing [`Monoid`] once, with its operation from [`Semigroup`], can be stored
+//! in any tree, table or decomposition.
+//! Structures that need more than a monoid ask for it through the refinements [`HasInverse`]
+//! (and [`Group`]), [`Idempotent`] and [`Action`].
+
+/// A semigroup is a set equipped with an associative binary operation, without an identity.
+///
+/// # Laws
+///
+/// Implementations must satisfy the following law:
+///
+/// - **Associativity**: `op(op(x, y), z) == op(x, op(y, z))`
+pub trait Semigroup: Clone {
+    /// Performs the binary operation of the semigroup.
+    fn op(&self, other: &Self) -> Self;
+}
+
+/// A monoid is a semigroup with an identity element.
+///
+/// # Laws
+///
+/// Implementations must satisfy all laws of [`Semigroup`] and the following law:
+///
+/// - **Identity**: `op(id(), x) == x` and `op(x, id()) == x`
+pub trait Monoid: Semigroup {
+    /// Returns the identity element of the monoid.
+    fn id() -> Self;
+}
+
+/// A trait for monoids where every element has an inverse.
+///
+/// # Laws
+///
+/// Implementations must satisfy the following law:
+///
+/// - **Inverse**: `op(x, inv(x)) == id()` and `op(inv(x), x) == id()`
+pub trait HasInverse: Monoid {
+    /// Returns the inverse of the element.
+    fn inv(&self) -> Self;
+}
+
+/// A group is a monoid where every element has an inverse.
+///
+/// This trait is automatically implemented for any type that implements
+/// both [`Monoid`] and [`HasInverse`].
+///
+/// # Laws
+///
+/// Implementations must satisfy all laws of [`Monoid`] and [`HasInverse`].
+pub trait Group: Monoid + HasInverse {}
+impl<T: Monoid + HasInverse> Group for T {}
+
+/// A monoid whose operation is idempotent.
+///
+/// # Laws
+///
+/// Implementations must satisfy all laws of [`Monoid`] and the following law:
+///
+/// - **Idempotence**: `op(x, x) == x`
+///
+/// Together with associativity, this makes folds over overlapping ranges exact:
+/// `op(op(a, b), op(b, c)) == op(a, op(b, c))`.
+pub trait Idempotent: Monoid {}
+
+/// An action of a monoid `F` on a monoid `S`.
+///
+/// This represents a homomorphism from `F` to the endomorphism monoid of `S`.
+///
+/// # Laws
+///
+/// Implementations must satisfy the following laws:
+///
+/// - **Identity action**: `F::id().act(s) == s`
+/// - **Compatibility**: `f.op(g).act(s) == f.act(g.act(s))`
+pub trait Action<S: Monoid>: Monoid {
+    /// Applies the action to an element of `S`.
+    fn act(&self, s: &S) -> S;
+}
+
+macro_rules! impl_monoid_tuple {
+    ($($t:ident $i:tt),*) => {
+        /// Componentwise operation.
+        impl<$($t: Semigroup),*> Semigroup for ($($t,)*) {
+            #[inline(always)]
+            fn op(&self, other: &Self) -> Self {
+                ($(self.$i.op(&other.$i),)*)
+            }
+        }
+
+        /// Componentwise identity.
+        impl<$($t: Monoid),*> Monoid for ($($t,)*) {
+            #[inline(always)]
+            fn id() -> Self {
+                ($($t::id(),)*)
+            }
+        }
+
+        impl<$($t: HasInverse),*> HasInverse for ($($t,)*) {
+            #[inline(always)]
+            fn inv(&self) -> Self {
+                ($(self.$i.inv(),)*)
+            }
+        }
+
+        impl<$($t: Idempotent),*> Idempotent for ($($t,)*) {}
+    };
+}
+
+impl_monoid_tuple!(A 0, B 1);
+impl_monoid_tuple!(A 0, B 1, C 2);
+
+/// An action applied to both components of a pair.
+///
+/// `Componentwise(f)` acts on `(a, b)` as `(f.act(a), f.act(b))`, composing as `f` does. Pairs
+/// whose components depend on each other, such as `(sum, len)` under range addition, need an
+/// action of their own instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Componentwise<F>(pub F);
+
+impl<F: Semigroup> Semigroup for Componentwise<F> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        Componentwise(self.0.op(&other.0))
+    }
+}
+
+impl<F: Monoid> Monoid for Componentwise<F> {
+    #[inline(always)]
+    fn id() -> Self {
+        Componentwise(F::id())
+    }
+}
+
+impl<A: Monoid, B: Monoid, F: Action<A> + Action<B>> Action<(A, B)> for Componentwise<F> {
+    #[inline(always)]
+    fn act(&self, s: &(A, B)) -> (A, B) {
+        (self.0.act(&s.0), self.0.act(&s.1))
+    }
+}
@@ -0,0 +1,312 @@
+/// A node of a binary trie over the bits of the values, from the highest bit down.
+///
+/// Node 0 is a sentinel with count 0 whose children are itself, standing for every missing
+/// node.
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    child: [u32; 2],
+    /// Number of values in the subtrie.
+    count: u32,
+}
+
+/// A multiset of fixed-width integers supporting XOR queries.
+///
+/// Given values less than `2^bits`, this data structure supports:
+/// - Update: `insert(x)` and `remove(x)`
+/// - Global XOR: `xor_all(x)` replaces every value `v` with `v ^ x`
+/// - XOR query: `max_xor_with(x)` and `min_xor_with(x)` return the maximum and minimum of
+///   `v ^ x` over the values `v`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest value, and `rank(x)` returns the
+///   number of values less than `x`
+///
+/// All operations run in O(bits) time. Values are stored in a trie over their bits, from
+/// the highest bit down, XORed with a global tag applied on the fly, so that `xor_all` runs
+/// in O(1) time. Nodes are never freed, so the trie holds up to `bits` nodes of 12 bytes per
+/// distinct value ever inserted.
+#[derive(Clone, Debug)]
+pub struct BinaryTrie {
+    nodes: Vec<Node>,
+    bits: u32,
+    /// Every stored value is the XOR of the actual value with this tag.
+    xor: u64,
+}
+
+/// Index of the root.
+const ROOT: u32 = 1;
+
+impl BinaryTrie {
+    /// Creates a new empty multiset of values less than `2^bits`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`.
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= 64, "bits must be at most 64: bits={}", bits);
+        Self {
+            nodes: vec![Node::default(); 2],
+            bits,
+            xor: 0,
+        }
+    }
+
+    /// Inserts `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn insert(&mut self, x: u64) {
+        self.check(x);
+        let y = x ^ self.xor;
+        let mut t = ROOT;
+        self.nodes[t as usize].count += 1;
+        for b in (0..self.bits).rev() {
+            let c = (y >> b & 1) as usize;
+            if self.nodes[t as usize].child[c] == 0 {
+                debug_assert!(
+                    self.nodes.len() < u32::MAX as usize,
+                    "too many nodes: {}",
+                    self.nodes.len(),
+                );
+                self.nodes.push(Node::default());
+                self.nodes[t as usize].child[c] = (self.nodes.len() - 1) as u32;
+            }
+            t = self.nodes[t as usize].child[c];
+            self.nodes[t as usize].count += 1;
+        }
+    }
+
+    /// Removes one occurrence of `x`, and returns `true` if there was one.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn remove(&mut self, x: u64) -> bool {
+        if self.count(x) == 0 {
+            return false;
+        }
+        let y = x ^ self.xor;
+        let mut t = ROOT;
+        self.nodes[t as usize].count -= 1;
+        for b in (0..self.bits).rev() {
+            t = self.nodes[t as usize].child[(y >> b & 1) as usize];
+            self.nodes[t as usize].count -= 1;
+        }
+        true
+    }
+
+    /// Returns the number of occurrences of `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn count(&self, x: u64) -> usize {
+        self.check(x);
+        let y = x ^ self.xor;
+        let mut t = ROOT;
+        for b in (0..self.bits).rev() {
+            t = self.nodes[t as usize].child[(y >> b & 1) as usize];
+        }
+        self.nodes[t as usize].count as usize
+    }
+
+    /// Returns `true` if the multiset contains `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn contains(&self, x: u64) -> bool {
+        self.count(x) > 0
+    }
+
+    /// Replaces every value `v` with `v ^ x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn xor_all(&mut self, x: u64) {
+        self.check(x);
+        self.xor ^= x;
+    }
+
+    /// Returns the maximum of `v ^ x` over the values `v`, or `None` if the multiset is
+    /// empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn max_xor_with(&self, x: u64) -> Option<u64> {
+        self.check(x);
+        self.min_xor_with_stored(!(x ^ self.xor))
+            .map(|v| v ^ x ^ self.xor)
+    }
+
+    /// Returns the minimum of `v ^ x` over the values `v`, or `None` if the multiset is
+    /// empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn min_xor_with(&self, x: u64) -> Option<u64> {
+        self.check(x);
+        self.min_xor_with_stored(x ^ self.xor)
+            .map(|v| v ^ x ^ self.xor)
+    }
+
+    /// Returns the smallest value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    #[inline]
+    pub fn min(&self) -> Option<u64> {
+        self.min_xor_with(0)
+    }
+
+    /// Returns the largest value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    #[inline]
+    pub fn max(&self) -> Option<u64> {
+        self.max_xor_with(0)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest value, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    pub fn kth(&self, mut k: usize) -> Option<u64> {
+        if k >= self.len() {
+            return None;
+        }
+        let mut t = ROOT;
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            let f = (self.xor >> b & 1) as usize;
+            let node = self.nodes[t as usize];
+            let zeros = self.nodes[node.child[f] as usize].count as usize;
+            if k < zeros {
+                t = node.child[f];
+            } else {
+                k -= zeros;
+                t = node.child[f ^ 1];
+                res |= 1 << b;
+            }
+        }
+        Some(res)
+    }
+
+    /// Returns the number of values less than `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn rank(&self, x: u64) -> usize {
+        self.check(x);
+        let mut t = ROOT;
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            // Child `f` holds the values whose bit `b` is 0.
+            let f = (self.xor >> b & 1) as usize;
+            let node = self.nodes[t as usize];
+            if x >> b & 1 == 1 {
+                res += self.nodes[node.child[f] as usize].count as usize;
+                t = node.child[f ^ 1];
+            } else {
+                t = node.child[f];
+            }
+        }
+        res
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.nodes[ROOT as usize].count as usize
+    }
+
+    /// Returns `true` if the multiset is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn check(&self, x: u64) {
+        debug_assert!(
+            self.bits == 64 || x >> self.bits == 0,
+            "value out of bounds: x={}, bits={}",
+            x,
+            self.bits,
+        );
+    }
+
+    /// Returns the stored value `s` minimizing `s ^ y`, if any.
+    fn min_xor_with_stored(&self, y: u64) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut t = ROOT;
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            let mut c = (y >> b & 1) as usize;
+            let node = self.nodes[t as usize];
+            if self.nodes[node.child[c] as usize].count == 0 {
+                c ^= 1;
+            }
+            res |= (c as u64) << b;
+            t = node.child[c];
+        }
+        Some(res)
+    }
+}
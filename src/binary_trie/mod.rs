@@ -0,0 +1,5 @@
+pub mod core;
+pub mod persistent;
+
+pub use core::BinaryTrie;
+pub use persistent::PersistentBinaryTrie;
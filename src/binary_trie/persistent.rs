@@ -0,0 +1,290 @@
+/// A node of a persistent binary trie, shared between versions.
+///
+/// Node 0 is a sentinel with count 0 whose children are itself, standing for every missing
+/// node, and the root of the empty version 0.
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    child: [u32; 2],
+    /// Number of values in the subtrie.
+    count: u32,
+}
+
+/// A persistent multiset of fixed-width integers supporting XOR queries on every version.
+///
+/// Given values less than `2^bits`, this data structure supports:
+/// - Update: `insert(v, x)` returns a new version, with `x` inserted into version `v`
+/// - XOR query: `max_xor_with(v, x)` and `min_xor_with(v, x)` return the maximum and minimum
+///   of `y ^ x` over the values `y` of version `v`
+/// - Difference query: `max_xor_between(u, v, x)` and `min_xor_between(u, v, x)` do the same
+///   over the values inserted into `v` since its ancestor `u`
+/// - Order statistics: `kth(v, k)` returns the `k`-th smallest value of version `v`
+///
+/// All operations run in O(bits) time. Every insertion copies the `bits + 1` nodes on the
+/// path of its value and shares the rest with the previous version. The versions built by
+/// [`from_slice`](Self::from_slice) are the prefixes of a sequence, so the values of any
+/// subarray `a[l..r]` are the difference of versions `l` and `r`.
+#[derive(Clone, Debug)]
+pub struct PersistentBinaryTrie {
+    nodes: Vec<Node>,
+    /// Root of every version.
+    roots: Vec<u32>,
+    bits: u32,
+}
+
+impl PersistentBinaryTrie {
+    /// Creates a new persistent multiset of values less than `2^bits`, with only the empty
+    /// version 0.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`.
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= 64, "bits must be at most 64: bits={}", bits);
+        Self {
+            nodes: vec![Node::default()],
+            roots: vec![0],
+            bits,
+        }
+    }
+
+    /// Creates a new persistent multiset of values less than `2^bits`, where version `i`
+    /// holds `v[..i]` for `i` in `0..=v.len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`, or if a value is at least `2^bits` in debug builds.
+    pub fn from_slice(v: &[u64], bits: u32) -> Self {
+        let mut res = Self::new(bits);
+        res.nodes.reserve(v.len() * (bits as usize + 1));
+        for (i, &x) in v.iter().enumerate() {
+            res.insert(i, x);
+        }
+        res
+    }
+
+    /// Creates a new version with `x` inserted into version `v`, and returns it.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version or `x >= 2^bits` in debug builds.
+    pub fn insert(&mut self, v: usize, x: u64) -> usize {
+        self.check_version(v);
+        self.check(x);
+        let mut t = self.roots[v];
+        let root = self.nodes.len() as u32;
+        for b in (0..=self.bits).rev() {
+            let mut node = self.nodes[t as usize];
+            node.count += 1;
+            if b > 0 {
+                let c = (x >> (b - 1) & 1) as usize;
+                t = node.child[c];
+                node.child[c] = self.nodes.len() as u32 + 1;
+            }
+            debug_assert!(
+                self.nodes.len() < u32::MAX as usize,
+                "too many nodes: {}",
+                self.nodes.len(),
+            );
+            self.nodes.push(node);
+        }
+        self.roots.push(root);
+        self.roots.len() - 1
+    }
+
+    /// Returns the number of occurrences of `x` in version `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version or `x >= 2^bits` in debug builds.
+    pub fn count(&self, v: usize, x: u64) -> usize {
+        self.check_version(v);
+        self.check(x);
+        let mut t = self.roots[v];
+        for b in (0..self.bits).rev() {
+            t = self.nodes[t as usize].child[(x >> b & 1) as usize];
+        }
+        self.nodes[t as usize].count as usize
+    }
+
+    /// Returns the maximum of `y ^ x` over the values `y` of version `v`, or `None` if it is
+    /// empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version or `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn max_xor_with(&self, v: usize, x: u64) -> Option<u64> {
+        self.max_xor_between(0, v, x)
+    }
+
+    /// Returns the minimum of `y ^ x` over the values `y` of version `v`, or `None` if it is
+    /// empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version or `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn min_xor_with(&self, v: usize, x: u64) -> Option<u64> {
+        self.min_xor_between(0, v, x)
+    }
+
+    /// Returns the maximum of `y ^ x` over the values `y` inserted into version `v` since
+    /// version `u`, or `None` if there are none.
+    ///
+    /// Version `u` must be an ancestor of version `v`, such as version `l` of version `r`
+    /// for the prefixes built by [`from_slice`](Self::from_slice).
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` is not a version, or `x >= 2^bits`, in debug builds.
+    #[inline]
+    pub fn max_xor_between(&self, u: usize, v: usize, x: u64) -> Option<u64> {
+        self.check(x);
+        self.closest(u, v, !x).map(|y| y ^ x)
+    }
+
+    /// Returns the minimum of `y ^ x` over the values `y` inserted into version `v` since
+    /// version `u`, or `None` if there are none.
+    ///
+    /// Version `u` must be an ancestor of version `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` or `v` is not a version, or `x >= 2^bits`, in debug builds.
+    #[inline]
+    pub fn min_xor_between(&self, u: usize, v: usize, x: u64) -> Option<u64> {
+        self.check(x);
+        self.closest(u, v, x).map(|y| y ^ x)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest value of version `v`, or `None` if
+    /// `k >= len(v)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version in debug builds.
+    pub fn kth(&self, v: usize, mut k: usize) -> Option<u64> {
+        if k >= self.len(v) {
+            return None;
+        }
+        let mut t = self.roots[v];
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            let node = self.nodes[t as usize];
+            let zeros = self.nodes[node.child[0] as usize].count as usize;
+            if k < zeros {
+                t = node.child[0];
+            } else {
+                k -= zeros;
+                t = node.child[1];
+                res |= 1 << b;
+            }
+        }
+        Some(res)
+    }
+
+    /// Returns the number of values of version `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not a version in debug builds.
+    #[inline]
+    pub fn len(&self, v: usize) -> usize {
+        self.check_version(v);
+        self.nodes[self.roots[v] as usize].count as usize
+    }
+
+    /// Returns the number of versions, including the empty version 0.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn versions(&self) -> usize {
+        self.roots.len()
+    }
+
+    #[inline(always)]
+    fn check(&self, x: u64) {
+        debug_assert!(
+            self.bits == 64 || x >> self.bits == 0,
+            "value out of bounds: x={}, bits={}",
+            x,
+            self.bits,
+        );
+    }
+
+    #[inline(always)]
+    fn check_version(&self, v: usize) {
+        debug_assert!(
+            v < self.versions(),
+            "version out of bounds: v={}, versions={}",
+            v,
+            self.versions(),
+        );
+    }
+
+    /// Returns the value `y` minimizing `y ^ x` among the values inserted into version `v`
+    /// since version `u`, if any.
+    fn closest(&self, u: usize, v: usize, x: u64) -> Option<u64> {
+        self.check_version(u);
+        self.check_version(v);
+        let (mut s, mut t) = (self.roots[u], self.roots[v]);
+        let count = |s: u32, t: u32| self.nodes[t as usize].count - self.nodes[s as usize].count;
+        if count(s, t) == 0 {
+            return None;
+        }
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            let mut c = (x >> b & 1) as usize;
+            let (ns, nt) = (self.nodes[s as usize], self.nodes[t as usize]);
+            if count(ns.child[c], nt.child[c]) == 0 {
+                c ^= 1;
+            }
+            res |= (c as u64) << b;
+            s = ns.child[c];
+            t = nt.child[c];
+        }
+        Some(res)
+    }
+}
@@ -0,0 +1,314 @@
+/// Number of bits covered by a block of `counts`.
+const BLOCK: usize = 512;
+
+/// Number of ones, or zeros, between two select hints.
+const HINT: usize = 512;
+
+/// A static bit vector supporting rank and select queries.
+///
+/// Given a sequence `b` of `n` bits, this data structure supports:
+/// - Access: `get(i)` returns `b[i]`
+/// - Rank: `rank1(i)` and `rank0(i)` return the number of ones and zeros in `b[..i]`
+/// - Select: `select1(k)` and `select0(k)` return the position of the `k`-th one and zero
+///
+/// Built in O(n) time. Rank runs in O(1) time: for every block of 512 bits, one word holds the
+/// number of ones before the block, and another packs the number of ones before each of its
+/// words in 9 bits each, which adds 25% to the bits. Select first narrows the blocks down to
+/// those between two hints, recorded every 512 ones and every 512 zeros, then searches them
+/// and the words of a block, in O(log n) worst-case time and almost constant time on
+/// evenly spread bits.
+///
+/// This backs the [`WaveletMatrix`](crate::wavelet_matrix::WaveletMatrix) and the
+/// [`FmIndex`](crate::fm_index::FmIndex), and serves on its own as a compact sorted set of
+/// positions.
+#[derive(Clone, Debug)]
+pub struct RankSelectBitVec {
+    words: Box<[u64]>,
+    /// For every block and a final one, the number of ones before it, then the number of ones
+    /// before each of its words `1..8` within it, in 9 bits each.
+    counts: Box<[u64]>,
+    /// Block of every `HINT`-th one.
+    hints1: Box<[usize]>,
+    /// Block of every `HINT`-th zero.
+    hints0: Box<[usize]>,
+    len: usize,
+}
+
+impl RankSelectBitVec {
+    /// Creates a new bit vector from an iterator of bits.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_bits(bits: impl IntoIterator<Item = bool>) -> Self {
+        let bits = bits.into_iter();
+        let mut words = Vec::with_capacity(bits.size_hint().0.div_ceil(64));
+        let mut len = 0;
+        for b in bits {
+            if len & 63 == 0 {
+                words.push(0);
+            }
+            *words.last_mut().unwrap() |= (b as u64) << (len & 63);
+            len += 1;
+        }
+        Self::from_words(words, len)
+    }
+
+    /// Creates a new bit vector from a slice of bits.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(bits: &[bool]) -> Self {
+        Self::from_bits(bits.iter().copied())
+    }
+
+    /// Returns the bit at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline(always)]
+    pub fn get(&self, i: usize) -> bool {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        (self.words[i >> 6] >> (i & 63)) & 1 == 1
+    }
+
+    /// Returns the number of ones in `b[..i]`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > len()` in debug builds.
+    #[inline(always)]
+    pub fn rank1(&self, i: usize) -> usize {
+        debug_assert!(
+            i <= self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let w = i >> 6;
+        let mut res = self.word_rank(w);
+        if i & 63 != 0 {
+            res += (self.words[w] & ((1 << (i & 63)) - 1)).count_ones() as usize;
+        }
+        res
+    }
+
+    /// Returns the number of zeros in `b[..i]`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > len()` in debug builds.
+    #[inline(always)]
+    pub fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+
+    /// Returns the position of the `k`-th (0-indexed) one, or `None` if there are at most `k`
+    /// ones.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        (k < self.count_ones()).then(|| self.select::<true>(k))
+    }
+
+    /// Returns the position of the `k`-th (0-indexed) zero, or `None` if there are at most
+    /// `k` zeros.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        (k < self.count_zeros()).then(|| self.select::<false>(k))
+    }
+
+    /// Returns the number of ones.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn count_ones(&self) -> usize {
+        self.rank1(self.len)
+    }
+
+    /// Returns the number of zeros.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns the number of bits.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the bit vector is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bits packed in words, bit `i` being bit `i % 64` of word `i / 64`.
+    #[inline(always)]
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    fn from_words(words: Vec<u64>, len: usize) -> Self {
+        let blocks = words.len() / 8 + 1;
+        let mut counts = Vec::with_capacity(2 * blocks + 2);
+        let mut total = 0;
+        for b in 0..blocks {
+            counts.push(total as u64);
+            let mut rel = 0;
+            let mut inner = 0;
+            for j in 0..8 {
+                if j > 0 {
+                    rel |= (inner as u64) << (9 * (j - 1));
+                }
+                inner += words.get(8 * b + j).map_or(0, |w| w.count_ones() as usize);
+            }
+            counts.push(rel);
+            total += inner;
+        }
+        // A final entry bounds the searches over the blocks.
+        counts.extend([total as u64, 0]);
+        let ones = total;
+        let mut res = Self {
+            words: words.into_boxed_slice(),
+            counts: counts.into_boxed_slice(),
+            hints1: Box::new([]),
+            hints0: Box::new([]),
+            len,
+        };
+        res.hints1 = res.hints::<true>(ones);
+        res.hints0 = res.hints::<false>(len - ones);
+        res
+    }
+
+    /// Returns the block of every `HINT`-th one, or zero, out of `count`.
+    fn hints<const ONE: bool>(&self, count: usize) -> Box<[usize]> {
+        let mut b = 0;
+        (0..count.div_ceil(HINT))
+            .map(|h| {
+                while self.block_rank::<ONE>(b + 1) <= h * HINT {
+                    b += 1;
+                }
+                b
+            })
+            .collect()
+    }
+
+    /// Returns the number of ones, or zeros, before block `b`.
+    #[inline(always)]
+    fn block_rank<const ONE: bool>(&self, b: usize) -> usize {
+        let ones = self.counts[2 * b] as usize;
+        if ONE { ones } else { b * BLOCK - ones }
+    }
+
+    /// Returns the number of ones before word `w`.
+    #[inline(always)]
+    fn word_rank(&self, w: usize) -> usize {
+        let (b, j) = (w >> 3, w & 7);
+        let mut res = self.counts[2 * b] as usize;
+        if j > 0 {
+            res += (self.counts[2 * b + 1] >> (9 * (j - 1)) & 511) as usize;
+        }
+        res
+    }
+
+    /// Returns the position of the `k`-th one, or zero, which exists.
+    fn select<const ONE: bool>(&self, k: usize) -> usize {
+        let hints = if ONE { &self.hints1 } else { &self.hints0 };
+        // Find the last block `b` with fewer than `k + 1` ones before it.
+        let mut lo = hints[k / HINT];
+        let mut hi = hints
+            .get(k / HINT + 1)
+            .map_or(self.counts.len() / 2 - 1, |&b| b + 1);
+        while hi - lo > 1 {
+            let mid = (lo + hi) >> 1;
+            if self.block_rank::<ONE>(mid) <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut w = 8 * lo;
+        let mut k = k - self.block_rank::<ONE>(lo);
+        loop {
+            let word = if ONE { self.words[w] } else { !self.words[w] };
+            let c = word.count_ones() as usize;
+            if k < c {
+                return (w << 6) + select_in_word(word, k as u32) as usize;
+            }
+            k -= c;
+            w += 1;
+        }
+    }
+}
+
+/// Returns the position of the `k`-th (0-indexed) one of `w`, which has more than `k` ones.
+#[inline]
+fn select_in_word(w: u64, mut k: u32) -> u32 {
+    let mut shift = 0;
+    loop {
+        let c = ((w >> shift) & 0xff).count_ones();
+        if k < c {
+            break;
+        }
+        k -= c;
+        shift += 8;
+    }
+    let mut byte = (w >> shift) & 0xff;
+    for _ in 0..k {
+        byte &= byte - 1;
+    }
+    shift + byte.trailing_zeros()
+}
+
+impl FromIterator<bool> for RankSelectBitVec {
+    /// Creates a new bit vector from an iterator of bits.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self::from_bits(iter)
+    }
+}
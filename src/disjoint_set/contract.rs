@@ -0,0 +1,88 @@
+/// The quotient map obtained by contracting every set of a DSU into a single vertex.
+///
+/// Created by [`GenericDsu::contract`](crate::disjoint_set::GenericDsu::contract).
+/// Component ids are dense in `0..num_components()`, which makes the result suitable as the
+/// vertex set of the next round of a Borůvka-style algorithm.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contraction {
+    /// Component id of each original vertex.
+    ids: Vec<usize>,
+    num_components: usize,
+}
+
+impl Contraction {
+    /// Creates a contraction from dense component ids.
+    pub(crate) fn new(ids: Vec<usize>, num_components: usize) -> Self {
+        Self {
+            ids,
+            num_components,
+        }
+    }
+
+    /// Returns the component id of the original vertex `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is out of bounds.
+    #[inline]
+    pub fn id(&self, v: usize) -> usize {
+        self.ids[v]
+    }
+
+    /// Returns the component ids of all original vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn ids(&self) -> &[usize] {
+        &self.ids
+    }
+
+    /// Consumes the contraction and returns the component ids of all original vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn into_ids(self) -> Vec<usize> {
+        self.ids
+    }
+
+    /// Returns the number of components, i.e. the number of vertices after contraction.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// Maps an edge list onto the contracted vertices, dropping intra-component edges.
+    ///
+    /// Returns `(id(u), id(v), i)` for every edge `edges[i] = (u, v)` whose endpoints lie in
+    /// different components, in the original order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m), where m is the number of edges
+    ///
+    /// # Panics
+    ///
+    /// Panics if an endpoint is out of bounds.
+    pub fn edges(&self, edges: &[(usize, usize)]) -> Vec<(usize, usize, usize)> {
+        edges
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(u, v))| {
+                let (cu, cv) = (self.ids[u], self.ids[v]);
+                (cu != cv).then_some((cu, cv, i))
+            })
+            .collect()
+    }
+}
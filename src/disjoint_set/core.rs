@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+
+use crate::disjoint_set::contract::Contraction;
+use crate::disjoint_set::frozen::FrozenDsu;
+use crate::disjoint_set::index::DsuIndex;
+use crate::disjoint_set::policy::{Compression, Representative};
+
+/// A disjoint set union (DSU) with 32-bit parent links, supporting up to `2^31 - 1` elements.
+pub type Dsu = GenericDsu<i32>;
+
+/// A disjoint set union (DSU) with 64-bit parent links, for very large element counts.
+pub type WideDsu = GenericDsu<i64>;
+
+/// A disjoint set union (DSU) data structure, generic over the integer type `I` used to
+/// store parent links.
+///
+/// By default, uses path halving and union by size, achieving amortized O(α(n)) time per
+/// operation, where α is the inverse Ackermann function. Both can be changed with
+/// [`with_policy`](Self::with_policy).
+///
+/// Prefer the [`Dsu`] and [`WideDsu`] aliases.
+#[derive(Clone, Debug)]
+pub struct GenericDsu<I: DsuIndex> {
+    /// If negative, this node is a root and the absolute value is the size of the set.
+    /// If non-negative, this is the index of the parent node.
+    parent: Vec<I>,
+    /// Number of non-tree edges (edges that closed a cycle) in the set rooted at each node.
+    /// Left empty until the first such edge is added.
+    extra_edges: Vec<usize>,
+    num_components: usize,
+    representative: Representative,
+    compression: Compression,
+}
+
+impl<I: DsuIndex> GenericDsu<I> {
+    /// Creates a new DSU with `n` elements, where each element is initially in its own set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if `n > I::MAX_LEN`
+    pub fn new(n: usize) -> Self {
+        Self::with_policy(n, Representative::default(), Compression::default())
+    }
+
+    /// Creates a new DSU with `n` elements using the given representative policy and
+    /// compression strategy.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if `n > I::MAX_LEN`
+    pub fn with_policy(n: usize, representative: Representative, compression: Compression) -> Self {
+        debug_assert!(
+            n <= I::MAX_LEN,
+            "`n` is too large for the index type: n={}, max={}",
+            n,
+            I::MAX_LEN
+        );
+        Self {
+            parent: vec![I::NEG_ONE; n],
+            extra_edges: Vec::new(),
+            num_components: n,
+            representative,
+            compression,
+        }
+    }
+
+    /// Creates a new DSU with `n` elements and adds every edge in `edges`.
+    ///
+    /// Each edge is recorded with [`add_edge`](Self::add_edge), so
+    /// [`edge_count`](Self::edge_count) reflects self-loops and multi-edges as well.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n + m α(n)), where m is the number of edges
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if `n > I::MAX_LEN` or an endpoint is out of bounds
+    pub fn from_edges(n: usize, edges: &[(usize, usize)]) -> Self {
+        let mut dsu = Self::new(n);
+        for &(u, v) in edges {
+            dsu.add_edge(u, v);
+        }
+        dsu
+    }
+
+    /// Appends a new element in its own singleton set and returns its index.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if the new length exceeds `I::MAX_LEN`
+    pub fn push(&mut self) -> usize {
+        let i = self.len();
+        debug_assert!(
+            i < I::MAX_LEN,
+            "`n` is too large for the index type: n={}, max={}",
+            i + 1,
+            I::MAX_LEN
+        );
+        self.parent.push(I::NEG_ONE);
+        if !self.extra_edges.is_empty() {
+            self.extra_edges.push(0);
+        }
+        self.num_components += 1;
+        i
+    }
+
+    /// Appends the elements of `other` after the current ones, preserving the sets of both.
+    ///
+    /// Element `i` of `other` becomes element `len() + i` of `self`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m), where m is the number of elements in `other`
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if the new length exceeds `I::MAX_LEN`
+    pub fn append(&mut self, other: &Self) {
+        let offset = self.len();
+        debug_assert!(
+            offset + other.len() <= I::MAX_LEN,
+            "`n` is too large for the index type: n={}, max={}",
+            offset + other.len(),
+            I::MAX_LEN
+        );
+        self.parent.extend(other.parent.iter().map(|&p| {
+            if p.is_negative() {
+                p
+            } else {
+                I::from_index(p.to_index() + offset)
+            }
+        }));
+        if !other.extra_edges.is_empty() {
+            self.extra_edges.resize(offset, 0);
+            self.extra_edges.extend_from_slice(&other.extra_edges);
+        } else if !self.extra_edges.is_empty() {
+            self.extra_edges.resize(self.len(), 0);
+        }
+        self.num_components += other.num_components;
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) worst case
+    pub fn reserve(&mut self, additional: usize) {
+        self.parent.reserve(additional);
+    }
+
+    /// Restores every element to its own singleton set, keeping the current allocation.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn reset(&mut self) {
+        self.parent.fill(I::NEG_ONE);
+        self.extra_edges.clear();
+        self.num_components = self.len();
+    }
+
+    /// Resizes the DSU to `n` elements, each in its own singleton set.
+    ///
+    /// Reuses the current allocation when its capacity is sufficient.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug mode if `n > I::MAX_LEN`
+    pub fn reset_to(&mut self, n: usize) {
+        debug_assert!(
+            n <= I::MAX_LEN,
+            "`n` is too large for the index type: n={}, max={}",
+            n,
+            I::MAX_LEN
+        );
+        self.parent.clear();
+        self.parent.resize(n, I::NEG_ONE);
+        self.extra_edges.clear();
+        self.num_components = n;
+    }
+
+    /// Returns the representative (root) of the set containing `x`.
+    ///
+    /// Applies path compression according to the configured [`Compression`] strategy.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline(always)]
+    pub fn root(&mut self, mut x: usize) -> usize {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        unsafe {
+            let p = self.parent.as_mut_ptr();
+            match self.compression {
+                Compression::Halving => {
+                    while !(*p.add(x)).is_negative() {
+                        let px = (*p.add(x)).to_index();
+                        if !(*p.add(px)).is_negative() {
+                            *p.add(x) = *p.add(px);
+                        }
+                        x = px;
+                    }
+                    x
+                }
+                Compression::Full => {
+                    let mut r = x;
+                    while !(*p.add(r)).is_negative() {
+                        r = (*p.add(r)).to_index();
+                    }
+                    while x != r {
+                        let px = (*p.add(x)).to_index();
+                        *p.add(x) = I::from_index(r);
+                        x = px;
+                    }
+                    r
+                }
+                Compression::None => {
+                    while !(*p.add(x)).is_negative() {
+                        x = (*p.add(x)).to_index();
+                    }
+                    x
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if `x` is the representative of its set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn is_root(&self, x: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        unsafe { self.parent.get_unchecked(x).is_negative() }
+    }
+
+    /// Unites the sets containing `x` and `y`.
+    ///
+    /// Returns `true` if `x` and `y` were in different sets, `false` otherwise.
+    /// The surviving root is chosen by the configured [`Representative`] policy.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn unite(&mut self, x: usize, y: usize) -> bool {
+        self.unite_with(x, y, |_, _| {})
+    }
+
+    /// Unites the sets containing `x` and `y`, calling `f(kept, absorbed)` if a merge happens.
+    ///
+    /// `kept` is the root that remains the representative of the merged set, and
+    /// `absorbed` is the root that was attached below it. `f` is not called if `x` and `y`
+    /// were already in the same set.
+    ///
+    /// Returns `true` if `x` and `y` were in different sets, `false` otherwise.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n)), plus the cost of `f`
+    #[inline]
+    pub fn unite_with<F>(&mut self, x: usize, y: usize, f: F) -> bool
+    where
+        F: FnOnce(usize, usize),
+    {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        debug_assert!(
+            y < self.len(),
+            "index out of bounds: y={}, len={}",
+            y,
+            self.len()
+        );
+        let (mut rx, mut ry) = (self.root(x), self.root(y));
+        if rx == ry {
+            return false;
+        }
+        unsafe {
+            let p = self.parent.as_mut_ptr();
+            let swap = match self.representative {
+                Representative::BySize => *p.add(rx) > *p.add(ry),
+                Representative::Min => rx > ry,
+            };
+            if swap {
+                std::mem::swap(&mut rx, &mut ry);
+            }
+            *p.add(rx) += *p.add(ry);
+            *p.add(ry) = I::from_index(rx);
+        }
+        if !self.extra_edges.is_empty() {
+            let extra = std::mem::take(&mut self.extra_edges[ry]);
+            self.extra_edges[rx] += extra;
+        }
+        self.num_components -= 1;
+        f(rx, ry);
+        true
+    }
+
+    /// Adds an edge between `x` and `y`, uniting their sets.
+    ///
+    /// Unlike [`unite`](Self::unite), the edge is recorded even if `x` and `y` are already
+    /// in the same set, so self-loops and multi-edges are counted by
+    /// [`edge_count`](Self::edge_count).
+    ///
+    /// Returns `true` if `x` and `y` were in different sets, `false` otherwise.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn add_edge(&mut self, x: usize, y: usize) -> bool {
+        if self.unite(x, y) {
+            return true;
+        }
+        if self.extra_edges.is_empty() {
+            self.extra_edges.resize(self.len(), 0);
+        }
+        let root = self.root(x);
+        self.extra_edges[root] += 1;
+        false
+    }
+
+    /// Returns the number of edges in the set containing `x`.
+    ///
+    /// Every successful [`unite`](Self::unite) counts as one edge, and every call to
+    /// [`add_edge`](Self::add_edge) counts as one edge.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn edge_count(&mut self, x: usize) -> usize {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        let root = self.root(x);
+        let size = unsafe { self.parent.get_unchecked(root).to_size() };
+        size - 1 + self.extra_edges.get(root).copied().unwrap_or(0)
+    }
+
+    /// Returns `true` if the set containing `x` forms a tree, i.e. it has exactly
+    /// `size(x) - 1` edges.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn is_tree(&mut self, x: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        let root = self.root(x);
+        self.extra_edges.get(root).is_none_or(|&e| e == 0)
+    }
+
+    /// Returns `true` if `x` and `y` belong to the same set.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        debug_assert!(
+            y < self.len(),
+            "index out of bounds: y={}, len={}",
+            y,
+            self.len()
+        );
+        self.root(x) == self.root(y)
+    }
+
+    /// Returns the size of the set containing `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn size(&mut self, x: usize) -> usize {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        let root = self.root(x);
+        unsafe { self.parent.get_unchecked(root).to_size() }
+    }
+
+    /// Returns all sets as a vector of vectors.
+    ///
+    /// Each inner vector contains the elements of one set in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut groups = vec![vec![]; self.len()];
+        for i in 0..self.len() {
+            groups[self.root(i)].push(i);
+        }
+        groups.into_iter().filter(|g| !g.is_empty()).collect()
+    }
+
+    /// Returns a component label for every element.
+    ///
+    /// Labels are compact ids in `0..num_components()`, assigned in order of the smallest
+    /// element of each set, so `labels[0] == 0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn component_labels(&mut self) -> Vec<usize> {
+        let mut labels = vec![usize::MAX; self.len()];
+        let mut k = 0;
+        for i in 0..self.len() {
+            let r = self.root(i);
+            if labels[r] == usize::MAX {
+                labels[r] = k;
+                k += 1;
+            }
+            labels[i] = labels[r];
+        }
+        labels
+    }
+
+    /// Returns an iterator over the representatives of all sets in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) for a full iteration
+    pub fn roots(&self) -> impl Iterator<Item = usize> {
+        (0..self.len()).filter(move |&i| self.is_root(i))
+    }
+
+    /// Alias of [`component_labels`](Self::component_labels).
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    #[inline]
+    pub fn compress_ids(&mut self) -> Vec<usize> {
+        self.component_labels()
+    }
+
+    /// Contracts every set into a single vertex and returns the quotient map.
+    ///
+    /// The component ids are the same as those returned by
+    /// [`component_labels`](Self::component_labels).
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn contract(&mut self) -> Contraction {
+        Contraction::new(self.compress_ids(), self.num_components)
+    }
+
+    /// Fully compresses every path and converts the DSU into an immutable [`FrozenDsu`].
+    ///
+    /// The returned snapshot answers queries through `&self` and can be shared across threads.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn freeze(mut self) -> FrozenDsu<I> {
+        for i in 0..self.len() {
+            let r = self.root(i);
+            if r != i {
+                unsafe {
+                    *self.parent.get_unchecked_mut(i) = I::from_index(r);
+                }
+            }
+        }
+        FrozenDsu::new(self.parent.into_boxed_slice(), self.num_components)
+    }
+
+    /// Returns all sets as a map from each representative to the elements of its set.
+    ///
+    /// Each vector contains the elements of one set in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn groups_map(&mut self) -> HashMap<usize, Vec<usize>> {
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::with_capacity(self.num_components);
+        for i in 0..self.len() {
+            groups.entry(self.root(i)).or_default().push(i);
+        }
+        groups
+    }
+
+    /// Returns a histogram of set sizes.
+    ///
+    /// The returned vector has length `len() + 1`, and its `s`-th entry is the number of
+    /// sets of size `s`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn size_histogram(&self) -> Vec<usize> {
+        let mut hist = vec![0; self.len() + 1];
+        for &p in &self.parent {
+            if p.is_negative() {
+                hist[p.to_size()] += 1;
+            }
+        }
+        hist
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// Returns the total number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Returns `true` if the DSU contains no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+}
@@ -0,0 +1,126 @@
+use crate::disjoint_set::index::DsuIndex;
+
+/// An immutable snapshot of a [`GenericDsu`](crate::disjoint_set::GenericDsu).
+///
+/// Every path is fully compressed, so all queries run in O(1) time through `&self`.
+/// Since there is no interior mutation, the snapshot is `Send + Sync` and can be
+/// shared across threads without locking.
+#[derive(Clone, Debug)]
+pub struct FrozenDsu<I: DsuIndex = i32> {
+    /// If negative, this node is a root and the absolute value is the size of the set.
+    /// If non-negative, this is the index of the root of the set.
+    parent: Box<[I]>,
+    num_components: usize,
+}
+
+impl<I: DsuIndex> FrozenDsu<I> {
+    /// Creates a snapshot from a fully compressed parent array.
+    pub(crate) fn new(parent: Box<[I]>, num_components: usize) -> Self {
+        Self {
+            parent,
+            num_components,
+        }
+    }
+
+    /// Returns the representative (root) of the set containing `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn root(&self, x: usize) -> usize {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        let p = unsafe { *self.parent.get_unchecked(x) };
+        if p.is_negative() { x } else { p.to_index() }
+    }
+
+    /// Returns `true` if `x` is the representative of its set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn is_root(&self, x: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        unsafe { self.parent.get_unchecked(x).is_negative() }
+    }
+
+    /// Returns `true` if `x` and `y` belong to the same set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn same(&self, x: usize, y: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        debug_assert!(
+            y < self.len(),
+            "index out of bounds: y={}, len={}",
+            y,
+            self.len()
+        );
+        self.root(x) == self.root(y)
+    }
+
+    /// Returns the size of the set containing `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn size(&self, x: usize) -> usize {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        let root = self.root(x);
+        unsafe { self.parent.get_unchecked(root).to_size() }
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// Returns the total number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Returns `true` if the snapshot contains no elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+}
@@ -0,0 +1,199 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::disjoint_set::Dsu;
+
+/// A disjoint set union over arbitrary hashable keys.
+///
+/// Keys are assigned dense indices lazily the first time they are united. A key that has
+/// never been united behaves as a singleton set.
+#[derive(Clone, Debug)]
+pub struct HashDsu<K: Hash + Eq + Clone> {
+    /// Dense index assigned to each key.
+    index: HashMap<K, usize>,
+    /// Key of each dense index.
+    keys: Vec<K>,
+    dsu: Dsu,
+}
+
+impl<K: Hash + Eq + Clone> HashDsu<K> {
+    /// Creates a new empty DSU.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates a new empty DSU with space for at least `capacity` keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(capacity)
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut dsu = Dsu::new(0);
+        dsu.reserve(capacity);
+        Self {
+            index: HashMap::with_capacity(capacity),
+            keys: Vec::with_capacity(capacity),
+            dsu,
+        }
+    }
+
+    /// Returns the dense index of `key`, inserting it as a singleton set if it is new.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(1)
+    pub fn insert(&mut self, key: K) -> usize {
+        if let Some(&i) = self.index.get(&key) {
+            return i;
+        }
+        let i = self.dsu.push();
+        self.keys.push(key.clone());
+        self.index.insert(key, i);
+        i
+    }
+
+    /// Returns the dense index of `key`, or `None` if it has never been inserted.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).copied()
+    }
+
+    /// Returns the key with dense index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()`.
+    #[inline]
+    pub fn key(&self, i: usize) -> &K {
+        &self.keys[i]
+    }
+
+    /// Unites the sets containing `x` and `y`, inserting either key if it is new.
+    ///
+    /// Returns `true` if `x` and `y` were in different sets, `false` otherwise.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    pub fn unite(&mut self, x: K, y: K) -> bool {
+        let x = self.insert(x);
+        let y = self.insert(y);
+        self.dsu.unite(x, y)
+    }
+
+    /// Returns `true` if `x` and `y` belong to the same set.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    pub fn same<Q>(&mut self, x: &Q, y: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match (self.index_of(x), self.index_of(y)) {
+            (Some(x), Some(y)) => self.dsu.same(x, y),
+            _ => x == y,
+        }
+    }
+
+    /// Returns the representative key of the set containing `x`.
+    ///
+    /// Returns `None` if `x` has never been inserted.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    pub fn root<Q>(&mut self, x: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.index_of(x)?;
+        let r = self.dsu.root(i);
+        Some(&self.keys[r])
+    }
+
+    /// Returns the size of the set containing `x`.
+    ///
+    /// Returns `1` if `x` has never been inserted.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    pub fn size<Q>(&mut self, x: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index_of(x).map_or(1, |i| self.dsu.size(i))
+    }
+
+    /// Returns all sets of inserted keys as a vector of vectors.
+    ///
+    /// Each inner vector contains the keys of one set in insertion order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n α(n))
+    pub fn groups(&mut self) -> Vec<Vec<K>> {
+        self.dsu
+            .groups()
+            .into_iter()
+            .map(|g| g.into_iter().map(|i| self.keys[i].clone()).collect())
+            .collect()
+    }
+
+    /// Returns the number of disjoint sets among the inserted keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_components(&self) -> usize {
+        self.dsu.num_components()
+    }
+
+    /// Returns the number of inserted keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no key has been inserted.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for HashDsu<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
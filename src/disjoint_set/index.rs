@@ -0,0 +1,103 @@
+/// A signed integer type used to store parent links in a DSU.
+///
+/// A non-negative value is the index of the parent node, and a negative value marks a root
+/// whose absolute value is the size of its set. Narrower types use less memory, while wider
+/// types support more elements.
+pub trait DsuIndex: Copy + Ord + std::ops::AddAssign + std::fmt::Debug {
+    /// The value stored for a root of a singleton set.
+    const NEG_ONE: Self;
+
+    /// The maximum number of elements that can be stored.
+    const MAX_LEN: usize;
+
+    /// Converts a node index into a parent link.
+    fn from_index(i: usize) -> Self;
+
+    /// Converts a parent link into a node index.
+    fn to_index(self) -> usize;
+
+    /// Returns `true` if the value marks a root.
+    fn is_negative(self) -> bool;
+
+    /// Converts the value stored at a root into the size of its set.
+    fn to_size(self) -> usize;
+}
+
+impl DsuIndex for i32 {
+    const NEG_ONE: Self = -1;
+    const MAX_LEN: usize = i32::MAX as usize;
+
+    #[inline(always)]
+    fn from_index(i: usize) -> Self {
+        i as i32
+    }
+
+    #[inline(always)]
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    #[inline(always)]
+    fn is_negative(self) -> bool {
+        self < 0
+    }
+
+    #[inline(always)]
+    fn to_size(self) -> usize {
+        (-self) as usize
+    }
+}
+
+impl DsuIndex for i64 {
+    const NEG_ONE: Self = -1;
+    const MAX_LEN: usize = if usize::BITS > 63 {
+        i64::MAX as usize
+    } else {
+        usize::MAX
+    };
+
+    #[inline(always)]
+    fn from_index(i: usize) -> Self {
+        i as i64
+    }
+
+    #[inline(always)]
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    #[inline(always)]
+    fn is_negative(self) -> bool {
+        self < 0
+    }
+
+    #[inline(always)]
+    fn to_size(self) -> usize {
+        (-self) as usize
+    }
+}
+
+impl DsuIndex for isize {
+    const NEG_ONE: Self = -1;
+    const MAX_LEN: usize = isize::MAX as usize;
+
+    #[inline(always)]
+    fn from_index(i: usize) -> Self {
+        i as isize
+    }
+
+    #[inline(always)]
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    #[inline(always)]
+    fn is_negative(self) -> bool {
+        self < 0
+    }
+
+    #[inline(always)]
+    fn to_size(self) -> usize {
+        (-self) as usize
+    }
+}
@@ -0,0 +1,37 @@
+use crate::disjoint_set::Dsu;
+
+/// Computes a minimum spanning forest of an undirected graph with Kruskal's algorithm.
+///
+/// `edges[i] = (w, u, v)` is an edge of weight `w` between `u` and `v`.
+/// Returns the total weight of the forest and the indices of the chosen edges in the
+/// order they were taken (non-decreasing weight, ties broken by index).
+///
+/// If the graph is disconnected, the result spans every connected component.
+///
+/// # Time complexity
+///
+/// O(m log m + n), where m is the number of edges
+///
+/// # Panics
+///
+/// Panics in debug mode if `n >= 2^31` or an endpoint is out of bounds
+pub fn kruskal<W>(n: usize, edges: &[(W, usize, usize)]) -> (W, Vec<usize>)
+where
+    W: Copy + Ord + std::iter::Sum,
+{
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by_key(|&i| edges[i].0);
+    let mut dsu = Dsu::new(n);
+    let mut chosen = Vec::with_capacity(n.saturating_sub(1));
+    for i in order {
+        let (_, u, v) = edges[i];
+        if dsu.unite(u, v) {
+            chosen.push(i);
+            if dsu.num_components() == 1 {
+                break;
+            }
+        }
+    }
+    let total = chosen.iter().map(|&i| edges[i].0).sum();
+    (total, chosen)
+}
@@ -0,0 +1,182 @@
+/// A set of indices `0..n` supporting "claim an index" and "find the next free index".
+///
+/// Each claimed index is united with its right neighbor, so the representative of an index
+/// is the smallest free index at or after it. This is the classic DSU trick used for range
+/// painting and interval scheduling, where every index is claimed at most once.
+///
+/// Uses path halving, achieving amortized O(log n) time per operation, and near O(1) in
+/// practice.
+#[derive(Clone, Debug)]
+pub struct NextFree {
+    /// `next[i] == i` if `i` is free, otherwise an index greater than `i` at or before the
+    /// next free index. `next[n] == n` is a sentinel that is never claimed.
+    next: Box<[usize]>,
+    num_free: usize,
+}
+
+impl NextFree {
+    /// Creates a new set of `n` indices, all initially free.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        Self {
+            next: (0..=n).collect(),
+            num_free: n,
+        }
+    }
+
+    /// Returns the smallest free index that is greater than or equal to `i`.
+    ///
+    /// If no such index exists, returns `len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > len()` in debug builds.
+    #[inline]
+    pub fn next_free(&mut self, mut i: usize) -> usize {
+        debug_assert!(
+            i <= self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len()
+        );
+        unsafe {
+            let p = self.next.as_mut_ptr();
+            while *p.add(i) != i {
+                let pi = *p.add(i);
+                *p.add(i) = *p.add(pi);
+                i = pi;
+            }
+        }
+        i
+    }
+
+    /// Marks index `i` as used.
+    ///
+    /// Returns `true` if `i` was free, `false` otherwise.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn claim(&mut self, i: usize) -> bool {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len()
+        );
+        if !self.is_free(i) {
+            return false;
+        }
+        unsafe {
+            *self.next.get_unchecked_mut(i) = i + 1;
+        }
+        self.num_free -= 1;
+        true
+    }
+
+    /// Claims every free index in the given range, calling `f(i)` for each newly claimed `i`
+    /// in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O((k + 1) log n), where k is the number of newly claimed indices
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn claim_range<F>(&mut self, range: impl std::ops::RangeBounds<usize>, mut f: F)
+    where
+        F: FnMut(usize),
+    {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let mut i = self.next_free(l);
+        while i < r {
+            self.claim(i);
+            f(i);
+            i = self.next_free(i + 1);
+        }
+    }
+
+    /// Returns `true` if index `i` has not been claimed.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn is_free(&self, i: usize) -> bool {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len()
+        );
+        unsafe { *self.next.get_unchecked(i) == i }
+    }
+
+    /// Returns the number of free indices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_free(&self) -> usize {
+        self.num_free
+    }
+
+    /// Returns the total number of indices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.next.len() - 1
+    }
+
+    /// Returns `true` if there are no indices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,29 @@
+/// Policy deciding which root becomes the representative when two sets are united.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Representative {
+    /// The root of the larger set is kept (union by size).
+    ///
+    /// Together with path compression, this gives amortized O(α(n)) time per operation.
+    #[default]
+    BySize,
+    /// The smaller root index is kept, so the representative of every set is its minimum
+    /// element.
+    ///
+    /// Relies on path compression alone, giving amortized O(log n) time per operation.
+    Min,
+}
+
+/// Strategy for shortening paths while searching for a root.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Every visited node is linked to its grandparent in a single pass.
+    #[default]
+    Halving,
+    /// Every visited node is linked directly to the root in a second pass.
+    Full,
+    /// Paths are never modified.
+    ///
+    /// Only sensible together with [`Representative::BySize`], which bounds the height of
+    /// every tree by O(log n).
+    None,
+}
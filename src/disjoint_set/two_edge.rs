@@ -0,0 +1,223 @@
+use crate::disjoint_set::Dsu;
+
+const NONE: usize = usize::MAX;
+
+/// An incremental structure maintaining connectivity and 2-edge-connectivity.
+///
+/// Edges are inserted online. Each 2-edge-connected component is contracted into a single
+/// node, and the bridges between them form a forest (the bridge tree). Inserting an edge
+/// between two trees links them by a new bridge, while inserting an edge inside a tree
+/// contracts the path between its endpoints, removing every bridge on that path.
+///
+/// Runs in amortized O(log n) time per insertion and O(α(n)) time per query.
+#[derive(Clone, Debug)]
+pub struct TwoEdgeDsu {
+    /// Connected components of the graph.
+    cc: Dsu,
+    /// 2-edge-connected components of the graph.
+    two_ecc: Dsu,
+    /// The bridge tree node representing each 2-edge-connected component, indexed by root.
+    node: Vec<usize>,
+    /// Parent of each bridge tree node, or `NONE` for the root of a tree.
+    /// Entries are only meaningful after being passed through `find_2ecc`.
+    parent: Vec<usize>,
+    /// Iteration at which each bridge tree node was last visited while searching for an LCA.
+    last_visit: Vec<usize>,
+    iteration: usize,
+    num_bridges: usize,
+}
+
+impl TwoEdgeDsu {
+    /// Creates a new structure with `n` vertices and no edges.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        Self {
+            cc: Dsu::new(n),
+            two_ecc: Dsu::new(n),
+            node: (0..n).collect(),
+            parent: vec![NONE; n],
+            last_visit: vec![0; n],
+            iteration: 0,
+            num_bridges: 0,
+        }
+    }
+
+    /// Adds an edge between `x` and `y`.
+    ///
+    /// Returns `true` if the edge is a new bridge, i.e. `x` and `y` were disconnected.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(log n)
+    pub fn add_edge(&mut self, x: usize, y: usize) -> bool {
+        debug_assert!(
+            x < self.len(),
+            "index out of bounds: x={}, len={}",
+            x,
+            self.len()
+        );
+        debug_assert!(
+            y < self.len(),
+            "index out of bounds: y={}, len={}",
+            y,
+            self.len()
+        );
+        let (mut a, mut b) = (self.find_2ecc(x), self.find_2ecc(y));
+        if a == b {
+            return false;
+        }
+        if self.cc.same(a, b) {
+            self.merge_path(a, b);
+            return false;
+        }
+        if self.cc.size(a) > self.cc.size(b) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.make_root(a);
+        self.parent[a] = b;
+        self.cc.unite(a, b);
+        self.num_bridges += 1;
+        true
+    }
+
+    /// Returns `true` if `x` and `y` are connected.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        self.cc.same(x, y)
+    }
+
+    /// Returns `true` if `x` and `y` belong to the same 2-edge-connected component,
+    /// i.e. they stay connected after removing any single edge.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn same_2ecc(&mut self, x: usize, y: usize) -> bool {
+        self.two_ecc.same(x, y)
+    }
+
+    /// Returns the number of vertices in the 2-edge-connected component containing `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(α(n))
+    #[inline]
+    pub fn size_2ecc(&mut self, x: usize) -> usize {
+        self.two_ecc.size(x)
+    }
+
+    /// Returns the number of bridges in the current graph.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_bridges(&self) -> usize {
+        self.num_bridges
+    }
+
+    /// Returns the number of connected components.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_components(&self) -> usize {
+        self.cc.num_components()
+    }
+
+    /// Returns the number of 2-edge-connected components.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn num_2ecc(&self) -> usize {
+        self.two_ecc.num_components()
+    }
+
+    /// Returns the number of vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Returns `true` if there are no vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the bridge tree node of the 2-edge-connected component containing `x`.
+    #[inline]
+    fn find_2ecc(&mut self, x: usize) -> usize {
+        let r = self.two_ecc.root(x);
+        self.node[r]
+    }
+
+    /// Re-roots the bridge tree containing node `v` at `v`.
+    fn make_root(&mut self, mut v: usize) {
+        let mut child = NONE;
+        while v != NONE {
+            let p = match self.parent[v] {
+                NONE => NONE,
+                p => self.find_2ecc(p),
+            };
+            self.parent[v] = child;
+            child = v;
+            v = p;
+        }
+    }
+
+    /// Contracts the bridge tree path between nodes `a` and `b` into their LCA.
+    fn merge_path(&mut self, mut a: usize, mut b: usize) {
+        self.iteration += 1;
+        let mut path_a = vec![];
+        let mut path_b = vec![];
+        let lca = loop {
+            if a != NONE {
+                a = self.find_2ecc(a);
+                path_a.push(a);
+                if self.last_visit[a] == self.iteration {
+                    break a;
+                }
+                self.last_visit[a] = self.iteration;
+                a = self.parent[a];
+            }
+            if b != NONE {
+                b = self.find_2ecc(b);
+                path_b.push(b);
+                if self.last_visit[b] == self.iteration {
+                    break b;
+                }
+                self.last_visit[b] = self.iteration;
+                b = self.parent[b];
+            }
+        };
+        // Either path may continue above the LCA, so stop at it.
+        let below_a = path_a.iter().take_while(|&&v| v != lca);
+        let below_b = path_b.iter().take_while(|&&v| v != lca);
+        for &v in below_a.chain(below_b) {
+            self.two_ecc.unite(v, lca);
+            self.num_bridges -= 1;
+        }
+        let r = self.two_ecc.root(lca);
+        self.node[r] = lca;
+    }
+}
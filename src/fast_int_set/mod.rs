@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Universes of at most this many bits are stored as a single bitmask.
+const LEAF_BITS: u32 = 6;
+
+/// A multiplicative hasher for the integer keys of the clusters.
+#[derive(Clone, Copy, Debug, Default)]
+struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_u64(b as u64);
+        }
+    }
+
+    #[inline(always)]
+    fn write_u64(&mut self, x: u64) {
+        self.0 = (self.0 ^ x)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            .rotate_left(26);
+    }
+}
+
+type Clusters = HashMap<u64, Veb, BuildHasherDefault<KeyHasher>>;
+
+/// A non-empty van Emde Boas tree over a universe of `2^bits` values, where `bits` is
+/// passed down by the caller.
+#[derive(Clone, Debug)]
+enum Veb {
+    /// The values of a universe of at most `2^LEAF_BITS` values, as a bitmask.
+    Leaf(u64),
+    Node(Box<Inner>),
+}
+
+/// A van Emde Boas tree over more than `2^LEAF_BITS` values.
+///
+/// Each value other than `min` is split into its high and low halves of bits, and its low
+/// half is stored in the cluster of its high half.
+#[derive(Clone, Debug)]
+struct Inner {
+    /// The minimum, which is not stored in any cluster.
+    min: u64,
+    max: u64,
+    /// The high halves of the non-empty clusters, if any.
+    summary: Option<Veb>,
+    clusters: Clusters,
+}
+
+/// Returns the numbers of high and low bits of a value of `bits` bits.
+#[inline(always)]
+fn halves(bits: u32) -> (u32, u32) {
+    (bits - bits / 2, bits / 2)
+}
+
+impl Veb {
+    /// Returns the tree containing only `x`.
+    fn singleton(bits: u32, x: u64) -> Self {
+        if bits <= LEAF_BITS {
+            Veb::Leaf(1 << x)
+        } else {
+            Veb::Node(Box::new(Inner {
+                min: x,
+                max: x,
+                summary: None,
+                clusters: Clusters::default(),
+            }))
+        }
+    }
+
+    #[inline]
+    fn min(&self) -> u64 {
+        match self {
+            Veb::Leaf(m) => m.trailing_zeros() as u64,
+            Veb::Node(n) => n.min,
+        }
+    }
+
+    #[inline]
+    fn max(&self) -> u64 {
+        match self {
+            Veb::Leaf(m) => 63 - m.leading_zeros() as u64,
+            Veb::Node(n) => n.max,
+        }
+    }
+
+    fn contains(&self, bits: u32, x: u64) -> bool {
+        match self {
+            Veb::Leaf(m) => m >> x & 1 == 1,
+            Veb::Node(n) => {
+                if x == n.min || x == n.max {
+                    return true;
+                }
+                let lo = halves(bits).1;
+                n.clusters
+                    .get(&(x >> lo))
+                    .is_some_and(|c| c.contains(lo, x & ((1 << lo) - 1)))
+            }
+        }
+    }
+
+    /// Inserts `x`, which is not in the tree.
+    fn insert(&mut self, bits: u32, mut x: u64) {
+        match self {
+            Veb::Leaf(m) => *m |= 1 << x,
+            Veb::Node(n) => {
+                if x < n.min {
+                    std::mem::swap(&mut x, &mut n.min);
+                }
+                n.max = n.max.max(x);
+                let (hi, lo) = halves(bits);
+                let (h, l) = (x >> lo, x & ((1 << lo) - 1));
+                match n.clusters.get_mut(&h) {
+                    Some(c) => c.insert(lo, l),
+                    None => {
+                        n.clusters.insert(h, Veb::singleton(lo, l));
+                        match &mut n.summary {
+                            Some(s) => s.insert(hi, h),
+                            None => n.summary = Some(Veb::singleton(hi, h)),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes `x`, which is in the tree, and returns `true` if the tree becomes empty.
+    fn remove(&mut self, bits: u32, mut x: u64) -> bool {
+        match self {
+            Veb::Leaf(m) => {
+                *m &= !(1 << x);
+                *m == 0
+            }
+            Veb::Node(n) => {
+                let Some(s) = &n.summary else {
+                    return true;
+                };
+                let (hi, lo) = halves(bits);
+                if x == n.min {
+                    // Promote the smallest value in the clusters to the minimum.
+                    let h = s.min();
+                    x = h << lo | n.clusters[&h].min();
+                    n.min = x;
+                }
+                let (h, l) = (x >> lo, x & ((1 << lo) - 1));
+                if n.clusters.get_mut(&h).unwrap().remove(lo, l) {
+                    n.clusters.remove(&h);
+                    if n.summary.as_mut().unwrap().remove(hi, h) {
+                        n.summary = None;
+                    }
+                }
+                if x == n.max {
+                    n.max = match &n.summary {
+                        Some(s) => {
+                            let h = s.max();
+                            h << lo | n.clusters[&h].max()
+                        }
+                        None => n.min,
+                    };
+                }
+                false
+            }
+        }
+    }
+
+    /// Returns the smallest value greater than `x`, if any.
+    fn successor(&self, bits: u32, x: u64) -> Option<u64> {
+        match self {
+            Veb::Leaf(m) => {
+                let m = m & (!0 << x << 1);
+                (m != 0).then(|| m.trailing_zeros() as u64)
+            }
+            Veb::Node(n) => {
+                if x < n.min {
+                    return Some(n.min);
+                }
+                let (hi, lo) = halves(bits);
+                let (h, l) = (x >> lo, x & ((1 << lo) - 1));
+                if let Some(c) = n.clusters.get(&h)
+                    && l < c.max()
+                {
+                    return Some(h << lo | c.successor(lo, l).unwrap());
+                }
+                let h = n.summary.as_ref()?.successor(hi, h)?;
+                Some(h << lo | n.clusters[&h].min())
+            }
+        }
+    }
+
+    /// Returns the largest value less than `x`, if any.
+    fn predecessor(&self, bits: u32, x: u64) -> Option<u64> {
+        match self {
+            Veb::Leaf(m) => {
+                let m = m & ((1 << x) - 1);
+                (m != 0).then(|| 63 - m.leading_zeros() as u64)
+            }
+            Veb::Node(n) => {
+                if x > n.max {
+                    return Some(n.max);
+                }
+                let (hi, lo) = halves(bits);
+                let (h, l) = (x >> lo, x & ((1 << lo) - 1));
+                if let Some(c) = n.clusters.get(&h)
+                    && c.min() < l
+                {
+                    return Some(h << lo | c.predecessor(lo, l).unwrap());
+                }
+                match n.summary.as_ref().and_then(|s| s.predecessor(hi, h)) {
+                    Some(h) => Some(h << lo | n.clusters[&h].max()),
+                    None => (n.min < x).then_some(n.min),
+                }
+            }
+        }
+    }
+}
+
+/// A set of integers supporting successor and predecessor queries.
+///
+/// Given values less than `2^bits`, this data structure supports:
+/// - Update: `insert(x)` and `remove(x)`
+/// - Membership: `contains(x)`
+/// - Neighbors: `successor(x)` and `predecessor(x)` return the nearest values greater than
+///   and less than `x`
+///
+/// All operations run in O(log bits) expected time, that is O(log log U) for a universe of
+/// `U` values, and the set uses O(n) space. This is a van Emde Boas tree whose clusters are
+/// allocated on demand in hash maps, with universes of at most 64 values stored as bitmasks.
+/// It is fastest on dense sets, where most lookups end in a few bitmasks, and may be slower
+/// than [`BTreeSet`](std::collections::BTreeSet) on sparse sets over large universes.
+#[derive(Clone)]
+pub struct FastIntSet {
+    root: Option<Veb>,
+    bits: u32,
+    len: usize,
+}
+
+impl FastIntSet {
+    /// Creates a new empty set of values less than `2^bits`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`.
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= 64, "bits must be at most 64: bits={}", bits);
+        Self {
+            root: None,
+            bits,
+            len: 0,
+        }
+    }
+
+    /// Inserts `x`, and returns `true` if it was not present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log bits) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn insert(&mut self, x: u64) -> bool {
+        if self.contains(x) {
+            return false;
+        }
+        match &mut self.root {
+            Some(root) => root.insert(self.bits, x),
+            None => self.root = Some(Veb::singleton(self.bits, x)),
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Removes `x`, and returns `true` if it was present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log bits) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    pub fn remove(&mut self, x: u64) -> bool {
+        if !self.contains(x) {
+            return false;
+        }
+        if self.root.as_mut().unwrap().remove(self.bits, x) {
+            self.root = None;
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Returns `true` if the set contains `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log bits) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn contains(&self, x: u64) -> bool {
+        self.check(x);
+        self.root.as_ref().is_some_and(|r| r.contains(self.bits, x))
+    }
+
+    /// Returns the smallest value greater than `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log bits) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn successor(&self, x: u64) -> Option<u64> {
+        self.check(x);
+        self.root.as_ref()?.successor(self.bits, x)
+    }
+
+    /// Returns the largest value less than `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log bits) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= 2^bits` in debug builds.
+    #[inline]
+    pub fn predecessor(&self, x: u64) -> Option<u64> {
+        self.check(x);
+        self.root.as_ref()?.predecessor(self.bits, x)
+    }
+
+    /// Returns the smallest value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn min(&self) -> Option<u64> {
+        self.root.as_ref().map(Veb::min)
+    }
+
+    /// Returns the largest value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn max(&self) -> Option<u64> {
+        self.root.as_ref().map(Veb::max)
+    }
+
+    /// Returns an iterator over the values, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log bits) expected
+    pub fn iter(&self) -> impl Iterator<Item = u64> {
+        std::iter::successors(self.min(), |&x| self.successor(x))
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn check(&self, x: u64) {
+        debug_assert!(
+            self.bits == 64 || x >> self.bits == 0,
+            "value out of bounds: x={}, bits={}",
+            x,
+            self.bits,
+        );
+    }
+}
+
+impl std::fmt::Debug for FastIntSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,269 @@
+use crate::algebra::Group;
+
+/// Returns the half-open range of `range`, for indices below `n`.
+#[inline]
+fn bounds(range: impl std::ops::RangeBounds<usize>, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        std::ops::Bound::Unbounded => 0,
+        std::ops::Bound::Included(&x) => x,
+        std::ops::Bound::Excluded(&x) => x + 1,
+    };
+    let r = match range.end_bound() {
+        std::ops::Bound::Unbounded => n,
+        std::ops::Bound::Included(&x) => x + 1,
+        std::ops::Bound::Excluded(&x) => x,
+    };
+    debug_assert!(
+        l <= r,
+        "left bound must be less than or equal to right bound: l={}, r={}",
+        l,
+        r,
+    );
+    debug_assert!(r <= n, "index out of bounds: r={}, len={}", r, n);
+    (l, r)
+}
+
+/// A static table of 2D prefix folds, for folds over rectangles in constant time.
+///
+/// Given a commutative group `(S, op, id, inv)` and an `n` by `m` grid `a`, this data
+/// structure supports:
+/// - Prefix query: `prefix_fold(i, j)` returns `op` of `a[x][y]` over `x < i` and `y < j`
+/// - Rectangle query: `rect_fold(il..ir, jl..jr)` returns `op` of `a[x][y]` over
+///   `il <= x < ir` and `jl <= y < jr`
+///
+/// The folds of the `(n + 1) * (m + 1)` prefix rectangles are built in O(nm) time, and a
+/// rectangle is the fold of four of them by inclusion-exclusion, which is why `op` must be
+/// commutative.
+#[derive(Clone, Debug)]
+pub struct PrefixSum2D<S: Group> {
+    /// Row-major folds of the prefix rectangles, with an extra first row and column of
+    /// `S::id()`.
+    prefix: Vec<S>,
+    n: usize,
+    m: usize,
+}
+
+impl<S: Group> PrefixSum2D<S> {
+    /// Creates a new table from the rows of a grid.
+    ///
+    /// # Time complexity
+    ///
+    /// O(nm)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows have different lengths in debug builds.
+    pub fn from_slice<R: AsRef<[S]>>(a: &[R]) -> Self {
+        let n = a.len();
+        let m = a.first().map_or(0, |row| row.as_ref().len());
+        let w = m + 1;
+        let mut prefix = vec![S::id(); (n + 1) * w];
+        for (i, row) in a.iter().enumerate() {
+            let row = row.as_ref();
+            debug_assert_eq!(
+                row.len(),
+                m,
+                "rows of different lengths: row {} has length {}, expected {}",
+                i,
+                row.len(),
+                m,
+            );
+            let mut acc = S::id();
+            for (j, x) in row.iter().enumerate() {
+                acc = S::op(&acc, x);
+                prefix[(i + 1) * w + j + 1] = S::op(&prefix[i * w + j + 1], &acc);
+            }
+        }
+        Self { prefix, n, m }
+    }
+
+    /// Returns `op` of `a[x][y]` over `x < i` and `y < j`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > rows()` or `j > cols()` in debug builds.
+    #[inline]
+    pub fn prefix_fold(&self, i: usize, j: usize) -> S {
+        debug_assert!(i <= self.n, "index out of bounds: i={}, rows={}", i, self.n);
+        debug_assert!(j <= self.m, "index out of bounds: j={}, cols={}", j, self.m);
+        self.prefix[i * (self.m + 1) + j].clone()
+    }
+
+    /// Returns `op` of `a[x][y]` over the rows `x` in `rows` and the columns `y` in `cols`.
+    ///
+    /// Returns `S::id()` if the rectangle is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn rect_fold(
+        &self,
+        rows: impl std::ops::RangeBounds<usize>,
+        cols: impl std::ops::RangeBounds<usize>,
+    ) -> S {
+        let (il, ir) = bounds(rows, self.n);
+        let (jl, jr) = bounds(cols, self.m);
+        let w = self.m + 1;
+        let p = &self.prefix;
+        let add = S::op(&p[ir * w + jr], &p[il * w + jl]);
+        let sub = S::op(&p[il * w + jr], &p[ir * w + jl]);
+        S::op(&add, &sub.inv())
+    }
+
+    /// Returns the number of rows `n`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn rows(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the number of columns `m`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn cols(&self) -> usize {
+        self.m
+    }
+
+    /// Returns `true` if the grid has no cells.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.n == 0 || self.m == 0
+    }
+}
+
+/// A 2D difference grid, for applying operations to rectangles before reading every cell.
+///
+/// Given a commutative group `(S, op, id, inv)` and an `n` by `m` grid `a` initialized to
+/// `S::id()`, this data structure supports:
+/// - Rectangle operation: `rect_operate(il..ir, jl..jr, x)` sets `a[i][j] = op(a[i][j], x)`
+///   for every `il <= i < ir` and `jl <= j < jr`
+/// - Materialization: `materialize()` returns the grid `a`
+///
+/// An operation stores `x` and its inverse at the four corners of the rectangle, in O(1)
+/// time, so that every cell of `a` is the 2D prefix fold of the corners, computed for all
+/// cells at once in O(nm) time (the imos method).
+#[derive(Clone, Debug)]
+pub struct Diff2D<S: Group> {
+    /// Row-major corners, with an extra last row and column.
+    diff: Vec<S>,
+    n: usize,
+    m: usize,
+}
+
+impl<S: Group> Diff2D<S> {
+    /// Creates a new `n` by `m` grid, with every cell initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(nm)
+    pub fn new(n: usize, m: usize) -> Self {
+        Self {
+            diff: vec![S::id(); (n + 1) * (m + 1)],
+            n,
+            m,
+        }
+    }
+
+    /// Applies `op(a[i][j], x)` to every cell with the row `i` in `rows` and the column `j`
+    /// in `cols`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is invalid or out of bounds in debug builds.
+    pub fn rect_operate(
+        &mut self,
+        rows: impl std::ops::RangeBounds<usize>,
+        cols: impl std::ops::RangeBounds<usize>,
+        x: S,
+    ) {
+        let (il, ir) = bounds(rows, self.n);
+        let (jl, jr) = bounds(cols, self.m);
+        if il == ir || jl == jr {
+            return;
+        }
+        let w = self.m + 1;
+        let inv = x.inv();
+        let d = &mut self.diff;
+        d[il * w + jl] = S::op(&d[il * w + jl], &x);
+        d[il * w + jr] = S::op(&d[il * w + jr], &inv);
+        d[ir * w + jl] = S::op(&d[ir * w + jl], &inv);
+        d[ir * w + jr] = S::op(&d[ir * w + jr], &x);
+    }
+
+    /// Returns the rows of the grid `a`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(nm)
+    pub fn materialize(&self) -> Vec<Vec<S>> {
+        let w = self.m + 1;
+        let mut res: Vec<Vec<S>> = Vec::with_capacity(self.n);
+        for i in 0..self.n {
+            let mut acc = S::id();
+            let row = (0..self.m)
+                .map(|j| {
+                    acc = S::op(&acc, &self.diff[i * w + j]);
+                    match res.last() {
+                        Some(prev) => S::op(&prev[j], &acc),
+                        None => acc.clone(),
+                    }
+                })
+                .collect();
+            res.push(row);
+        }
+        res
+    }
+
+    /// Returns the number of rows `n`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn rows(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the number of columns `m`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn cols(&self) -> usize {
+        self.m
+    }
+
+    /// Returns `true` if the grid has no cells.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.n == 0 || self.m == 0
+    }
+}
@@ -0,0 +1,230 @@
+use super::hash_pair;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A probabilistic set answering membership queries with one-sided error.
+///
+/// Given values of a hashable type `T`, this data structure supports:
+/// - Insertion: `insert(&x)`
+/// - Membership: `may_contain(&x)` returns `true` for every inserted `x`, and `false` for
+///   other values except with a small false positive rate
+/// - Set operations: `union_with(&other)` and `intersect_with(&other)` for filters with the
+///   same parameters
+///
+/// Every value sets `k` bits among `m`, at the positions `h1 + i * h2` for `i` in `0..k`,
+/// reduced to `0..m`, where `h1` and `h2` are derived from a single hash of the value by
+/// double hashing. After inserting `n` values, a value is wrongly reported with probability
+/// about `(1 - e^(-kn/m))^k`. [`BloomFilter::new`] picks `m` and `k` minimizing the bits for
+/// a target rate, about 1.44 log2(1/p) bits per value. All operations run in O(k) time, and
+/// values cannot be removed.
+///
+/// Values are hashed by [`DefaultHasher::new`](std::hash::DefaultHasher::new), which is the
+/// same for every filter and every run of a given build, so that filters with the same
+/// parameters can be combined.
+pub struct BloomFilter<T: ?Sized> {
+    words: Box<[u64]>,
+    /// Number of bits `m`.
+    bits: usize,
+    /// Number of bits `k` set by every value.
+    hashes: u32,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    /// Creates a new empty filter for about `n` values with false positive rate `p`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log(1/p))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not in the open interval `(0, 1)` in debug builds.
+    pub fn new(n: usize, p: f64) -> Self {
+        debug_assert!(
+            0.0 < p && p < 1.0,
+            "false positive rate out of bounds: p={}",
+            p,
+        );
+        let ln2 = std::f64::consts::LN_2;
+        let n = n.max(1) as f64;
+        let bits = (-n * p.ln() / (ln2 * ln2)).ceil().max(1.0);
+        let hashes = (bits / n * ln2).round().max(1.0);
+        Self::with_params(bits as usize, hashes as u32)
+    }
+
+    /// Creates a new empty filter of `bits` bits, setting `hashes` bits for every value.
+    ///
+    /// # Time complexity
+    ///
+    /// O(bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` or `hashes` is zero in debug builds.
+    pub fn with_params(bits: usize, hashes: u32) -> Self {
+        debug_assert!(bits > 0, "the number of bits must be positive");
+        debug_assert!(hashes > 0, "the number of hashes must be positive");
+        Self {
+            words: vec![0; bits.div_ceil(64)].into_boxed_slice(),
+            bits,
+            hashes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k)
+    pub fn insert(&mut self, x: &T) {
+        let (mut h, step) = hash_pair(x);
+        for _ in 0..self.hashes {
+            let i = self.reduce(h);
+            self.words[i >> 6] |= 1 << (i & 63);
+            h = h.wrapping_add(step);
+        }
+    }
+
+    /// Returns `false` if `x` was never inserted, and `true` if it may have been.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k)
+    pub fn may_contain(&self, x: &T) -> bool {
+        let (mut h, step) = hash_pair(x);
+        (0..self.hashes).all(|_| {
+            let i = self.reduce(h);
+            h = h.wrapping_add(step);
+            (self.words[i >> 6] >> (i & 63)) & 1 == 1
+        })
+    }
+
+    /// Adds the values of `other`, so that the filter reports every value of either.
+    ///
+    /// The result is the same as inserting the values of both into one filter.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have different parameters in debug builds.
+    pub fn union_with(&mut self, other: &Self) {
+        self.check_params(other);
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Keeps the bits set in both filters, so that the filter reports every value of both.
+    ///
+    /// The result may report more values than a filter of the common values alone.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have different parameters in debug builds.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.check_params(other);
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    /// Removes all values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Returns an estimate of the number of distinct values inserted, from the number of set
+    /// bits.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn estimated_len(&self) -> f64 {
+        let (m, k) = (self.bits as f64, self.hashes as f64);
+        let ones = self.count_ones() as f64;
+        -m / k * (1.0 - ones / m).ln()
+    }
+
+    /// Returns the probability that a value never inserted is reported, given the bits set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn false_positive_rate(&self) -> f64 {
+        (self.count_ones() as f64 / self.bits as f64).powi(self.hashes as i32)
+    }
+
+    /// Returns the number of bits `m`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn num_bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Returns the number of bits `k` set by every value.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn num_hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// Maps a hash to a bit position, by multiplying with `m` and keeping the high word.
+    #[inline(always)]
+    fn reduce(&self, h: u64) -> usize {
+        ((h as u128 * self.bits as u128) >> 64) as usize
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn check_params(&self, other: &Self) {
+        debug_assert!(
+            self.bits == other.bits && self.hashes == other.hashes,
+            "filters with different parameters: bits={}, hashes={}, other bits={}, other hashes={}",
+            self.bits,
+            self.hashes,
+            other.bits,
+            other.hashes,
+        );
+    }
+}
+
+impl<T: ?Sized> Clone for BloomFilter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            bits: self.bits,
+            hashes: self.hashes,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> std::fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("bits", &self.bits)
+            .field("hashes", &self.hashes)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,251 @@
+use super::hash_pair;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Number of fingerprints in a bucket.
+const SLOTS: usize = 4;
+
+/// Number of fingerprints moved before an insertion gives up.
+const MAX_KICKS: usize = 500;
+
+/// An empty slot; fingerprints are never zero.
+const EMPTY: u16 = 0;
+
+/// A probabilistic multiset answering membership queries with one-sided error, supporting
+/// removal.
+///
+/// Given values of a hashable type `T`, this data structure supports:
+/// - Insertion and deletion: `insert(&x)` and `remove(&x)`
+/// - Membership: `may_contain(&x)` returns `true` for every inserted `x` not removed since,
+///   and `false` for other values except with a false positive rate below `2^-12`
+///
+/// Every value is stored as a 16-bit fingerprint in one of two buckets of 4 slots, the
+/// second bucket being the first one xor a hash of the fingerprint, so that either is found
+/// from the other and the fingerprint alone. A value whose buckets are full evicts a random
+/// fingerprint of one of them to its other bucket, and so on, giving a load of about 95%
+/// before insertions fail. A value is wrongly reported when one of the 8 slots of its
+/// buckets holds its fingerprint, with probability at most `8 / 65535`. This takes about 17
+/// bits per value, where a [`BloomFilter`](super::BloomFilter) with the same rate takes about
+/// 19. Membership and removal run in O(1) time, and insertion in O(1) amortized expected
+/// time.
+///
+/// Inserting a value again stores another copy, up to 8 times, and removing a value never
+/// inserted may remove a colliding value instead.
+pub struct CuckooFilter<T: ?Sized> {
+    buckets: Box<[[u16; SLOTS]]>,
+    /// A fingerprint and one of its buckets, evicted by the last failed insertion.
+    victim: Option<(usize, u16)>,
+    len: usize,
+    /// State of the xorshift generator choosing the fingerprints to evict.
+    rng: u64,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CuckooFilter<T> {
+    /// Creates a new empty filter with room for at least `capacity` values, and usually
+    /// about 5% more.
+    ///
+    /// # Time complexity
+    ///
+    /// O(capacity)
+    pub fn new(capacity: usize) -> Self {
+        let buckets = (capacity.div_ceil(SLOTS) * 100 / 95 + 1).next_power_of_two();
+        Self {
+            buckets: vec![[EMPTY; SLOTS]; buckets].into_boxed_slice(),
+            victim: None,
+            len: 0,
+            rng: 0x2545_f491_4f6c_dd1d,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts `x`, and returns `false` if the filter is full.
+    ///
+    /// The filter is full after an insertion fails, which stores `x` but evicts another
+    /// fingerprint into a single spare slot, until removals make room for it.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized expected
+    pub fn insert(&mut self, x: &T) -> bool {
+        if self.victim.is_some() {
+            return false;
+        }
+        let (i, fp) = self.locate(x);
+        self.len += 1;
+        self.store(i, fp)
+    }
+
+    /// Returns `false` if `x` is not in the filter, and `true` if it may be.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn may_contain(&self, x: &T) -> bool {
+        let (i, fp) = self.locate(x);
+        let j = self.alt(i, fp);
+        self.buckets[i].contains(&fp)
+            || self.buckets[j].contains(&fp)
+            || self
+                .victim
+                .is_some_and(|(k, v)| v == fp && (k == i || k == j))
+    }
+
+    /// Removes a copy of `x`, and returns `true` if one was found.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn remove(&mut self, x: &T) -> bool {
+        let (i, fp) = self.locate(x);
+        let j = self.alt(i, fp);
+        if let Some((k, v)) = self.victim
+            && v == fp
+            && (k == i || k == j)
+        {
+            self.victim = None;
+        } else if let Some(s) = [i, j]
+            .into_iter()
+            .find_map(|b| Some((b, self.buckets[b].iter().position(|&v| v == fp)?)))
+        {
+            self.buckets[s.0][s.1] = EMPTY;
+            // The freed slot may make room for the evicted fingerprint.
+            if let Some((k, v)) = self.victim.take() {
+                self.store(k, v);
+            }
+        } else {
+            return false;
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Removes all values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(capacity)
+    pub fn clear(&mut self) {
+        self.buckets.fill([EMPTY; SLOTS]);
+        self.victim = None;
+        self.len = 0;
+    }
+
+    /// Returns `true` if an insertion failed and no value was removed since.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.victim.is_some()
+    }
+
+    /// Returns the number of slots, the largest possible number of values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * SLOTS
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the filter is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the first bucket and the fingerprint of `x`.
+    #[inline]
+    fn locate(&self, x: &T) -> (usize, u16) {
+        let (a, b) = hash_pair(x);
+        let fp = ((b >> 48) as u16).max(1);
+        (a as usize & (self.buckets.len() - 1), fp)
+    }
+
+    /// Returns the other bucket of the fingerprint `fp` stored in bucket `i`.
+    #[inline]
+    fn alt(&self, i: usize, fp: u16) -> usize {
+        let h = (fp as u64)
+            .wrapping_mul(0xc6a4_a793_5bd1_e995)
+            .rotate_left(32);
+        (i ^ h as usize) & (self.buckets.len() - 1)
+    }
+
+    /// Stores `fp` in bucket `i` or its other bucket, evicting fingerprints to their other
+    /// buckets if both are full, and returns `false` if one is left over as the victim.
+    fn store(&mut self, i: usize, fp: u16) -> bool {
+        if self.put(i, fp) || self.put(self.alt(i, fp), fp) {
+            return true;
+        }
+        let (mut i, mut fp) = (
+            if self.rng & 1 == 0 {
+                i
+            } else {
+                self.alt(i, fp)
+            },
+            fp,
+        );
+        for _ in 0..MAX_KICKS {
+            self.rng ^= self.rng << 13;
+            self.rng ^= self.rng >> 7;
+            self.rng ^= self.rng << 17;
+            std::mem::swap(&mut fp, &mut self.buckets[i][self.rng as usize % SLOTS]);
+            i = self.alt(i, fp);
+            if self.put(i, fp) {
+                return true;
+            }
+        }
+        self.victim = Some((i, fp));
+        false
+    }
+
+    /// Stores `fp` in an empty slot of bucket `i`, and returns `false` if there is none.
+    #[inline]
+    fn put(&mut self, i: usize, fp: u16) -> bool {
+        match self.buckets[i].iter_mut().find(|v| **v == EMPTY) {
+            Some(v) => {
+                *v = fp;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for CuckooFilter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buckets: self.buckets.clone(),
+            victim: self.victim,
+            len: self.len,
+            rng: self.rng,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> std::fmt::Debug for CuckooFilter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CuckooFilter")
+            .field("len", &self.len)
+            .field("capacity", &(self.buckets.len() * SLOTS))
+            .finish_non_exhaustive()
+    }
+}
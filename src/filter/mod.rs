@@ -0,0 +1,20 @@
+pub mod bloom;
+pub mod cuckoo;
+
+pub use bloom::BloomFilter;
+pub use cuckoo::CuckooFilter;
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Returns two hashes of `x`, the second odd and well mixed from the first.
+#[inline]
+fn hash_pair<T: Hash + ?Sized>(x: &T) -> (u64, u64) {
+    let mut h = DefaultHasher::new();
+    x.hash(&mut h);
+    let a = h.finish();
+    // The finalizer of splitmix64, a bijection mixing every bit of `a`.
+    let mut b = a.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    b = (b ^ (b >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    b = (b ^ (b >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (a, (b ^ (b >> 31)) | 1)
+}
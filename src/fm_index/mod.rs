@@ -0,0 +1,171 @@
+use crate::bit_vector::RankSelectBitVec;
+use crate::suffix_array::suffix_array;
+use crate::wavelet_matrix::WaveletMatrix;
+
+/// An FM-index, a compressed full-text index of a string.
+///
+/// Given a string `s` of length `n` over a totally ordered type `T`, this data structure
+/// supports:
+/// - Counting: `count(p)` returns the number of occurrences of `p` in `s`
+/// - Locating: `locate(p)` returns the starts of the occurrences of `p` in `s`
+///
+/// The index stores the Burrows–Wheeler transform of `s` in a [`WaveletMatrix`], so that a
+/// backward search counts the occurrences of a pattern of length `m` in O(m log σ) time, for
+/// an alphabet of size `σ`. Every `rate`-th position of the suffix array is sampled and
+/// marked in a bit vector, and every other one is found by at most `rate - 1` steps of the
+/// LF mapping, so `locate` runs in O(m log σ + occ · rate · log σ) time for `occ`
+/// occurrences.
+///
+/// Built in O(n log n) time through a suffix array, which is not kept: the index takes about
+/// `n log σ + n + 64 n / rate` bits.
+#[derive(Clone, Debug)]
+pub struct FmIndex<T> {
+    /// Distinct symbols of `s` in ascending order; symbol `i` is encoded as `i + 1`.
+    alphabet: Vec<T>,
+    /// Burrows–Wheeler transform of the encoded `s` followed by the sentinel 0.
+    bwt: WaveletMatrix,
+    /// `c[x]` is the number of encoded symbols less than `x`, the sentinel included.
+    c: Vec<usize>,
+    /// Marks the suffix array indices whose position is sampled.
+    sampled: RankSelectBitVec,
+    /// Sampled positions, in order of their suffix array index.
+    samples: Vec<usize>,
+}
+
+impl<T: Ord + Clone> FmIndex<T> {
+    /// Creates the FM-index of `s`, sampling every 32nd position.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn new(s: &[T]) -> Self {
+        Self::with_sample_rate(s, 32)
+    }
+
+    /// Creates the FM-index of `s`, sampling every `rate`-th position.
+    ///
+    /// A larger rate takes less space and makes `locate` slower.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate == 0`.
+    pub fn with_sample_rate(s: &[T], rate: usize) -> Self {
+        assert!(rate > 0, "sample rate must be positive: rate={}", rate);
+        let mut alphabet = s.to_vec();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        let mut text: Vec<usize> = s
+            .iter()
+            .map(|x| alphabet.binary_search(x).unwrap() + 1)
+            .collect();
+        text.push(0);
+        let n = text.len();
+        let sa = suffix_array(&text, alphabet.len() + 1);
+        let bwt: Vec<u64> = sa
+            .iter()
+            .map(|&p| text[if p == 0 { n - 1 } else { p - 1 }] as u64)
+            .collect();
+        let mut c = vec![0; alphabet.len() + 2];
+        for &x in &text {
+            c[x + 1] += 1;
+        }
+        for x in 0..=alphabet.len() {
+            c[x + 1] += c[x];
+        }
+        let sampled = RankSelectBitVec::from_bits(sa.iter().map(|&p| p % rate == 0));
+        let samples = sa.into_iter().filter(|&p| p % rate == 0).collect();
+        Self {
+            alphabet,
+            bwt: WaveletMatrix::from_vec(bwt),
+            c,
+            sampled,
+            samples,
+        }
+    }
+
+    /// Returns the number of occurrences of `pattern`.
+    ///
+    /// The empty pattern occurs `n + 1` times, at every position.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn count(&self, pattern: &[T]) -> usize {
+        let (l, r) = self.backward_search(pattern);
+        r - l
+    }
+
+    /// Returns `true` if `pattern` occurs in the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.count(pattern) > 0
+    }
+
+    /// Returns the starts of the occurrences of `pattern`, in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ + occ · rate · log σ)
+    pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+        let (l, r) = self.backward_search(pattern);
+        (l..r).map(|i| self.position(i)).collect()
+    }
+
+    /// Returns the length of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bwt.len() - 1
+    }
+
+    /// Returns `true` if the string is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the range of suffix array indices of the suffixes starting with `pattern`.
+    fn backward_search(&self, pattern: &[T]) -> (usize, usize) {
+        let (mut l, mut r) = (0, self.bwt.len());
+        for x in pattern.iter().rev() {
+            let Ok(x) = self.alphabet.binary_search(x) else {
+                return (0, 0);
+            };
+            let x = x + 1;
+            l = self.c[x] + self.bwt.rank(x as u64, l);
+            r = self.c[x] + self.bwt.rank(x as u64, r);
+            if l == r {
+                return (0, 0);
+            }
+        }
+        (l, r)
+    }
+
+    /// Returns the position of the suffix at suffix array index `i`.
+    fn position(&self, mut i: usize) -> usize {
+        let mut steps = 0;
+        while !self.sampled.get(i) {
+            // LF mapping: the index of the suffix one position to the left.
+            let x = self.bwt.get(i);
+            i = self.c[x as usize] + self.bwt.rank(x, i);
+            steps += 1;
+        }
+        self.samples[self.sampled.rank1(i)] + steps
+    }
+}
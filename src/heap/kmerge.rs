@@ -0,0 +1,106 @@
+/// Lazily merges sorted iterators by a binary heap of their next elements.
+struct KMergeBy<I: Iterator, F> {
+    /// Next element of every nonempty iterator, with its index, as a binary heap by `less`.
+    heap: Vec<(I::Item, usize)>,
+    iters: Vec<I>,
+    less: F,
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> bool> KMergeBy<I, F> {
+    /// Returns `true` if `a` comes before `b`, breaking ties by the index of the iterator.
+    #[inline(always)]
+    fn before(&mut self, a: usize, b: usize) -> bool {
+        let (x, i) = &self.heap[a];
+        let (y, j) = &self.heap[b];
+        (self.less)(x, y) || (!(self.less)(y, x) && i < j)
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let (l, r) = (2 * i + 1, 2 * i + 2);
+            let mut first = i;
+            if l < self.heap.len() && self.before(l, first) {
+                first = l;
+            }
+            if r < self.heap.len() && self.before(r, first) {
+                first = r;
+            }
+            if first == i {
+                break;
+            }
+            self.heap.swap(i, first);
+            i = first;
+        }
+    }
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> bool> Iterator for KMergeBy<I, F> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let i = self.heap.first()?.1;
+        let res = match self.iters[i].next() {
+            Some(x) => std::mem::replace(&mut self.heap[0].0, x),
+            None => self.heap.swap_remove(0).0,
+        };
+        self.sift_down(0);
+        Some(res)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter().map(|it| it.size_hint()).fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(l, r), (a, b)| {
+                (
+                    l.saturating_add(a),
+                    r.zip(b).and_then(|(r, b)| r.checked_add(b)),
+                )
+            },
+        )
+    }
+}
+
+/// Returns an iterator over the elements of the given iterators, each sorted in ascending
+/// order, in ascending order.
+///
+/// Equal elements are returned in the order of their iterators, so the merge is stable.
+///
+/// # Time complexity
+///
+/// O(k) to start, and O(log k) per element, for `k` iterators
+pub fn kmerge<I>(iters: I) -> impl Iterator<Item = <I::Item as IntoIterator>::Item>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    <I::Item as IntoIterator>::Item: Ord,
+{
+    kmerge_by(iters, |a, b| a < b)
+}
+
+/// Returns an iterator over the elements of the given iterators, each sorted by `less`, in
+/// the order of `less`.
+///
+/// `less(a, b)` returns `true` if `a` comes strictly before `b`. Equal elements are returned
+/// in the order of their iterators, so the merge is stable.
+///
+/// # Time complexity
+///
+/// O(k) to start, and O(log k) calls to `less` per element, for `k` iterators
+pub fn kmerge_by<I, F>(iters: I, less: F) -> impl Iterator<Item = <I::Item as IntoIterator>::Item>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    F: FnMut(&<I::Item as IntoIterator>::Item, &<I::Item as IntoIterator>::Item) -> bool,
+{
+    let mut iters: Vec<_> = iters.into_iter().map(IntoIterator::into_iter).collect();
+    let heap = iters
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, it)| it.next().map(|x| (x, i)))
+        .collect::<Vec<_>>();
+    let mut res = KMergeBy { heap, iters, less };
+    for i in (0..res.heap.len() / 2).rev() {
+        res.sift_down(i);
+    }
+    res
+}
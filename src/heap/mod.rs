@@ -0,0 +1,11 @@
+pub mod kmerge;
+pub mod pairing;
+pub mod persistent;
+pub mod radix;
+pub mod removable;
+
+pub use kmerge::{kmerge, kmerge_by};
+pub use pairing::PairingHeap;
+pub use persistent::PersistentLeftistHeap;
+pub use radix::RadixHeap;
+pub use removable::{Heap, RemovableHeap};
@@ -0,0 +1,185 @@
+/// A node of a pairing heap, in the left-child right-sibling representation.
+struct Node<T> {
+    value: T,
+    /// First child, whose siblings are the other children.
+    child: Option<Box<Node<T>>>,
+    /// Next sibling.
+    sibling: Option<Box<Node<T>>>,
+}
+
+/// Melds two trees without siblings into one, with the larger root on top.
+fn meld<T: Ord>(mut a: Box<Node<T>>, mut b: Box<Node<T>>) -> Box<Node<T>> {
+    if a.value < b.value {
+        std::mem::swap(&mut a, &mut b);
+    }
+    b.sibling = a.child.take();
+    a.child = Some(b);
+    a
+}
+
+/// A meldable max-heap.
+///
+/// Given elements of a totally ordered type `T`, this data structure supports:
+/// - Insertion: `push(x)` inserts `x` in O(1) time
+/// - Meld: `meld(other)` moves all elements of `other` into this heap in O(1) time
+/// - Extraction: `pop()` removes and returns the largest element in O(log n) amortized time
+///
+/// Like [`BinaryHeap`](std::collections::BinaryHeap), this is a max-heap; wrap the elements in
+/// [`Reverse`](std::cmp::Reverse) for a min-heap. Unlike it, melding does not reinsert the
+/// elements of the other heap, so merging the heaps of components, e.g. small-to-large, costs
+/// O(1) per merge.
+pub struct PairingHeap<T> {
+    root: Option<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Creates a new empty heap.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Inserts `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn push(&mut self, x: T) {
+        let node = Box::new(Node {
+            value: x,
+            child: None,
+            sibling: None,
+        });
+        self.root = Some(match self.root.take() {
+            Some(root) => meld(root, node),
+            None => node,
+        });
+        self.len += 1;
+    }
+
+    /// Moves all elements of `other` into this heap.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn meld(&mut self, mut other: Self) {
+        self.len += std::mem::take(&mut other.len);
+        self.root = match (self.root.take(), other.root.take()) {
+            (Some(a), Some(b)) => Some(meld(a, b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Removes and returns the largest element, or `None` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    pub fn pop(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        self.len -= 1;
+        // Meld the children in pairs from left to right, then the pairs from right to left.
+        let mut pairs = Vec::new();
+        let mut next = root.child.take();
+        while let Some(mut a) = next {
+            next = a.sibling.take();
+            pairs.push(match next {
+                Some(mut b) => {
+                    next = b.sibling.take();
+                    meld(a, b)
+                }
+                None => a,
+            });
+        }
+        self.root = pairs.into_iter().rev().reduce(|acc, t| meld(t, acc));
+        Some(root.value)
+    }
+
+    /// Returns the largest element, or `None` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|root| &root.value)
+    }
+
+    /// Returns an iterator over the elements in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) for a full traversal
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut stack: Vec<&Node<T>> = self.root.as_deref().into_iter().collect();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.sibling.as_deref());
+            stack.extend(node.child.as_deref());
+            Some(&node.value)
+        })
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for PairingHeap<T> {
+    /// Drops the nodes iteratively, as the sibling lists can be as long as the heap.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.child.take());
+            stack.extend(node.sibling.take());
+        }
+    }
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PairingHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl<T: Ord> Extend<T> for PairingHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<T: Ord + std::fmt::Debug> std::fmt::Debug for PairingHeap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
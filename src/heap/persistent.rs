@@ -0,0 +1,218 @@
+use std::rc::Rc;
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+/// An immutable node of a leftist heap, shared between versions.
+struct Node<T> {
+    value: T,
+    /// Length of the right spine of the subtree, which is at most that of the left one.
+    rank: u32,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+#[inline(always)]
+fn rank<T>(t: &Link<T>) -> u32 {
+    t.as_ref().map_or(0, |t| t.rank)
+}
+
+#[inline(always)]
+fn size<T>(t: &Link<T>) -> usize {
+    t.as_ref().map_or(0, |t| t.size)
+}
+
+/// Melds two heaps along their right spines, copying the O(log n) nodes on them.
+fn meld<T: Ord + Clone>(a: &Link<T>, b: &Link<T>) -> Link<T> {
+    let (x, y) = match (a, b) {
+        (None, b) => return b.clone(),
+        (a, None) => return a.clone(),
+        (Some(x), Some(y)) if x.value < y.value => (y, x),
+        (Some(x), Some(y)) => (x, y),
+    };
+    let mut left = x.left.clone();
+    let mut right = meld(&x.right, &Some(y.clone()));
+    if rank(&left) < rank(&right) {
+        std::mem::swap(&mut left, &mut right);
+    }
+    Some(Rc::new(Node {
+        value: x.value.clone(),
+        rank: rank(&right) + 1,
+        size: 1 + size(&left) + size(&right),
+        left,
+        right,
+    }))
+}
+
+/// A persistent meldable max-heap.
+///
+/// Every update leaves the heap unchanged and returns a new version, so that all versions
+/// remain available. Given elements of a totally ordered type `T`, this data structure
+/// supports:
+/// - Insertion: `push(x)` returns a new version with `x` inserted
+/// - Meld: `meld(other)` returns a new version with the elements of both heaps
+/// - Extraction: `peek()` returns the largest element, and `pop()` returns a new version
+///   without it
+///
+/// All updates run in O(log n) time, and cloning a version in O(1) time. This is a leftist
+/// heap of reference-counted nodes: an update copies the O(log n) nodes on the right spines
+/// it melds and shares the rest with the previous versions, which is what algorithms built
+/// on persistent heaps, such as Eppstein's k shortest paths, need. Wrap the elements in
+/// [`Reverse`](std::cmp::Reverse) for a min-heap.
+pub struct PersistentLeftistHeap<T> {
+    root: Link<T>,
+}
+
+impl<T> Clone for PersistentLeftistHeap<T> {
+    /// Returns the same version, sharing every node.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> PersistentLeftistHeap<T> {
+    /// Creates a new empty heap.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns a new version with `x` inserted.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[must_use]
+    pub fn push(&self, x: T) -> Self {
+        let node = Some(Rc::new(Node {
+            value: x,
+            rank: 1,
+            size: 1,
+            left: None,
+            right: None,
+        }));
+        Self {
+            root: meld(&self.root, &node),
+        }
+    }
+
+    /// Returns a new version with the elements of both `self` and `other`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[must_use]
+    pub fn meld(&self, other: &Self) -> Self {
+        Self {
+            root: meld(&self.root, &other.root),
+        }
+    }
+
+    /// Returns a new version with the largest element removed, or an empty heap if the heap
+    /// is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[must_use]
+    pub fn pop(&self) -> Self {
+        Self {
+            root: self
+                .root
+                .as_ref()
+                .and_then(|root| meld(&root.left, &root.right)),
+        }
+    }
+
+    /// Returns the largest element, or `None` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|root| &root.value)
+    }
+
+    /// Returns an iterator over the elements in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) for a full traversal
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut stack: Vec<&Node<T>> = self.root.as_deref().into_iter().collect();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.right.as_deref());
+            stack.extend(node.left.as_deref());
+            Some(&node.value)
+        })
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns `true` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for PersistentLeftistHeap<T> {
+    /// Drops the nodes no longer shared iteratively, as the left spines can be as long as the
+    /// heap.
+    fn drop(&mut self) {
+        let mut stack: Vec<Rc<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(t) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(t) {
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentLeftistHeap<T> {
+    /// Creates a new empty heap.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for PersistentLeftistHeap<T> {
+    /// Creates a new heap from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), |heap, x| heap.push(x))
+    }
+}
+
+impl<T: Ord + Clone + std::fmt::Debug> std::fmt::Debug for PersistentLeftistHeap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
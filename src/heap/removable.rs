@@ -0,0 +1,186 @@
+use crate::heap::PairingHeap;
+use std::collections::BinaryHeap;
+
+/// A max-heap, for writing code generically over the heaps of this crate and the standard
+/// library.
+///
+/// The trait methods forward to the inherent methods of the same name.
+pub trait Heap<T> {
+    /// Inserts `x`.
+    fn push(&mut self, x: T);
+
+    /// Removes and returns the largest element, or `None` if the heap is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns the largest element, or `None` if the heap is empty.
+    fn peek(&self) -> Option<&T>;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the heap is empty.
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Implements [`Heap`] by forwarding to the inherent methods.
+macro_rules! impl_heap {
+    ($t:ident) => {
+        impl<T: Ord> Heap<T> for $t<T> {
+            #[inline(always)]
+            fn push(&mut self, x: T) {
+                $t::push(self, x)
+            }
+
+            #[inline(always)]
+            fn pop(&mut self) -> Option<T> {
+                $t::pop(self)
+            }
+
+            #[inline(always)]
+            fn peek(&self) -> Option<&T> {
+                $t::peek(self)
+            }
+
+            #[inline(always)]
+            fn len(&self) -> usize {
+                $t::len(self)
+            }
+        }
+    };
+}
+
+impl_heap!(BinaryHeap);
+impl_heap!(PairingHeap);
+
+/// A max-heap supporting the removal of arbitrary elements.
+///
+/// Given a [`Heap`] `H` of elements of type `T`, this data structure supports:
+/// - Insertion: `push(x)` inserts `x`
+/// - Deletion: `remove(x)` removes one occurrence of `x`, which must be present
+/// - Extraction: `peek()` and `pop()` return the largest element
+///
+/// Removed elements are pushed to a second heap of the same type, and discarded from the
+/// main heap when they reach its top at the same time as in the second heap. Every operation
+/// runs in O(log n) amortized time of `H`, where `n` counts the removed elements not yet
+/// discarded, turning any heap into a multiset supporting maximum queries.
+///
+/// Wrap the elements in [`Reverse`](std::cmp::Reverse) for a min-heap.
+#[derive(Clone, Debug)]
+pub struct RemovableHeap<T, H = BinaryHeap<T>> {
+    heap: H,
+    /// Removed elements still in `heap`, whose top is never the top of `heap`.
+    removed: H,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Ord, H: Heap<T> + Default> RemovableHeap<T, H> {
+    /// Creates a new empty heap.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            heap: H::default(),
+            removed: H::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Inserts `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    #[inline]
+    pub fn push(&mut self, x: T) {
+        self.heap.push(x);
+        self.discard();
+    }
+
+    /// Removes one occurrence of `x`, which must be present.
+    ///
+    /// Removing an element that is not present leaves the heap in an unspecified state.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    #[inline]
+    pub fn remove(&mut self, x: T) {
+        self.removed.push(x);
+        self.discard();
+    }
+
+    /// Removes and returns the largest element, or `None` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let res = self.heap.pop();
+        self.discard();
+        res
+    }
+
+    /// Returns the largest element, or `None` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.heap.len() - self.removed.len()
+    }
+
+    /// Returns `true` if the heap is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Discards the removed elements at the top of the heap.
+    fn discard(&mut self) {
+        while let (Some(x), Some(y)) = (self.heap.peek(), self.removed.peek())
+            && x == y
+        {
+            self.heap.pop();
+            self.removed.pop();
+        }
+    }
+}
+
+impl<T: Ord, H: Heap<T> + Default> Default for RemovableHeap<T, H> {
+    /// Creates a new empty heap.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, H: Heap<T> + Default> FromIterator<T> for RemovableHeap<T, H> {
+    /// Creates a new heap from an iterator.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.heap.push(x);
+        }
+        res
+    }
+}
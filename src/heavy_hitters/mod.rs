@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A deterministic summary of the most frequent values of a stream.
+///
+/// Given a stream of values of a hashable type `T`, and a number `k` of counters, this data
+/// structure supports:
+/// - Add: `add(x)` counts one occurrence of `x`
+/// - Estimate: `estimate(&x)` returns a count `c` such that the true count of `x` lies in
+///   `c..=c + error()`
+/// - Heavy hitters: `candidates(t)` returns every value occurring at least `t` times, and
+///   possibly values occurring at least `t - error()` times
+/// - Merge: `merge(other)` combines the summaries of two streams
+///
+/// This is the Misra–Gries summary. At most `k` values have counters; a value without one
+/// gets a counter of 1 if one is free, and otherwise every counter is decremented, dropping
+/// those reaching zero. Each decrement round cancels `k + 1` occurrences, so that
+/// `error() <= n / (k + 1)` after `n` occurrences, and every value occurring more than
+/// `n / (k + 1)` times keeps a counter. Additions run in O(1) amortized expected time, as
+/// a round takes O(k) time and removes `k` earlier additions from the counters.
+#[derive(Clone)]
+pub struct HeavyHitters<T> {
+    counters: HashMap<T, u64>,
+    k: usize,
+    /// Number of occurrences counted.
+    total: u64,
+    /// Amount subtracted from every counter so far.
+    error: u64,
+}
+
+impl<T: Hash + Eq> HeavyHitters<T> {
+    /// Creates a new empty summary with `k` counters.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k == 0` in debug builds.
+    pub fn new(k: usize) -> Self {
+        debug_assert!(k > 0, "the number of counters must be positive");
+        Self {
+            counters: HashMap::with_capacity(k + 1),
+            k,
+            total: 0,
+            error: 0,
+        }
+    }
+
+    /// Counts one occurrence of `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized expected
+    pub fn add(&mut self, x: T) {
+        self.total += 1;
+        if let Some(c) = self.counters.get_mut(&x) {
+            *c += 1;
+        } else if self.counters.len() < self.k {
+            self.counters.insert(x, 1);
+        } else {
+            self.error += 1;
+            self.counters.retain(|_, c| {
+                *c -= 1;
+                *c > 0
+            });
+        }
+    }
+
+    /// Returns a lower bound `c` on the count of `x`, whose true count lies in
+    /// `c..=c + error()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) expected
+    #[inline]
+    pub fn estimate(&self, x: &T) -> u64 {
+        self.counters.get(x).copied().unwrap_or(0)
+    }
+
+    /// Returns the values whose count may be at least `threshold`, with their estimates, in
+    /// descending order of estimates.
+    ///
+    /// Every value occurring at least `threshold` times is returned if `threshold > error()`,
+    /// which holds for `threshold > total() / (k + 1)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k log k)
+    pub fn candidates(&self, threshold: u64) -> Vec<(&T, u64)> {
+        let mut res: Vec<_> = self
+            .counters
+            .iter()
+            .filter(|&(_, &c)| c + self.error >= threshold)
+            .map(|(x, &c)| (x, c))
+            .collect();
+        res.sort_unstable_by_key(|&(_, c)| std::cmp::Reverse(c));
+        res
+    }
+
+    /// Adds the counts of `other`, so that the summary covers both streams with the error
+    /// bound `error() <= total() / (k + 1)` still holding.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if the summaries have different numbers of counters in debug builds.
+    pub fn merge(&mut self, other: Self) {
+        debug_assert_eq!(
+            self.k, other.k,
+            "summaries with different numbers of counters: k={}, other k={}",
+            self.k, other.k,
+        );
+        self.total += other.total;
+        self.error += other.error;
+        for (x, c) in other.counters {
+            *self.counters.entry(x).or_insert(0) += c;
+        }
+        if self.counters.len() > self.k {
+            // Subtract the `k + 1`-th largest count, leaving at most `k` counters.
+            let mut counts: Vec<u64> = self.counters.values().copied().collect();
+            let (_, &mut d, _) = counts.select_nth_unstable_by(self.k, |a, b| b.cmp(a));
+            self.error += d;
+            self.counters.retain(|_, c| {
+                *c = c.saturating_sub(d);
+                *c > 0
+            });
+        }
+    }
+
+    /// Returns the largest possible undercount of an estimate.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn error(&self) -> u64 {
+        self.error
+    }
+
+    /// Returns the number of occurrences counted.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns an iterator over the values with a counter and their estimates, in arbitrary
+    /// order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) per item
+    pub fn iter(&self) -> impl Iterator<Item = (&T, u64)> {
+        self.counters.iter().map(|(x, &c)| (x, c))
+    }
+
+    /// Returns the number of counters `k`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the number of values with a counter.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns `true` if no value has a counter.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for HeavyHitters<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.add(x);
+        }
+    }
+}
+
+impl<T: Hash + Eq + std::fmt::Debug> std::fmt::Debug for HeavyHitters<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
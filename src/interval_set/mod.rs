@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A set of values stored as disjoint half-open intervals.
+///
+/// Given values of a totally ordered type `T`, this data structure supports:
+/// - Update: `insert(l..r)` and `remove(l..r)` add or remove every value in `l..r`
+/// - Point query: `covering(x)` returns the interval containing `x`, and `mex(x)` returns
+///   the smallest value at least `x` not in the set
+/// - Range query: `overlaps(l..r)` and `gaps(l..r)` iterate over the intervals and the
+///   missing intervals intersecting `l..r`
+///
+/// Intervals that overlap or touch are merged, so the intervals are always disjoint and
+/// separated by at least one missing value. The intervals are kept in a [`BTreeMap`] from
+/// start to end, so an update runs in O((k + 1) log n) time, where `k` is the number of
+/// intervals it destroys. Every update creates at most two intervals, so `k` is O(1)
+/// amortized. Queries run in O(log n) time, plus O(1) per interval iterated.
+///
+/// [`insert_with`](Self::insert_with) and [`remove_with`](Self::remove_with) report every
+/// interval they destroy and create, so that values attached to the intervals can be kept
+/// up to date, as in the technique known as the Chtholly tree.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    /// The end of every interval, keyed by its start.
+    map: BTreeMap<T, T>,
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+    /// Creates a new empty set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Adds every value in `range`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    #[inline]
+    pub fn insert(&mut self, range: Range<T>) {
+        self.insert_with(range, |_, _| {}, |_, _| {});
+    }
+
+    /// Adds every value in `range`, and calls `on_remove(l, r)` for every interval `l..r`
+    /// merged away, then `on_insert(l, r)` for the merged interval.
+    ///
+    /// Does nothing if every value in `range` is already in the set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized, plus the calls
+    pub fn insert_with<F, G>(&mut self, range: Range<T>, mut on_remove: F, mut on_insert: G)
+    where
+        F: FnMut(T, T),
+        G: FnMut(T, T),
+    {
+        let Range {
+            start: mut l,
+            end: mut r,
+        } = range;
+        if r <= l {
+            return;
+        }
+        if let Some((&a, &b)) = self.map.range(..=l).next_back() {
+            if r <= b {
+                return;
+            }
+            if l <= b {
+                self.map.remove(&a);
+                on_remove(a, b);
+                l = a;
+            }
+        }
+        while let Some((&a, &b)) = self.map.range(l..=r).next() {
+            self.map.remove(&a);
+            on_remove(a, b);
+            r = r.max(b);
+        }
+        self.map.insert(l, r);
+        on_insert(l, r);
+    }
+
+    /// Removes every value in `range`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized
+    #[inline]
+    pub fn remove(&mut self, range: Range<T>) {
+        self.remove_with(range, |_, _| {}, |_, _| {});
+    }
+
+    /// Removes every value in `range`, and calls `on_remove(l, r)` for every interval `l..r`
+    /// intersecting `range`, then `on_insert(l, r)` for each of the at most two remaining
+    /// parts of these intervals.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) amortized, plus the calls
+    pub fn remove_with<F, G>(&mut self, range: Range<T>, mut on_remove: F, mut on_insert: G)
+    where
+        F: FnMut(T, T),
+        G: FnMut(T, T),
+    {
+        let Range { start: l, end: r } = range;
+        if r <= l {
+            return;
+        }
+        let mut parts = [None, None];
+        if let Some((&a, &b)) = self.map.range(..l).next_back()
+            && l < b
+        {
+            self.map.remove(&a);
+            on_remove(a, b);
+            parts = [Some((a, l)), (r < b).then_some((r, b))];
+        }
+        while let Some((&a, &b)) = self.map.range(l..r).next() {
+            self.map.remove(&a);
+            on_remove(a, b);
+            if r < b {
+                parts[1] = Some((r, b));
+            }
+        }
+        for (a, b) in parts.into_iter().flatten() {
+            self.map.insert(a, b);
+            on_insert(a, b);
+        }
+    }
+
+    /// Returns the interval containing `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn covering(&self, x: T) -> Option<Range<T>> {
+        let (&a, &b) = self.map.range(..=x).next_back()?;
+        (x < b).then_some(a..b)
+    }
+
+    /// Returns `true` if the set contains `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn contains(&self, x: T) -> bool {
+        self.covering(x).is_some()
+    }
+
+    /// Returns the smallest value at least `x` not in the set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn mex(&self, x: T) -> T {
+        self.covering(x).map_or(x, |c| c.end)
+    }
+
+    /// Returns an iterator over the intervals intersecting `range`, in ascending order.
+    ///
+    /// The intervals are returned whole, and may extend past `range`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n), plus O(1) amortized per interval
+    pub fn overlaps(&self, range: Range<T>) -> impl Iterator<Item = Range<T>> {
+        let Range { start: l, end: r } = range;
+        let first = self
+            .map
+            .range(..l)
+            .next_back()
+            .filter(|&(_, &b)| l < b && l < r);
+        let rest = self.map.range(l..r.max(l));
+        first.into_iter().chain(rest).map(|(&a, &b)| a..b)
+    }
+
+    /// Returns an iterator over the maximal intervals of values in `range` not in the set,
+    /// in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n), plus O(1) amortized per interval
+    pub fn gaps(&self, range: Range<T>) -> impl Iterator<Item = Range<T>> {
+        let Range { start: l, end: r } = range;
+        let mut overlaps = self.overlaps(l..r);
+        // The start of the next gap, or `None` once the range is exhausted.
+        let mut cur = (l < r).then_some(l);
+        std::iter::from_fn(move || {
+            loop {
+                let c = cur?;
+                match overlaps.next() {
+                    Some(Range { start: a, end: b }) => {
+                        cur = (b < r).then_some(b);
+                        if c < a {
+                            return Some(c..a);
+                        }
+                    }
+                    None => {
+                        cur = None;
+                        return Some(c..r);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over the intervals, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> {
+        self.map.iter().map(|(&a, &b)| a..b)
+    }
+
+    /// Returns the number of intervals.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for IntervalSet<T> {
+    /// Creates a new set with every value of the given ranges.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for range in iter {
+            res.insert(range);
+        }
+        res
+    }
+}
+
+impl<T: Ord + Copy + std::fmt::Debug> std::fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
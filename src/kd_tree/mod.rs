@@ -0,0 +1,267 @@
+use crate::num::Numeric;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A point of a k-d tree, splitting its subtree along `axis`.
+#[derive(Clone, Debug)]
+struct Node<T, const D: usize> {
+    point: [T; D],
+    /// Index of the point in the input.
+    index: usize,
+    axis: usize,
+}
+
+/// Returns `|a - b|`, also for unsigned types.
+#[inline(always)]
+fn abs_diff<T: Numeric>(a: T, b: T) -> T {
+    if a < b { b - a } else { a - b }
+}
+
+/// Returns the squared Euclidean distance between `a` and `b`.
+#[inline]
+fn dist2<T: Numeric, const D: usize>(a: &[T; D], b: &[T; D]) -> T {
+    let mut res = T::ZERO;
+    for i in 0..D {
+        let d = abs_diff(a[i], b[i]);
+        res = res + d * d;
+    }
+    res
+}
+
+/// A candidate neighbor, ordered by distance.
+struct Candidate<T>(T, usize);
+
+impl<T: PartialOrd> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for Candidate<T> {}
+
+impl<T: PartialOrd> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// A static k-d tree over points in `D` dimensions, for nearest neighbor and box queries.
+///
+/// Given points with coordinates of a primitive numeric type `T`, this data structure
+/// supports:
+/// - Nearest neighbors: `nearest(&p)` and `k_nearest(&p, k)` return the points closest to
+///   `p` in Euclidean distance
+/// - Box query: `range(&lo, &hi)` returns the points `q` with `lo[d] <= q[d] <= hi[d]` in
+///   every dimension `d`
+///
+/// Points are identified by their index in the input, and distances are returned squared, in
+/// `T`, so that integer coordinates are exact as long as `D` times the squared coordinate
+/// range fits in `T`. Coordinates must not be NaN.
+///
+/// The tree is stored implicitly in one array: the subtree of a range of the array is rooted
+/// at its middle point, which is the median of the range along the dimension in which the
+/// range is widest, and the points before and after it form the two children. Built in
+/// O(n log n) time. A nearest neighbor query visits O(log n) nodes on evenly spread points,
+/// and a box query with `m` results visits O(n^(1 - 1/D) + m) nodes in the worst case.
+#[derive(Clone, Debug)]
+pub struct KdTree<T, const D: usize> {
+    nodes: Box<[Node<T, D>]>,
+}
+
+impl<T: Numeric, const D: usize> KdTree<T, D> {
+    /// Creates a new tree from a vec of points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_vec(v: Vec<[T; D]>) -> Self {
+        let mut nodes: Vec<_> = v
+            .into_iter()
+            .enumerate()
+            .map(|(index, point)| Node {
+                point,
+                index,
+                axis: 0,
+            })
+            .collect();
+        Self::build(&mut nodes);
+        Self {
+            nodes: nodes.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new tree from a slice of points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_slice(v: &[[T; D]]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Returns the index of a point closest to `p` and its squared distance, or `None` if
+    /// the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) on evenly spread points
+    #[inline]
+    pub fn nearest(&self, p: &[T; D]) -> Option<(usize, T)> {
+        self.k_nearest(p, 1).pop()
+    }
+
+    /// Returns the indices of the `min(k, n)` points closest to `p` and their squared
+    /// distances, in ascending order of distance, ties broken arbitrarily.
+    ///
+    /// # Time complexity
+    ///
+    /// O((k + log n) log k) on evenly spread points
+    pub fn k_nearest(&self, p: &[T; D], k: usize) -> Vec<(usize, T)> {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_rec(0, self.nodes.len(), p, k, &mut heap);
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Candidate(d, i)| (i, d))
+            .collect()
+    }
+
+    /// Returns an iterator over the indices of the points `q` with `lo[d] <= q[d] <= hi[d]` in
+    /// every dimension `d`, in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n^(1 - 1/D) + m) for `m` results
+    pub fn range(&self, lo: &[T; D], hi: &[T; D]) -> impl Iterator<Item = usize> {
+        let (lo, hi) = (*lo, *hi);
+        let mut stack = vec![(0, self.nodes.len())];
+        std::iter::from_fn(move || {
+            while let Some((l, r)) = stack.pop() {
+                if l == r {
+                    continue;
+                }
+                let m = (l + r) >> 1;
+                let node = &self.nodes[m];
+                let a = node.axis;
+                if lo[a] <= node.point[a] {
+                    stack.push((l, m));
+                }
+                if node.point[a] <= hi[a] {
+                    stack.push((m + 1, r));
+                }
+                if (0..D).all(|d| lo[d] <= node.point[d] && node.point[d] <= hi[d]) {
+                    return Some(node.index);
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns the number of points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Arranges `nodes` as a subtree, rooted at its middle.
+    fn build(nodes: &mut [Node<T, D>]) {
+        if nodes.len() <= 1 {
+            return;
+        }
+        let mut lo = nodes[0].point;
+        let mut hi = lo;
+        for node in nodes.iter() {
+            for d in 0..D {
+                if node.point[d] < lo[d] {
+                    lo[d] = node.point[d];
+                }
+                if hi[d] < node.point[d] {
+                    hi[d] = node.point[d];
+                }
+            }
+        }
+        let axis = (0..D)
+            .max_by(|&a, &b| {
+                (hi[a] - lo[a])
+                    .partial_cmp(&(hi[b] - lo[b]))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap_or(0);
+        let m = nodes.len() >> 1;
+        nodes.select_nth_unstable_by(m, |x, y| {
+            x.point[axis]
+                .partial_cmp(&y.point[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        nodes[m].axis = axis;
+        let (left, right) = nodes.split_at_mut(m);
+        Self::build(left);
+        Self::build(&mut right[1..]);
+    }
+
+    /// Offers the points of the subtree of `l..r` to `heap`, holding the `k` closest so far.
+    fn nearest_rec(
+        &self,
+        l: usize,
+        r: usize,
+        p: &[T; D],
+        k: usize,
+        heap: &mut BinaryHeap<Candidate<T>>,
+    ) {
+        if l == r {
+            return;
+        }
+        let m = (l + r) >> 1;
+        let node = &self.nodes[m];
+        let d = dist2(&node.point, p);
+        if heap.len() < k {
+            heap.push(Candidate(d, node.index));
+        } else if d < heap.peek().unwrap().0 {
+            *heap.peek_mut().unwrap() = Candidate(d, node.index);
+        }
+        let a = node.axis;
+        let (near, far) = if p[a] < node.point[a] {
+            ((l, m), (m + 1, r))
+        } else {
+            ((m + 1, r), (l, m))
+        };
+        self.nearest_rec(near.0, near.1, p, k, heap);
+        // The far side is at least as far as the splitting plane.
+        let plane = abs_diff(p[a], node.point[a]);
+        if heap.len() < k || plane * plane < heap.peek().unwrap().0 {
+            self.nearest_rec(far.0, far.1, p, k, heap);
+        }
+    }
+}
+
+impl<T: Numeric, const D: usize> FromIterator<[T; D]> for KdTree<T, D> {
+    /// Creates a new tree from an iterator of points, indexed in iteration order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = [T; D]>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
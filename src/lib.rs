@@ -1,3 +1,40 @@
+pub mod aho_corasick;
+pub mod algebra;
+pub mod binary_trie;
+pub mod bit_vector;
 pub mod disjoint_set;
+pub mod fast_int_set;
 pub mod fenwick_tree;
+pub mod filter;
+pub mod fm_index;
+pub mod heap;
+pub mod heavy_hitters;
+pub mod interval_set;
+pub mod kd_tree;
+pub mod mo;
+pub mod monoids;
+pub mod monotone_deque;
+pub mod num;
+pub mod order_maintenance;
+pub mod order_stat_set;
+pub mod quad_tree;
+pub mod r_tree;
+pub mod range_kth;
+pub mod range_query;
+pub mod reservoir;
+pub mod retroactive;
+pub mod ring_buffer;
+pub mod running_median;
 pub mod segment_tree;
+pub mod skip_list;
+pub mod sorted_list;
+pub mod sparse_table;
+pub mod sqrt_decomposition;
+pub mod sqrt_tree;
+pub mod strings;
+pub mod suffix_array;
+pub mod suffix_automaton;
+pub mod treap;
+pub mod tree;
+pub mod trie;
+pub mod wavelet_matrix;
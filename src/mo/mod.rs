@@ -0,0 +1,210 @@
+/// The state maintained over a sliding window `l..r` by [`MoSolver`].
+///
+/// The window starts empty, and is moved by adding or removing one element at either end.
+/// Windows whose state does not depend on the side an element is added or removed at only
+/// need [`add`](Self::add) and [`remove`](Self::remove).
+pub trait MoState {
+    /// The answer to a query.
+    type Answer;
+
+    /// Adds element `i` to the window.
+    fn add(&mut self, i: usize);
+
+    /// Removes element `i` from the window.
+    fn remove(&mut self, i: usize);
+
+    /// Returns the answer for the current window.
+    fn answer(&self) -> Self::Answer;
+
+    /// Adds element `i` at the left end of the window.
+    #[inline(always)]
+    fn add_left(&mut self, i: usize) {
+        self.add(i);
+    }
+
+    /// Adds element `i` at the right end of the window.
+    #[inline(always)]
+    fn add_right(&mut self, i: usize) {
+        self.add(i);
+    }
+
+    /// Removes element `i` from the left end of the window.
+    #[inline(always)]
+    fn remove_left(&mut self, i: usize) {
+        self.remove(i);
+    }
+
+    /// Removes element `i` from the right end of the window.
+    #[inline(always)]
+    fn remove_right(&mut self, i: usize) {
+        self.remove(i);
+    }
+}
+
+/// An offline driver for Mo's algorithm.
+///
+/// Given `q` range queries over a sequence of length `n`, and a [`MoState`] that can move its
+/// window by one element, this answers every query by visiting them in an order that keeps
+/// the total movement of the window small:
+/// - Block order, by [`solve`](Self::solve): queries are sorted by the block of `l`, and by
+///   `r` within a block, alternating direction between blocks. The window moves O(n √q)
+///   times in total.
+/// - Hilbert order, by [`solve_hilbert`](Self::solve_hilbert): queries are sorted along a
+///   Hilbert curve through the `(l, r)` plane, with the same bound. Which order moves the
+///   window less depends on how the queries are distributed.
+///
+/// This complements the online range structures of this crate for queries that cannot be
+/// expressed as a monoid fold, such as the number of distinct values in a range.
+#[derive(Clone, Debug)]
+pub struct MoSolver {
+    n: usize,
+    queries: Vec<(usize, usize)>,
+}
+
+impl MoSolver {
+    /// Creates a new solver for queries over a sequence of length `n`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            queries: Vec::new(),
+        }
+    }
+
+    /// Adds a query for the given range, and returns its index among the answers.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn add_query(&mut self, range: impl std::ops::RangeBounds<usize>) -> usize {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.n,
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(r <= self.n, "index out of bounds: r={}, len={}", r, self.n);
+        self.queries.push((l, r));
+        self.queries.len() - 1
+    }
+
+    /// Answers every query in block order, and returns the answers in the order the queries
+    /// were added.
+    ///
+    /// # Time complexity
+    ///
+    /// O(q log q + n √q) calls to the state
+    pub fn solve<M: MoState>(&self, state: &mut M) -> Vec<M::Answer> {
+        let q = self.queries.len();
+        let block = (self.n / q.max(1).isqrt()).max(1);
+        let mut order: Vec<usize> = (0..q).collect();
+        order.sort_by_key(|&i| {
+            let (l, r) = self.queries[i];
+            let b = l / block;
+            (b, if b & 1 == 0 { r } else { usize::MAX - r })
+        });
+        self.run(state, &order)
+    }
+
+    /// Answers every query in Hilbert curve order, and returns the answers in the order the
+    /// queries were added.
+    ///
+    /// # Time complexity
+    ///
+    /// O(q log q + n √q) calls to the state
+    pub fn solve_hilbert<M: MoState>(&self, state: &mut M) -> Vec<M::Answer> {
+        let log = usize::BITS - self.n.leading_zeros();
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_cached_key(|&i| {
+            let (l, r) = self.queries[i];
+            hilbert(l as u64, r as u64, log)
+        });
+        self.run(state, &order)
+    }
+
+    /// Returns the number of queries.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Returns `true` if there are no queries.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Answers the queries in the given order.
+    fn run<M: MoState>(&self, state: &mut M, order: &[usize]) -> Vec<M::Answer> {
+        let mut answers: Vec<Option<M::Answer>> = (0..order.len()).map(|_| None).collect();
+        let (mut cl, mut cr) = (0, 0);
+        for &i in order {
+            let (l, r) = self.queries[i];
+            // Grow the window before shrinking it, so that it never becomes inverted.
+            while l < cl {
+                cl -= 1;
+                state.add_left(cl);
+            }
+            while cr < r {
+                state.add_right(cr);
+                cr += 1;
+            }
+            while cl < l {
+                state.remove_left(cl);
+                cl += 1;
+            }
+            while r < cr {
+                cr -= 1;
+                state.remove_right(cr);
+            }
+            answers[i] = Some(state.answer());
+        }
+        answers.into_iter().flatten().collect()
+    }
+}
+
+/// Returns the position of `(x, y)` along the Hilbert curve through `[0, 2^log)^2`.
+fn hilbert(mut x: u64, mut y: u64, log: u32) -> u128 {
+    let mut d = 0;
+    let mut s = if log == 0 { 0 } else { 1u64 << (log - 1) };
+    while s > 0 {
+        let (rx, ry) = (x & s != 0, y & s != 0);
+        d += (s as u128 * s as u128) * ((3 * rx as u128) ^ ry as u128);
+        if !ry {
+            if rx {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x &= s - 1;
+        y &= s - 1;
+        s >>= 1;
+    }
+    d
+}
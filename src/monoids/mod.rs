@@ -0,0 +1,1039 @@
+//! Prebuilt monoids.
+//!
+//! Every monoid implements [`Monoid`]. Monoids with inverses implement
+//! [`HasInverse`], and idempotent ones implement [`Idempotent`], so that they can be used
+//! with `FenwickTree::range_fold` and `SparseTable` respectively. Every monoid carrying data
+//! implements [`Codec`] when its values do, so that segment trees over it can be dumped with
+//! `to_bytes`.
+//!
+//! Tuples `(A, B)` and `(A, B, C)` of monoids are monoids under the componentwise operation,
+//! and any [`Semigroup`] becomes a monoid through [`OptionMonoid`]. [`Reversed`] swaps the
+//! operand order of a monoid, and [`Bidirectional`] lifts an action to folds in both
+//! directions.
+
+pub use crate::algebra::Semigroup;
+use crate::algebra::{Action, HasInverse, Idempotent, Monoid};
+use crate::num::{Integer, Numeric};
+use crate::segment_tree::Codec;
+use crate::segment_tree::codec::impl_codec;
+use crate::strings::PolyHash;
+
+/// Implements [`Semigroup`] and [`Monoid`] from an identity and a binary operation.
+macro_rules! impl_monoid {
+    ([$($g:tt)*] $t:ty, $id:expr, |$a:ident, $b:ident| $op:expr) => {
+        impl<$($g)*> Semigroup for $t {
+            #[inline(always)]
+            fn op(&self, other: &Self) -> Self {
+                let ($a, $b) = (self, other);
+                $op
+            }
+        }
+
+        impl<$($g)*> Monoid for $t {
+            #[inline(always)]
+            fn id() -> Self {
+                $id
+            }
+        }
+    };
+}
+
+/// Addition, with `0` as identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Add<T>(pub T);
+
+impl_monoid!([T: Numeric] Add<T>, Add(T::ZERO), |a, b| Add(a.0 + b.0));
+impl_codec!([T: Codec] Add<T>, |s| s.0 => |x| Add(x));
+
+impl<T: Numeric + std::ops::Neg<Output = T>> HasInverse for Add<T> {
+    #[inline(always)]
+    fn inv(&self) -> Self {
+        Add(-self.0)
+    }
+}
+
+/// Multiplication, with `1` as identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mul<T>(pub T);
+
+impl_monoid!([T: Numeric] Mul<T>, Mul(T::ONE), |a, b| Mul(a.0 * b.0));
+impl_codec!([T: Codec] Mul<T>, |s| s.0 => |x| Mul(x));
+
+/// Minimum, with `T::MAX` as identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Min<T>(pub T);
+
+impl_monoid!([T: Numeric] Min<T>, Min(T::MAX), |a, b| if b.0 < a.0 { *b } else { *a });
+impl_codec!([T: Codec] Min<T>, |s| s.0 => |x| Min(x));
+
+impl<T: Numeric> Idempotent for Min<T> {}
+
+/// Maximum, with `T::MIN` as identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Max<T>(pub T);
+
+impl_monoid!([T: Numeric] Max<T>, Max(T::MIN), |a, b| if a.0 < b.0 { *b } else { *a });
+impl_codec!([T: Codec] Max<T>, |s| s.0 => |x| Max(x));
+
+impl<T: Numeric> Idempotent for Max<T> {}
+
+/// Bitwise exclusive or, with `0` as identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Xor<T>(pub T);
+
+impl_monoid!([T: Integer] Xor<T>, Xor(T::ZERO), |a, b| Xor(a.0 ^ b.0));
+impl_codec!([T: Codec] Xor<T>, |s| s.0 => |x| Xor(x));
+
+impl<T: Integer> HasInverse for Xor<T> {
+    #[inline(always)]
+    fn inv(&self) -> Self {
+        *self
+    }
+}
+
+/// Bitwise and, with all bits set as identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitAnd<T>(pub T);
+
+impl_monoid!([T: Integer] BitAnd<T>, BitAnd(!T::ZERO), |a, b| BitAnd(a.0 & b.0));
+impl_codec!([T: Codec] BitAnd<T>, |s| s.0 => |x| BitAnd(x));
+
+impl<T: Integer> Idempotent for BitAnd<T> {}
+
+/// Bitwise or, with `0` as identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitOr<T>(pub T);
+
+impl_monoid!([T: Integer] BitOr<T>, BitOr(T::ZERO), |a, b| BitOr(a.0 | b.0));
+impl_codec!([T: Codec] BitOr<T>, |s| s.0 => |x| BitOr(x));
+
+impl<T: Integer> Idempotent for BitOr<T> {}
+
+/// Returns the greatest common divisor of two non-negative integers.
+#[inline]
+fn gcd<T: Integer>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Greatest common divisor of non-negative integers, with `0` as identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Gcd<T>(pub T);
+
+impl_monoid!([T: Integer] Gcd<T>, Gcd(T::ZERO), |a, b| Gcd(gcd(a.0, b.0)));
+impl_codec!([T: Codec] Gcd<T>, |s| s.0 => |x| Gcd(x));
+
+impl<T: Integer> Idempotent for Gcd<T> {}
+
+/// Least common multiple of non-negative integers, with `1` as identity.
+///
+/// The least common multiple involving `0` is `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lcm<T>(pub T);
+
+impl_monoid!([T: Integer] Lcm<T>, Lcm(T::ONE), |a, b| {
+    if a.0 == T::ZERO || b.0 == T::ZERO {
+        Lcm(T::ZERO)
+    } else {
+        Lcm(a.0 / gcd(a.0, b.0) * b.0)
+    }
+});
+impl_codec!([T: Codec] Lcm<T>, |s| s.0 => |x| Lcm(x));
+
+impl<T: Integer> Idempotent for Lcm<T> {}
+
+/// A tie-breaking policy between equal values at different indices.
+pub trait TieBreak {
+    /// An index that is never preferred, used by the identity.
+    const WORST: usize;
+
+    /// Returns `true` if index `i` is preferred over index `j`.
+    fn prefer(i: usize, j: usize) -> bool;
+}
+
+/// Prefers the smallest index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Leftmost;
+
+impl TieBreak for Leftmost {
+    const WORST: usize = usize::MAX;
+
+    #[inline(always)]
+    fn prefer(i: usize, j: usize) -> bool {
+        i < j
+    }
+}
+
+/// Prefers the largest index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rightmost;
+
+impl TieBreak for Rightmost {
+    const WORST: usize = 0;
+
+    #[inline(always)]
+    fn prefer(i: usize, j: usize) -> bool {
+        i > j
+    }
+}
+
+/// Minimum together with an index attaining it, chosen by the tie-breaking policy `P`.
+///
+/// The identity is `T::MAX` at index `P::WORST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MinIndex<T, P = Leftmost> {
+    /// The minimum value.
+    pub value: T,
+    /// The preferred index attaining the minimum.
+    pub index: usize,
+    policy: std::marker::PhantomData<P>,
+}
+
+impl<T, P> MinIndex<T, P> {
+    /// Returns the element `value` at index `index`.
+    #[inline(always)]
+    pub fn new(value: T, index: usize) -> Self {
+        Self {
+            value,
+            index,
+            policy: std::marker::PhantomData,
+        }
+    }
+}
+
+impl_monoid!(
+    [T: Numeric, P: TieBreak + Copy] MinIndex<T, P>,
+    MinIndex::new(T::MAX, P::WORST),
+    |a, b| {
+        if b.value < a.value || (b.value == a.value && P::prefer(b.index, a.index)) {
+            *b
+        } else {
+            *a
+        }
+    }
+);
+
+impl<T: Numeric, P: TieBreak + Copy> Idempotent for MinIndex<T, P> {}
+
+impl_codec!(
+    [T: Codec, P] MinIndex<T, P>,
+    |s| s.value, s.index => |value, index| MinIndex::new(value, index)
+);
+
+/// Minimum together with the smallest index attaining it.
+pub type MinWithIndex<T> = MinIndex<T, Leftmost>;
+
+/// Maximum together with an index attaining it, chosen by the tie-breaking policy `P`.
+///
+/// The identity is `T::MIN` at index `P::WORST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaxIndex<T, P = Leftmost> {
+    /// The maximum value.
+    pub value: T,
+    /// The preferred index attaining the maximum.
+    pub index: usize,
+    policy: std::marker::PhantomData<P>,
+}
+
+impl<T, P> MaxIndex<T, P> {
+    /// Returns the element `value` at index `index`.
+    #[inline(always)]
+    pub fn new(value: T, index: usize) -> Self {
+        Self {
+            value,
+            index,
+            policy: std::marker::PhantomData,
+        }
+    }
+}
+
+impl_monoid!(
+    [T: Numeric, P: TieBreak + Copy] MaxIndex<T, P>,
+    MaxIndex::new(T::MIN, P::WORST),
+    |a, b| {
+        if a.value < b.value || (b.value == a.value && P::prefer(b.index, a.index)) {
+            *b
+        } else {
+            *a
+        }
+    }
+);
+
+impl<T: Numeric, P: TieBreak + Copy> Idempotent for MaxIndex<T, P> {}
+
+impl_codec!(
+    [T: Codec, P] MaxIndex<T, P>,
+    |s| s.value, s.index => |value, index| MaxIndex::new(value, index)
+);
+
+/// Minimum together with the number of elements attaining it.
+///
+/// The identity is `T::MAX` with count `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MinWithCount<T> {
+    /// The minimum value.
+    pub value: T,
+    /// The number of elements attaining the minimum.
+    pub count: usize,
+}
+
+impl<T> MinWithCount<T> {
+    /// Returns a single element `value`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self { value, count: 1 }
+    }
+}
+
+impl_monoid!(
+    [T: Numeric] MinWithCount<T>,
+    MinWithCount {
+        value: T::MAX,
+        count: 0,
+    },
+    |a, b| {
+        if b.value < a.value {
+            *b
+        } else if a.value < b.value {
+            *a
+        } else {
+            MinWithCount {
+                value: a.value,
+                count: a.count + b.count,
+            }
+        }
+    }
+);
+impl_codec!(
+    [T: Codec] MinWithCount<T>,
+    |s| s.value, s.count => |value, count| MinWithCount { value, count }
+);
+
+/// Maximum together with the number of elements attaining it.
+///
+/// The identity is `T::MIN` with count `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaxWithCount<T> {
+    /// The maximum value.
+    pub value: T,
+    /// The number of elements attaining the maximum.
+    pub count: usize,
+}
+
+impl<T> MaxWithCount<T> {
+    /// Returns a single element `value`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self { value, count: 1 }
+    }
+}
+
+impl_monoid!(
+    [T: Numeric] MaxWithCount<T>,
+    MaxWithCount {
+        value: T::MIN,
+        count: 0,
+    },
+    |a, b| {
+        if a.value < b.value {
+            *b
+        } else if b.value < a.value {
+            *a
+        } else {
+            MaxWithCount {
+                value: a.value,
+                count: a.count + b.count,
+            }
+        }
+    }
+);
+impl_codec!(
+    [T: Codec] MaxWithCount<T>,
+    |s| s.value, s.count => |value, count| MaxWithCount { value, count }
+);
+
+/// The affine map `x -> a * x + b`, composed as functions, with the identity map as identity.
+///
+/// `op(f, g)` is `f ∘ g`, which applies `g` first, so the fold of a range `l..r` applies
+/// `a[r-1]` first and `a[l]` last. Use [`Reversed<Affine<T>>`](Reversed) to apply the range
+/// from left to right.
+///
+/// As an action of [`LazySegmentTree`](crate::segment_tree::LazySegmentTree), it composes
+/// assignment (`a = 0`) and addition (`a = 1`), e.g. for range-affine range-sum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Affine<T> {
+    /// The coefficient of `x`.
+    pub a: T,
+    /// The constant term.
+    pub b: T,
+}
+
+impl<T: Numeric> Affine<T> {
+    /// Returns the map `x -> a * x + b`.
+    #[inline(always)]
+    pub fn new(a: T, b: T) -> Self {
+        Self { a, b }
+    }
+
+    /// Returns `a * x + b`.
+    #[inline(always)]
+    pub fn eval(&self, x: T) -> T {
+        self.a * x + self.b
+    }
+}
+
+impl_monoid!([T: Numeric] Affine<T>, Affine::new(T::ONE, T::ZERO), |f, g| Affine {
+    a: f.a * g.a,
+    b: f.a * g.b + f.b,
+});
+impl_codec!([T: Codec] Affine<T>, |s| s.a, s.b => |a, b| Affine { a, b });
+
+/// A square matrix of size `N`, with multiplication as operation and the identity matrix as
+/// identity.
+///
+/// The fold of a range `l..r` is the product `a[l] * ... * a[r-1]`, e.g. the transition of
+/// a linear recurrence over the range, or a composition of linear maps applied from right
+/// to left to column vectors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Matrix<T, const N: usize>(pub [[T; N]; N]);
+
+impl<T: Numeric, const N: usize> Matrix<T, N> {
+    /// Returns the identity matrix.
+    #[inline]
+    pub fn identity() -> Self {
+        let mut m = [[T::ZERO; N]; N];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = T::ONE;
+        }
+        Self(m)
+    }
+
+    /// Returns the product of this matrix and the column vector `v`.
+    #[inline]
+    pub fn mul_vec(&self, v: &[T; N]) -> [T; N] {
+        std::array::from_fn(|i| {
+            let mut s = T::ZERO;
+            for (&x, &y) in self.0[i].iter().zip(v) {
+                s = s + x * y;
+            }
+            s
+        })
+    }
+}
+
+impl_monoid!([T: Numeric, const N: usize] Matrix<T, N>, Matrix::identity(), |a, b| {
+    let mut m = [[T::ZERO; N]; N];
+    for (row, x) in m.iter_mut().zip(&a.0) {
+        for (k, &x) in x.iter().enumerate() {
+            for (c, &y) in row.iter_mut().zip(&b.0[k]) {
+                *c = *c + x * y;
+            }
+        }
+    }
+    Matrix(m)
+});
+impl_codec!([T: Codec, const N: usize] Matrix<T, N>, |s| s.0 => |m| Matrix(m));
+
+/// Returns the larger of two values, or `a` if they are not comparable.
+#[inline(always)]
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if a < b { b } else { a }
+}
+
+/// Maximum subarray sum of a range, over non-empty subarrays.
+///
+/// The identity is the empty range, with sum `0` and `T::MIN` for the other fields. The
+/// maximum over possibly empty subarrays is `best` clamped below at `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaxSubarray<T> {
+    /// The sum of all elements.
+    pub sum: T,
+    /// The maximum sum of a non-empty prefix.
+    pub prefix: T,
+    /// The maximum sum of a non-empty suffix.
+    pub suffix: T,
+    /// The maximum sum of a non-empty subarray.
+    pub best: T,
+}
+
+impl<T: Numeric> MaxSubarray<T> {
+    /// Returns a single element `x`.
+    #[inline(always)]
+    pub fn new(x: T) -> Self {
+        Self {
+            sum: x,
+            prefix: x,
+            suffix: x,
+            best: x,
+        }
+    }
+
+    /// Returns `true` if this is the identity, which must not take part in any sum as
+    /// `T::MIN` would overflow.
+    #[inline(always)]
+    fn is_id(&self) -> bool {
+        self.best == T::MIN && self.sum == T::ZERO
+    }
+}
+
+impl_monoid!(
+    [T: Numeric] MaxSubarray<T>,
+    MaxSubarray {
+        sum: T::ZERO,
+        prefix: T::MIN,
+        suffix: T::MIN,
+        best: T::MIN,
+    },
+    |a, b| {
+        if a.is_id() {
+            *b
+        } else if b.is_id() {
+            *a
+        } else {
+            MaxSubarray {
+                sum: a.sum + b.sum,
+                prefix: max(a.prefix, a.sum + b.prefix),
+                suffix: max(b.suffix, a.suffix + b.sum),
+                best: max(max(a.best, b.best), a.suffix + b.prefix),
+            }
+        }
+    }
+);
+impl_codec!(
+    [T: Codec] MaxSubarray<T>,
+    |s| s.sum, s.prefix, s.suffix, s.best => |sum, prefix, suffix, best| MaxSubarray {
+        sum,
+        prefix,
+        suffix,
+        best,
+    }
+);
+
+/// Bracket matching of a range of `(` and `)`, with the empty range as identity.
+///
+/// Matching every `)` with the nearest unmatched `(` to its left leaves a range of the form
+/// `)...)(...(`, described by the number of unmatched brackets of each kind. When two ranges
+/// are concatenated, the unmatched `(` of the left one match the unmatched `)` of the right
+/// one. A range is a balanced bracket sequence if and only if nothing is left unmatched.
+///
+/// Prefix queries go through `max_right`: with `p = Brackets::is_balanced_prefix`, it returns
+/// the end of the longest range starting at `l` in which every `)` is matched. The longest
+/// balanced prefix itself is not supported: whether a later prefix is balanced depends on
+/// how many `(` are unmatched before it, so it cannot be kept in a fold of fixed size.
+/// [`matched_len`](Self::matched_len) is the length of the longest balanced subsequence,
+/// which a fold does determine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Brackets {
+    /// The number of unmatched `(`, all after the unmatched `)`.
+    pub open: usize,
+    /// The number of unmatched `)`.
+    pub close: usize,
+    /// The number of matched pairs.
+    pub pairs: usize,
+}
+
+impl Brackets {
+    /// Returns a single `(`.
+    #[inline(always)]
+    pub fn opening() -> Self {
+        Self {
+            open: 1,
+            close: 0,
+            pairs: 0,
+        }
+    }
+
+    /// Returns a single `)`.
+    #[inline(always)]
+    pub fn closing() -> Self {
+        Self {
+            open: 0,
+            close: 1,
+            pairs: 0,
+        }
+    }
+
+    /// Returns `(` for `b'('`, `)` for `b')'`, and the identity for any other byte.
+    #[inline(always)]
+    pub fn from_byte(b: u8) -> Self {
+        match b {
+            b'(' => Self::opening(),
+            b')' => Self::closing(),
+            _ => Self::default(),
+        }
+    }
+
+    /// Returns `true` if the range is a balanced bracket sequence.
+    #[inline(always)]
+    pub fn is_balanced(&self) -> bool {
+        self.open == 0 && self.close == 0
+    }
+
+    /// Returns `true` if every `)` of the range is matched, so that it is a prefix of a
+    /// balanced bracket sequence.
+    ///
+    /// This holds for every prefix of such a range, so it can be passed to `max_right`.
+    #[inline(always)]
+    pub fn is_balanced_prefix(&self) -> bool {
+        self.close == 0
+    }
+
+    /// Returns the length of the longest balanced subsequence of the range.
+    ///
+    /// This is not the longest balanced prefix, which a fold does not determine.
+    #[inline(always)]
+    pub fn matched_len(&self) -> usize {
+        self.pairs << 1
+    }
+}
+
+impl_monoid!([] Brackets, Brackets::default(), |a, b| {
+    let m = a.open.min(b.close);
+    Brackets {
+        open: a.open - m + b.open,
+        close: a.close + b.close - m,
+        pairs: a.pairs + b.pairs + m,
+    }
+});
+impl_codec!(
+    [] Brackets,
+    |s| s.open, s.close, s.pairs => |open, close, pairs| Brackets { open, close, pairs }
+);
+
+/// The mergeable Boyer–Moore majority vote, with no votes as identity.
+///
+/// Every element votes for itself, and votes for distinct values cancel out in pairs. If a
+/// value occurs in more than half of a range, it survives every cancellation, so it is the
+/// candidate of the fold of the range. The converse does not hold, and a candidate must be
+/// verified by counting its occurrences, e.g. with
+/// [`RangeMajority`](crate::segment_tree::RangeMajority).
+///
+/// The operation is associative only up to this guarantee: folding the same range in
+/// different orders may give different candidates or counts when there is no majority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MajorityCandidate<T> {
+    /// The candidate, meaningless if `count` is `0`.
+    pub value: Option<T>,
+    /// The number of votes for the candidate left after cancellation.
+    pub count: usize,
+}
+
+impl<T> MajorityCandidate<T> {
+    /// Returns a single vote for `value`.
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Some(value),
+            count: 1,
+        }
+    }
+}
+
+impl_monoid!(
+    [T: Clone + PartialEq] MajorityCandidate<T>,
+    MajorityCandidate {
+        value: None,
+        count: 0,
+    },
+    |a, b| {
+        if a.value == b.value {
+            MajorityCandidate {
+                value: a.value.clone(),
+                count: a.count + b.count,
+            }
+        } else if b.count <= a.count {
+            MajorityCandidate {
+                value: a.value.clone(),
+                count: a.count - b.count,
+            }
+        } else {
+            MajorityCandidate {
+                value: b.value.clone(),
+                count: b.count - a.count,
+            }
+        }
+    }
+);
+impl_codec!(
+    [T: Codec] MajorityCandidate<T>,
+    |s| s.value, s.count => |value, count| MajorityCandidate { value, count }
+);
+
+/// Concatenation of vecs, with the empty vec as identity.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Concat<T>(pub Vec<T>);
+
+impl_monoid!([T: Clone] Concat<T>, Concat(Vec::new()), |a, b| {
+    let mut v = Vec::with_capacity(a.0.len() + b.0.len());
+    v.extend_from_slice(&a.0);
+    v.extend_from_slice(&b.0);
+    Concat(v)
+});
+impl_codec!([T: Codec] Concat<T>, |s| s.0 => |v| Concat(v));
+
+/// Concatenation of strings, with the empty string as identity.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrConcat(pub String);
+
+impl_monoid!([] StrConcat, StrConcat(String::new()), |a, b| {
+    let mut s = String::with_capacity(a.0.len() + b.0.len());
+    s.push_str(&a.0);
+    s.push_str(&b.0);
+    StrConcat(s)
+});
+impl_codec!([] StrConcat, |s| s.0 => |x| StrConcat(x));
+
+/// The Mersenne prime `2^61 - 1`, the modulus of [`RangeHash`].
+const HASH_MOD: u64 = (1 << 61) - 1;
+
+/// Returns `a * b mod 2^61 - 1` for `a, b < 2^61 - 1`.
+#[inline(always)]
+fn hash_mul(a: u64, b: u64) -> u64 {
+    let t = a as u128 * b as u128;
+    let r = (t >> 61) as u64 + (t as u64 & HASH_MOD);
+    if r >= HASH_MOD { r - HASH_MOD } else { r }
+}
+
+/// Returns `a + b mod 2^61 - 1` for `a, b < 2^61 - 1`.
+#[inline(always)]
+fn hash_add(a: u64, b: u64) -> u64 {
+    let r = a + b;
+    if r >= HASH_MOD { r - HASH_MOD } else { r }
+}
+
+/// Returns `a - b mod 2^61 - 1` for `a, b < 2^61 - 1`.
+#[inline(always)]
+fn hash_sub(a: u64, b: u64) -> u64 {
+    if a >= b { a - b } else { a + HASH_MOD - b }
+}
+
+/// Polynomial rolling hash of a string modulo `2^61 - 1`, with the empty string as identity.
+///
+/// The hash of `s` is `s[0] * BASE^(len-1) + ... + s[len-1]`, and `BASE^len` is carried along
+/// so that hashes of adjacent ranges can be concatenated. Two ranges of a `SegmentTree` are
+/// then compared for equality by comparing their folds, in O(log n) time under point updates.
+/// Equal strings always compare equal; distinct strings of length `n` collide with
+/// probability about `n / 2^61` over a random choice of `BASE`.
+///
+/// The default `BASE` is fixed, so inputs crafted against it can collide. Pick a random
+/// `BASE` in `2..2^61 - 1` when that matters, or use [`DoubleRangeHash`].
+///
+/// [`Assign<u64>`](crate::segment_tree::actions::Assign) acts on it by overwriting every
+/// character of the range with the same character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RangeHash<const BASE: u64 = 0x1d3f_84a5_b4c9_1e27> {
+    hash: u64,
+    /// `BASE^len`.
+    pow: u64,
+    /// The hash of the string of `len` ones, `BASE^(len-1) + ... + 1`.
+    ones: u64,
+}
+
+impl<const BASE: u64> RangeHash<BASE> {
+    /// Returns the hash of the single character `c`.
+    ///
+    /// `c` is reduced modulo `2^61 - 1`.
+    #[inline(always)]
+    pub fn new(c: u64) -> Self {
+        Self {
+            hash: c % HASH_MOD,
+            pow: BASE,
+            ones: 1,
+        }
+    }
+
+    /// Returns the hash value.
+    #[inline(always)]
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl<const BASE: u64> RangeHash<BASE> {
+    /// The hash of the empty string.
+    const EMPTY: Self = Self {
+        hash: 0,
+        pow: 1,
+        ones: 0,
+    };
+}
+
+impl_monoid!(
+    [const BASE: u64] RangeHash<BASE>,
+    RangeHash::EMPTY,
+    |a, b| RangeHash {
+        hash: hash_add(hash_mul(a.hash, b.pow), b.hash),
+        pow: hash_mul(a.pow, b.pow),
+        ones: hash_add(hash_mul(a.ones, b.pow), b.ones),
+    }
+);
+
+/// Rejects residues not below `2^61 - 1`, which the operations assume.
+impl<const BASE: u64> Codec for RangeHash<BASE> {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.hash, self.pow, self.ones).encode(out);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (hash, pow, ones) = <(u64, u64, u64)>::decode(bytes)?;
+        (hash < HASH_MOD && pow < HASH_MOD && ones < HASH_MOD).then_some(RangeHash {
+            hash,
+            pow,
+            ones,
+        })
+    }
+}
+
+/// Overwrites every character with `c`.
+impl<const BASE: u64> Action<RangeHash<BASE>> for crate::segment_tree::actions::Assign<u64> {
+    #[inline(always)]
+    fn act(&self, s: &RangeHash<BASE>) -> RangeHash<BASE> {
+        match self.0 {
+            Some(c) => RangeHash {
+                hash: hash_mul(c % HASH_MOD, s.ones),
+                ..*s
+            },
+            None => *s,
+        }
+    }
+}
+
+impl<const BASE: u64> PolyHash for RangeHash<BASE> {
+    #[inline(always)]
+    fn new(c: u64) -> Self {
+        RangeHash::new(c)
+    }
+
+    #[inline(always)]
+    fn strip_prefix(&self, prefix: &Self, len: &Self) -> Self {
+        RangeHash {
+            hash: hash_sub(self.hash, hash_mul(prefix.hash, len.pow)),
+            pow: len.pow,
+            ones: hash_sub(self.ones, hash_mul(prefix.ones, len.pow)),
+        }
+    }
+}
+
+/// A pair of [`RangeHash`]es with independent bases, with the empty string as identity.
+///
+/// Distinct strings collide only if both hashes do, with probability about `(n / 2^61)^2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DoubleRangeHash(
+    pub RangeHash<0x1d3f_84a5_b4c9_1e27>,
+    pub RangeHash<0x0b6e_2c91_f7d0_5a43>,
+);
+
+impl DoubleRangeHash {
+    /// Returns the hash of the single character `c`.
+    #[inline(always)]
+    pub fn new(c: u64) -> Self {
+        Self(RangeHash::new(c), RangeHash::new(c))
+    }
+
+    /// Returns both hash values.
+    #[inline(always)]
+    pub fn hash(&self) -> (u64, u64) {
+        (self.0.hash(), self.1.hash())
+    }
+}
+
+impl_monoid!(
+    [] DoubleRangeHash,
+    DoubleRangeHash(RangeHash::EMPTY, RangeHash::EMPTY),
+    |a, b| DoubleRangeHash(
+        Semigroup::op(&a.0, &b.0),
+        Semigroup::op(&a.1, &b.1),
+    )
+);
+impl_codec!([] DoubleRangeHash, |s| s.0, s.1 => |a, b| DoubleRangeHash(a, b));
+
+impl PolyHash for DoubleRangeHash {
+    #[inline(always)]
+    fn new(c: u64) -> Self {
+        DoubleRangeHash::new(c)
+    }
+
+    #[inline(always)]
+    fn strip_prefix(&self, prefix: &Self, len: &Self) -> Self {
+        DoubleRangeHash(
+            self.0.strip_prefix(&prefix.0, &len.0),
+            self.1.strip_prefix(&prefix.1, &len.1),
+        )
+    }
+}
+
+/// Overwrites every character with `c`.
+impl Action<DoubleRangeHash> for crate::segment_tree::actions::Assign<u64> {
+    #[inline(always)]
+    fn act(&self, s: &DoubleRangeHash) -> DoubleRangeHash {
+        DoubleRangeHash(self.act(&s.0), self.act(&s.1))
+    }
+}
+
+/// A monoid with the operand order swapped, so that folds combine elements from right to left.
+///
+/// Storing `(x, Reversed(x))` in a segment tree keeps the fold of every node in both
+/// directions, so that `range_fold_rev` can answer reversed folds from the same tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Reversed<S>(pub S);
+
+impl<S: Semigroup> Semigroup for Reversed<S> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        Reversed(other.0.op(&self.0))
+    }
+}
+
+impl<S: Monoid> Monoid for Reversed<S> {
+    #[inline(always)]
+    fn id() -> Self {
+        Reversed(S::id())
+    }
+}
+
+impl<S: HasInverse> HasInverse for Reversed<S> {
+    #[inline(always)]
+    fn inv(&self) -> Self {
+        Reversed(self.0.inv())
+    }
+}
+
+impl<S: Idempotent> Idempotent for Reversed<S> {}
+
+impl_codec!([S: Codec] Reversed<S>, |s| s.0 => |x| Reversed(x));
+
+/// An action on `S` applied to both directions `(S, Reversed<S>)` of a fold.
+///
+/// As the action of a [`LazySegmentTree`](crate::segment_tree::LazySegmentTree) storing
+/// `(x, Reversed(x))`, it keeps both folds of every node up to date, so that
+/// `range_fold_rev` answers reversed folds. `f` acts on a reversed fold as on a fold, which is
+/// correct because `f.act` distributes over `op` in either order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bidirectional<F>(pub F);
+
+impl<F: Semigroup> Semigroup for Bidirectional<F> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        Bidirectional(self.0.op(&other.0))
+    }
+}
+
+impl<F: Monoid> Monoid for Bidirectional<F> {
+    #[inline(always)]
+    fn id() -> Self {
+        Bidirectional(F::id())
+    }
+}
+
+impl<S: Monoid, F: Action<S>> Action<(S, Reversed<S>)> for Bidirectional<F> {
+    #[inline(always)]
+    fn act(&self, s: &(S, Reversed<S>)) -> (S, Reversed<S>) {
+        (self.0.act(&s.0), Reversed(self.0.act(&s.1.0)))
+    }
+}
+
+impl_codec!([F: Codec] Bidirectional<F>, |s| s.0 => |f| Bidirectional(f));
+
+/// A semigroup lifted to a monoid by adjoining `None` as identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OptionMonoid<T>(pub Option<T>);
+
+impl_monoid!([T: Semigroup] OptionMonoid<T>, OptionMonoid(None), |a, b| {
+    match (&a.0, &b.0) {
+        (Some(x), Some(y)) => OptionMonoid(Some(x.op(y))),
+        (Some(_), None) => a.clone(),
+        (None, _) => b.clone(),
+    }
+});
+impl_codec!([T: Codec] OptionMonoid<T>, |s| s.0 => |x| OptionMonoid(x));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment_tree::SegmentTree;
+    use crate::segment_tree::core::tests::Rng;
+
+    /// Matches the brackets of `s` with a stack, and returns the unmatched `(`, the unmatched
+    /// `)` and the matched pairs.
+    fn brute(s: &[u8]) -> (usize, usize, usize) {
+        let (mut stack, mut close, mut pairs) = (0, 0, 0);
+        for &b in s {
+            match b {
+                b'(' => stack += 1,
+                b')' if stack > 0 => {
+                    stack -= 1;
+                    pairs += 1;
+                }
+                b')' => close += 1,
+                _ => {}
+            }
+        }
+        (stack, close, pairs)
+    }
+
+    fn random_brackets(rng: &mut Rng, n: usize) -> Vec<u8> {
+        (0..n).map(|_| b"(()x"[rng.below(4)]).collect()
+    }
+
+    #[test]
+    fn brackets_identity_and_associativity() {
+        let mut rng = Rng(88_172_645_463_325_252);
+        let id = Brackets::id();
+        assert!(id.is_balanced());
+        assert_eq!(id.matched_len(), 0);
+        assert_eq!(Brackets::from_byte(b'x'), id);
+        for _ in 0..1000 {
+            let [a, b, c] = std::array::from_fn(|_| {
+                let n = rng.below(8);
+                let s = random_brackets(&mut rng, n);
+                s.iter()
+                    .fold(id, |acc, &b| Brackets::op(&acc, &Brackets::from_byte(b)))
+            });
+            assert_eq!(Brackets::op(&id, &a), a);
+            assert_eq!(Brackets::op(&a, &id), a);
+            assert_eq!(
+                Brackets::op(&Brackets::op(&a, &b), &c),
+                Brackets::op(&a, &Brackets::op(&b, &c)),
+            );
+        }
+    }
+
+    #[test]
+    fn brackets_range_fold() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for n in (0..=40).chain([1000]) {
+            let s = random_brackets(&mut rng, n);
+            let t: SegmentTree<Brackets> = s.iter().map(|&b| Brackets::from_byte(b)).collect();
+            for _ in 0..200 {
+                let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                let (l, r) = (x.min(y), x.max(y));
+                let f = t.range_fold(l..r);
+                let (open, close, pairs) = brute(&s[l..r]);
+                assert_eq!((f.open, f.close, f.pairs), (open, close, pairs), "{l}..{r}");
+                assert_eq!(f.is_balanced(), open == 0 && close == 0);
+                assert_eq!(f.matched_len(), 2 * pairs);
+                // The first `)` matching nothing ends the longest prefix without one.
+                let mut depth = 0usize;
+                let expected = (l..n)
+                    .find(|&i| match s[i] {
+                        b'(' => {
+                            depth += 1;
+                            false
+                        }
+                        b')' if depth == 0 => true,
+                        b')' => {
+                            depth -= 1;
+                            false
+                        }
+                        _ => false,
+                    })
+                    .unwrap_or(n);
+                assert_eq!(t.max_right(l, Brackets::is_balanced_prefix), expected);
+            }
+        }
+    }
+}
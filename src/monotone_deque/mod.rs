@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+/// A queue maintaining the minimum of its elements.
+///
+/// Given elements of a totally ordered type `T`, this data structure supports:
+/// - Update: `push_back(x)` appends `x`, and `pop_front()` removes the oldest element
+/// - Query: `min()` returns the smallest element
+///
+/// All operations run in O(1) amortized time. Only the elements smaller than every later
+/// element can become the minimum, so the queue keeps just those, in increasing order; an
+/// element is dropped as soon as a smaller or equal one is pushed after it, or the window
+/// moves past it. Wrap the elements in [`Reverse`](std::cmp::Reverse) to maintain the
+/// maximum.
+///
+/// For fixed-size windows over a slice, [`sliding_window_min`] and [`sliding_window_max`]
+/// avoid cloning the elements.
+#[derive(Clone, Debug)]
+pub struct MonotoneDeque<T> {
+    /// Candidates for the minimum with their positions, increasing in both.
+    deque: VecDeque<(usize, T)>,
+    /// Position of the oldest element.
+    head: usize,
+    /// Position of the next element to be pushed.
+    tail: usize,
+}
+
+impl<T: Ord> MonotoneDeque<T> {
+    /// Creates a new empty queue.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            deque: VecDeque::new(),
+            head: 0,
+            tail: 0,
+        }
+    }
+
+    /// Appends `x` at the back.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized
+    pub fn push_back(&mut self, x: T) {
+        while self.deque.back().is_some_and(|(_, y)| *y >= x) {
+            self.deque.pop_back();
+        }
+        self.deque.push_back((self.tail, x));
+        self.tail += 1;
+    }
+
+    /// Removes the oldest element.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is empty in debug builds.
+    pub fn pop_front(&mut self) {
+        debug_assert!(!self.is_empty(), "pop from an empty queue");
+        if self.deque.front().is_some_and(|&(i, _)| i == self.head) {
+            self.deque.pop_front();
+        }
+        self.head += 1;
+    }
+
+    /// Returns the smallest element, or `None` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn min(&self) -> Option<&T> {
+        self.deque.front().map(|(_, x)| x)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.tail - self.head
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord> Default for MonotoneDeque<T> {
+    /// Creates a new empty queue.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns an iterator over the minimum of every window `v[i..i + k]`, for `i` in
+/// `0..=v.len() - k`.
+///
+/// Returns an empty iterator if `k > v.len()`. Among equal minimums, the last one is returned.
+///
+/// # Time complexity
+///
+/// O(n) for a full traversal
+///
+/// # Panics
+///
+/// Panics if `k == 0` in debug builds.
+#[inline]
+pub fn sliding_window_min<T: Ord>(v: &[T], k: usize) -> impl Iterator<Item = &T> {
+    sliding_window_by(v, k, |x, y| x <= y)
+}
+
+/// Returns an iterator over the maximum of every window `v[i..i + k]`, for `i` in
+/// `0..=v.len() - k`.
+///
+/// Returns an empty iterator if `k > v.len()`. Among equal maximums, the last one is returned.
+///
+/// # Time complexity
+///
+/// O(n) for a full traversal
+///
+/// # Panics
+///
+/// Panics if `k == 0` in debug builds.
+#[inline]
+pub fn sliding_window_max<T: Ord>(v: &[T], k: usize) -> impl Iterator<Item = &T> {
+    sliding_window_by(v, k, |x, y| x >= y)
+}
+
+/// Returns an iterator over the best element of every window of length `k`, where
+/// `better(x, y)` returns `true` if `x` is at least as good as `y`.
+fn sliding_window_by<T>(
+    v: &[T],
+    k: usize,
+    better: impl Fn(&T, &T) -> bool,
+) -> impl Iterator<Item = &T> {
+    debug_assert!(k > 0, "window length must be positive: k={}", k);
+    let mut deque: VecDeque<usize> = VecDeque::new();
+    let mut r = 0;
+    std::iter::from_fn(move || {
+        while r < v.len() {
+            while deque.back().is_some_and(|&j| better(&v[r], &v[j])) {
+                deque.pop_back();
+            }
+            deque.push_back(r);
+            r += 1;
+            if r >= k {
+                if deque[0] < r - k {
+                    deque.pop_front();
+                }
+                return Some(&v[deque[0]]);
+            }
+        }
+        None
+    })
+}
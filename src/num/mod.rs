@@ -0,0 +1,72 @@
+/// A primitive numeric type, used by the prebuilt monoids and actions.
+///
+/// Implemented for all primitive integer and floating point types. For floats, `MIN` and
+/// `MAX` are the infinities, so that they act as identities of `min` and `max`.
+pub trait Numeric:
+    Copy
+    + PartialOrd
+    + std::fmt::Debug
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+{
+    /// The additive identity.
+    const ZERO: Self;
+    /// The multiplicative identity.
+    const ONE: Self;
+    /// The smallest value, the identity of `max`.
+    const MIN: Self;
+    /// The largest value, the identity of `min`.
+    const MAX: Self;
+}
+
+/// A primitive integer type.
+///
+/// Implemented for all primitive integer types.
+pub trait Integer:
+    Numeric
+    + Eq
+    + Ord
+    + std::hash::Hash
+    + std::ops::Div<Output = Self>
+    + std::ops::Rem<Output = Self>
+    + std::ops::BitAnd<Output = Self>
+    + std::ops::BitOr<Output = Self>
+    + std::ops::BitXor<Output = Self>
+    + std::ops::Not<Output = Self>
+{
+}
+
+macro_rules! impl_numeric_int {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+            }
+
+            impl Integer for $t {}
+        )*
+    };
+}
+
+impl_numeric_int!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+macro_rules! impl_numeric_float {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+                const MIN: Self = <$t>::NEG_INFINITY;
+                const MAX: Self = <$t>::INFINITY;
+            }
+        )*
+    };
+}
+
+impl_numeric_float!(f32, f64);
@@ -0,0 +1,428 @@
+/// Index of a missing item or group.
+const NIL: u32 = u32::MAX;
+
+/// A group is split in two when it holds more than this many items.
+const GROUP_CAPACITY: u32 = 64;
+
+/// A range of `2^i` group tags is relabeled only if it holds at most `(2 / T)^i` groups,
+/// where `T = 1.5`.
+const DENSITY_BASE: f64 = 2.0 / 1.5;
+
+/// An item of the list, tagged within its group.
+#[derive(Clone, Debug)]
+struct Item {
+    /// Index of the group, or `NIL` if the item was removed.
+    group: u32,
+    tag: u64,
+    prev: u32,
+    next: u32,
+}
+
+/// A run of consecutive items, tagged within the list of groups.
+#[derive(Clone, Debug)]
+struct Group {
+    tag: u64,
+    prev: u32,
+    next: u32,
+    /// Index of the first item of the group.
+    first: u32,
+    len: u32,
+}
+
+/// A list supporting insertion and deletion at arbitrary positions, and comparison of the
+/// positions of two items.
+///
+/// Items are referred to by the handles returned on insertion. This data structure supports:
+/// - Insertion: `insert_front()`, `insert_after(x)` and `insert_before(x)` return a new item
+///   at the given position
+/// - Deletion: `remove(x)` removes item `x`
+/// - Order query: `precedes(x, y)` returns `true` if `x` is before `y` in the list
+///
+/// Insertions run in O(1) amortized time, and the other operations in O(1) time. The items
+/// are split into groups of at most 64 consecutive items. Every group holds a 64-bit tag,
+/// and every item a 64-bit tag within its group, so that two items are compared by their
+/// pairs of tags. A full group is split, and when no tag is free between two groups, the
+/// smallest enclosing range of tags that is sparse enough is relabeled evenly. This takes
+/// O(log n) amortized time, but happens once every Θ(log n) insertions.
+///
+/// The handle of a removed item may be returned again by a later insertion.
+#[derive(Clone, Debug)]
+pub struct OrderMaintenance {
+    /// Arena of all items; item 0 is a sentinel before every other item.
+    items: Vec<Item>,
+    /// Arena of all groups; group 0 holds the sentinel.
+    groups: Vec<Group>,
+    free_items: Vec<u32>,
+    free_groups: Vec<u32>,
+    len: usize,
+}
+
+impl OrderMaintenance {
+    /// Creates a new empty list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            items: vec![Item {
+                group: 0,
+                tag: 0,
+                prev: NIL,
+                next: NIL,
+            }],
+            groups: vec![Group {
+                tag: 0,
+                prev: NIL,
+                next: NIL,
+                first: 0,
+                len: 1,
+            }],
+            free_items: Vec::new(),
+            free_groups: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Inserts a new item at the front of the list, and returns its handle.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized
+    #[inline]
+    pub fn insert_front(&mut self) -> usize {
+        self.insert(0)
+    }
+
+    /// Inserts a new item right after item `x`, and returns its handle.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not in the list in debug builds.
+    #[inline]
+    pub fn insert_after(&mut self, x: usize) -> usize {
+        self.check(x);
+        self.insert(x as u32)
+    }
+
+    /// Inserts a new item right before item `x`, and returns its handle.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not in the list in debug builds.
+    #[inline]
+    pub fn insert_before(&mut self, x: usize) -> usize {
+        self.check(x);
+        self.insert(self.items[x].prev)
+    }
+
+    /// Removes item `x` from the list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not in the list in debug builds.
+    pub fn remove(&mut self, x: usize) {
+        self.check(x);
+        let Item {
+            group, prev, next, ..
+        } = self.items[x];
+        self.items[prev as usize].next = next;
+        if next != NIL {
+            self.items[next as usize].prev = prev;
+        }
+        self.items[x].group = NIL;
+        self.free_items.push(x as u32);
+        self.len -= 1;
+        let g = &mut self.groups[group as usize];
+        g.len -= 1;
+        if g.len == 0 {
+            let Group { prev, next, .. } = *g;
+            self.groups[prev as usize].next = next;
+            if next != NIL {
+                self.groups[next as usize].prev = prev;
+            }
+            self.free_groups.push(group);
+        } else if g.first == x as u32 {
+            g.first = next;
+        }
+    }
+
+    /// Returns `true` if item `x` is before item `y` in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not in the list in debug builds.
+    #[inline]
+    pub fn precedes(&self, x: usize, y: usize) -> bool {
+        self.cmp(x, y).is_lt()
+    }
+
+    /// Compares the positions of items `x` and `y` in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not in the list in debug builds.
+    #[inline]
+    pub fn cmp(&self, x: usize, y: usize) -> std::cmp::Ordering {
+        self.check(x);
+        self.check(y);
+        self.key(x).cmp(&self.key(y))
+    }
+
+    /// Returns the item right after item `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not in the list in debug builds.
+    #[inline]
+    pub fn next(&self, x: usize) -> Option<usize> {
+        self.check(x);
+        let next = self.items[x].next;
+        (next != NIL).then_some(next as usize)
+    }
+
+    /// Returns the item right before item `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is not in the list in debug builds.
+    #[inline]
+    pub fn prev(&self, x: usize) -> Option<usize> {
+        self.check(x);
+        let prev = self.items[x].prev;
+        (prev != 0).then_some(prev as usize)
+    }
+
+    /// Returns the number of items.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn check(&self, x: usize) {
+        debug_assert!(
+            x != 0 && x < self.items.len() && self.items[x].group != NIL,
+            "item not in the list: x={}",
+            x,
+        );
+    }
+
+    #[inline(always)]
+    fn key(&self, x: usize) -> (u64, u64) {
+        let item = &self.items[x];
+        (self.groups[item.group as usize].tag, item.tag)
+    }
+
+    /// Inserts a new item right after item `x`, which may be the sentinel.
+    fn insert(&mut self, x: u32) -> usize {
+        let group = self.items[x as usize].group;
+        let next = self.items[x as usize].next;
+        if self.gap_after(x) < 2 {
+            self.relabel_group(group);
+        }
+        let lo = self.items[x as usize].tag;
+        let tag = lo + ((self.tag_after(x) - lo as u128) / 2) as u64;
+        let item = Item {
+            group,
+            tag,
+            prev: x,
+            next,
+        };
+        let y = match self.free_items.pop() {
+            Some(y) => {
+                self.items[y as usize] = item;
+                y
+            }
+            None => {
+                debug_assert!(
+                    self.items.len() < NIL as usize,
+                    "too many items: {}",
+                    self.items.len(),
+                );
+                self.items.push(item);
+                (self.items.len() - 1) as u32
+            }
+        };
+        self.items[x as usize].next = y;
+        if next != NIL {
+            self.items[next as usize].prev = y;
+        }
+        self.len += 1;
+        self.groups[group as usize].len += 1;
+        if self.groups[group as usize].len > GROUP_CAPACITY {
+            self.split_group(group);
+        }
+        y as usize
+    }
+
+    /// Returns the tag of the item after item `x` in its group, or `2^64` if there is none.
+    #[inline]
+    fn tag_after(&self, x: u32) -> u128 {
+        let item = &self.items[x as usize];
+        match self.items.get(item.next as usize) {
+            Some(next) if next.group == item.group => next.tag as u128,
+            _ => 1 << 64,
+        }
+    }
+
+    #[inline]
+    fn gap_after(&self, x: u32) -> u128 {
+        self.tag_after(x) - self.items[x as usize].tag as u128
+    }
+
+    /// Spreads the tags of the items of group `g` evenly.
+    fn relabel_group(&mut self, g: u32) {
+        let Group { first, len, .. } = self.groups[g as usize];
+        let step = (1u128 << 64) / (len as u128 + 1);
+        let mut x = first;
+        for k in 0..len {
+            let item = &mut self.items[x as usize];
+            item.group = g;
+            item.tag = (k as u128 * step) as u64;
+            x = item.next;
+        }
+    }
+
+    /// Moves the second half of the items of group `g` to a new group right after it.
+    fn split_group(&mut self, g: u32) {
+        let Group { first, len, .. } = self.groups[g as usize];
+        let mut x = first;
+        for _ in 0..len / 2 {
+            x = self.items[x as usize].next;
+        }
+        let h = self.insert_group_after(g, x, len - len / 2);
+        self.groups[g as usize].len = len / 2;
+        self.relabel_group(g);
+        self.relabel_group(h);
+    }
+
+    /// Inserts a new group right after group `g`, whose items start at `first`, and returns
+    /// its index.
+    fn insert_group_after(&mut self, g: u32, first: u32, len: u32) -> u32 {
+        let next = self.groups[g as usize].next;
+        let group = Group {
+            tag: 0,
+            prev: g,
+            next,
+            first,
+            len,
+        };
+        let h = match self.free_groups.pop() {
+            Some(h) => {
+                self.groups[h as usize] = group;
+                h
+            }
+            None => {
+                self.groups.push(group);
+                (self.groups.len() - 1) as u32
+            }
+        };
+        self.groups[g as usize].next = h;
+        if next != NIL {
+            self.groups[next as usize].prev = h;
+        }
+        let lo = self.groups[g as usize].tag as u128;
+        let hi = match next {
+            NIL => 1 << 64,
+            _ => self.groups[next as usize].tag as u128,
+        };
+        if hi - lo >= 2 {
+            self.groups[h as usize].tag = (lo + (hi - lo) / 2) as u64;
+        } else {
+            self.relabel_groups(g, h);
+        }
+        h
+    }
+
+    /// Relabels the smallest sparse enough range of tags around group `g`, so that group `h`
+    /// right after it gets a tag.
+    fn relabel_groups(&mut self, g: u32, h: u32) {
+        let tag = self.groups[g as usize].tag as u128;
+        let mut threshold = 1.0;
+        for i in 1..=64 {
+            threshold *= DENSITY_BASE;
+            let base = tag & !((1u128 << i) - 1);
+            let end = base + (1 << i);
+            // The groups in the range, from `first` to `last`, together with `h`.
+            let (mut first, mut count) = (g, 2);
+            loop {
+                let prev = self.groups[first as usize].prev;
+                if prev == NIL || (self.groups[prev as usize].tag as u128) < base {
+                    break;
+                }
+                first = prev;
+                count += 1;
+            }
+            let mut last = h;
+            loop {
+                let next = self.groups[last as usize].next;
+                if next == NIL || self.groups[next as usize].tag as u128 >= end {
+                    break;
+                }
+                last = next;
+                count += 1;
+            }
+            if count as f64 > threshold {
+                continue;
+            }
+            let step = (1u128 << i) / count;
+            let mut t = first;
+            for k in 0..count {
+                self.groups[t as usize].tag = (base + k * step) as u64;
+                t = self.groups[t as usize].next;
+            }
+            return;
+        }
+        panic!("too many groups: {}", self.groups.len());
+    }
+}
+
+impl Default for OrderMaintenance {
+    /// Creates a new empty list.
+    fn default() -> Self {
+        Self::new()
+    }
+}
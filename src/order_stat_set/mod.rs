@@ -0,0 +1,370 @@
+use std::cmp::Ordering;
+
+/// A subtree is rebalanced when the weight of one child exceeds `DELTA` times the weight of
+/// the other.
+const DELTA: usize = 3;
+
+/// A rebalancing rotation is double when the weight of the inner grandchild is at least
+/// `GAMMA` times the weight of the outer one.
+const GAMMA: usize = 2;
+
+type Link<K> = Option<Box<Node<K>>>;
+
+/// A node of a weight-balanced tree.
+#[derive(Clone)]
+struct Node<K> {
+    key: K,
+    size: usize,
+    left: Link<K>,
+    right: Link<K>,
+}
+
+#[inline(always)]
+fn size<K>(t: &Link<K>) -> usize {
+    t.as_ref().map_or(0, |t| t.size)
+}
+
+/// Returns the weight of a subtree, one more than its size.
+#[inline(always)]
+fn weight<K>(t: &Link<K>) -> usize {
+    size(t) + 1
+}
+
+impl<K> Node<K> {
+    #[inline]
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn rotate_left<K>(mut t: Box<Node<K>>) -> Box<Node<K>> {
+    let mut r = t.right.take().unwrap();
+    t.right = r.left.take();
+    t.update();
+    r.left = Some(t);
+    r.update();
+    r
+}
+
+fn rotate_right<K>(mut t: Box<Node<K>>) -> Box<Node<K>> {
+    let mut l = t.left.take().unwrap();
+    t.left = l.right.take();
+    t.update();
+    l.right = Some(t);
+    l.update();
+    l
+}
+
+/// Restores the balance of `t` after one of its children grew or shrank by one.
+fn balance<K>(t: &mut Link<K>) {
+    let Some(mut node) = t.take() else {
+        return;
+    };
+    node.update();
+    let (wl, wr) = (weight(&node.left), weight(&node.right));
+    if wr > DELTA * wl {
+        let r = node.right.take().unwrap();
+        node.right = Some(if weight(&r.left) >= GAMMA * weight(&r.right) {
+            rotate_right(r)
+        } else {
+            r
+        });
+        node = rotate_left(node);
+    } else if wl > DELTA * wr {
+        let l = node.left.take().unwrap();
+        node.left = Some(if weight(&l.right) >= GAMMA * weight(&l.left) {
+            rotate_left(l)
+        } else {
+            l
+        });
+        node = rotate_right(node);
+    }
+    *t = Some(node);
+}
+
+fn insert<K: Ord>(t: &mut Link<K>, key: K) -> bool {
+    let Some(node) = t else {
+        *t = Some(Box::new(Node {
+            key,
+            size: 1,
+            left: None,
+            right: None,
+        }));
+        return true;
+    };
+    let res = match key.cmp(&node.key) {
+        Ordering::Less => insert(&mut node.left, key),
+        Ordering::Greater => insert(&mut node.right, key),
+        Ordering::Equal => false,
+    };
+    if res {
+        balance(t);
+    }
+    res
+}
+
+/// Removes and returns the smallest key of the non-empty tree `t`.
+fn remove_min<K>(t: &mut Link<K>) -> K {
+    let node = t.as_mut().unwrap();
+    if node.left.is_some() {
+        let res = remove_min(&mut node.left);
+        balance(t);
+        res
+    } else {
+        let node = t.take().unwrap();
+        *t = node.right;
+        node.key
+    }
+}
+
+fn remove<K: Ord>(t: &mut Link<K>, key: &K) -> bool {
+    let Some(node) = t else {
+        return false;
+    };
+    let res = match key.cmp(&node.key) {
+        Ordering::Less => remove(&mut node.left, key),
+        Ordering::Greater => remove(&mut node.right, key),
+        Ordering::Equal => {
+            if node.right.is_some() {
+                node.key = remove_min(&mut node.right);
+            } else {
+                *t = node.left.take();
+            }
+            true
+        }
+    };
+    if res {
+        balance(t);
+    }
+    res
+}
+
+/// An ordered set supporting order statistics and predecessor and successor queries.
+///
+/// Given keys of a totally ordered type `K`, this data structure supports:
+/// - Insertion and deletion: `insert(x)` and `remove(&x)`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest key, and `rank(&x)` returns the
+///   number of keys less than `x`
+/// - Neighbors: `predecessor(&x)` and `successor(&x)` return the nearest keys less than and
+///   greater than `x`
+///
+/// All operations run in O(log n) worst-case time. This is a weight-balanced tree storing
+/// the size of every subtree, whose children differ in weight by a factor of at most 3, so
+/// unlike [`TreapSet`](crate::treap::TreapSet) it needs no randomness.
+#[derive(Clone)]
+pub struct OrderStatSet<K> {
+    root: Link<K>,
+}
+
+impl<K: Ord> OrderStatSet<K> {
+    /// Creates a new empty set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Inserts `x`, and returns `true` if it was not present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn insert(&mut self, x: K) -> bool {
+        insert(&mut self.root, x)
+    }
+
+    /// Removes `x`, and returns `true` if it was present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn remove(&mut self, x: &K) -> bool {
+        remove(&mut self.root, x)
+    }
+
+    /// Returns `true` if the set contains `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn contains(&self, x: &K) -> bool {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            t = match x.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// Returns the number of keys less than `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn rank(&self, x: &K) -> usize {
+        let mut res = 0;
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            if *x <= node.key {
+                t = node.left.as_deref();
+            } else {
+                res += size(&node.left) + 1;
+                t = node.right.as_deref();
+            }
+        }
+        res
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest key, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn kth(&self, mut k: usize) -> Option<&K> {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            let ls = size(&node.left);
+            match k.cmp(&ls) {
+                Ordering::Less => t = node.left.as_deref(),
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Greater => {
+                    k -= ls + 1;
+                    t = node.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the largest key less than `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn predecessor(&self, x: &K) -> Option<&K> {
+        let mut res = None;
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            if node.key < *x {
+                res = Some(&node.key);
+                t = node.right.as_deref();
+            } else {
+                t = node.left.as_deref();
+            }
+        }
+        res
+    }
+
+    /// Returns the smallest key greater than `x`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn successor(&self, x: &K) -> Option<&K> {
+        let mut res = None;
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            if *x < node.key {
+                res = Some(&node.key);
+                t = node.left.as_deref();
+            } else {
+                t = node.right.as_deref();
+            }
+        }
+        res
+    }
+
+    /// Returns the smallest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn first(&self) -> Option<&K> {
+        self.kth(0)
+    }
+
+    /// Returns the largest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn last(&self) -> Option<&K> {
+        self.kth(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the keys, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        let mut stack = Vec::new();
+        let mut t = self.root.as_deref();
+        std::iter::from_fn(move || {
+            while let Some(node) = t {
+                stack.push(node);
+                t = node.left.as_deref();
+            }
+            let node = stack.pop()?;
+            t = node.right.as_deref();
+            Some(&node.key)
+        })
+    }
+
+    /// Returns the number of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Ord> Default for OrderStatSet<K> {
+    /// Creates a new empty set.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for OrderStatSet<K> {
+    /// Creates a new set from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.insert(x);
+        }
+        res
+    }
+}
+
+impl<K: Ord + std::fmt::Debug> std::fmt::Debug for OrderStatSet<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,447 @@
+use crate::algebra::Monoid;
+
+/// Index of a missing child.
+const NIL: u32 = u32::MAX;
+
+/// A node of a quadtree covering a rectangle of cells.
+#[derive(Clone, Debug)]
+struct Node<S> {
+    /// `op` of every value in the rectangle of this node.
+    val: S,
+    /// Indices of the children in the arena, or `NIL`, for the lower and upper halves in `x`
+    /// and then in `y`.
+    children: [u32; 4],
+}
+
+/// A rectangle of cells `x0..x1` by `y0..y1`, as `[x0, x1, y0, y1]`.
+type Rect = [u64; 4];
+
+/// Returns the midpoints of a rectangle, the starts of its upper halves.
+#[inline(always)]
+fn mid([x0, x1, y0, y1]: Rect) -> (u64, u64) {
+    (x0 + ((x1 - x0) >> 1), y0 + ((y1 - y0) >> 1))
+}
+
+/// Returns `true` if a rectangle is a single cell.
+#[inline(always)]
+fn is_cell([x0, x1, y0, y1]: Rect) -> bool {
+    x1 - x0 == 1 && y1 - y0 == 1
+}
+
+/// Returns the child of a node covering `rect` containing the cell `(x, y)`.
+#[inline(always)]
+fn quadrant(rect: Rect, x: u64, y: u64) -> usize {
+    let (mx, my) = mid(rect);
+    (x >= mx) as usize | ((y >= my) as usize) << 1
+}
+
+/// Returns the rectangle covered by child `q` of a node covering `rect`.
+#[inline(always)]
+fn child_rect(rect: Rect, q: usize) -> Rect {
+    let [x0, x1, y0, y1] = rect;
+    let (mx, my) = mid(rect);
+    let (x0, x1) = if q & 1 == 0 { (x0, mx) } else { (mx, x1) };
+    let (y0, y1) = if q & 2 == 0 { (y0, my) } else { (my, y1) };
+    [x0, x1, y0, y1]
+}
+
+/// Returns the half-open range of `range`, for coordinates below `n`.
+#[inline]
+fn bounds(range: impl std::ops::RangeBounds<u64>, n: u64) -> (u64, u64) {
+    let l = match range.start_bound() {
+        std::ops::Bound::Unbounded => 0,
+        std::ops::Bound::Included(&x) => x,
+        std::ops::Bound::Excluded(&x) => x + 1,
+    };
+    let r = match range.end_bound() {
+        std::ops::Bound::Unbounded => n,
+        std::ops::Bound::Included(&x) => x + 1,
+        std::ops::Bound::Excluded(&x) => x,
+    };
+    debug_assert!(
+        l <= r,
+        "left bound must be less than or equal to right bound: l={}, r={}",
+        l,
+        r,
+    );
+    debug_assert!(r <= n, "index out of bounds: r={}, len={}", r, n);
+    (l, r)
+}
+
+/// A sparse quadtree over a huge grid, for monoid folds over rectangles.
+///
+/// Given a monoid `(S, op, id)` and a grid of `w * h` cells with `w` and `h` up to
+/// `u64::MAX`, this data structure supports:
+/// - Point update: `set(x, y, v)` stores the point `(x, y)` with value `v`
+/// - Point operation: `operate(x, y, v)` sets `a[x][y] = op(a[x][y], v)`
+/// - Point removal: `remove(x, y)` removes the point `(x, y)` and frees its nodes
+/// - Rectangle query: `rect_fold(xl..xr, yl..yr)` returns `op` of `a[x][y]` over the stored
+///   points with `xl <= x < xr` and `yl <= y < yr`
+///
+/// Every cell without a point holds `S::id()`. Each node halves its rectangle in both
+/// dimensions, down to single cells, and only nodes containing a point are allocated, so
+/// that a point update runs in O(log(w + h)) time and allocates at most that many nodes,
+/// without coordinate compression. A rectangle query visits only nodes partially covering
+/// it, which are O(min(k log(w + h), p + log(w + h))) for `k` stored points and a rectangle
+/// of perimeter `p`.
+///
+/// Values are folded in an order depending on the layout of the tree, so `op` should be
+/// commutative. Nodes live in a single arena and refer to their children by `u32` indices,
+/// and nodes freed by removals are reused, so fewer than `2^32 - 1` nodes can be alive.
+#[derive(Clone, Debug)]
+pub struct QuadTree<S: Monoid> {
+    nodes: Vec<Node<S>>,
+    /// Indices of the freed nodes in the arena.
+    free: Vec<u32>,
+    /// Index of the root in the arena, or `NIL`.
+    root: u32,
+    w: u64,
+    h: u64,
+    /// Number of stored points.
+    len: usize,
+}
+
+impl<S: Monoid> QuadTree<S> {
+    /// Creates a new empty quadtree over a grid of `w` by `h` cells.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(w: u64, h: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NIL,
+            w,
+            h,
+            len: 0,
+        }
+    }
+
+    /// Stores the point `(x, y)` with value `v`, replacing its previous value.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= width()` or `y >= height()` in debug builds.
+    #[inline]
+    pub fn set(&mut self, x: u64, y: u64, v: S) {
+        self.point_update(x, y, move |_| v);
+    }
+
+    /// Applies `op(a[x][y], v)` to the point `(x, y)`, storing it with value `v` if absent.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= width()` or `y >= height()` in debug builds.
+    #[inline]
+    pub fn operate(&mut self, x: u64, y: u64, v: S) {
+        self.point_update(x, y, move |a| S::op(a, &v));
+    }
+
+    /// Returns the value of the point `(x, y)`, or `S::id()` if it is not stored.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= width()` or `y >= height()` in debug builds.
+    pub fn get(&self, x: u64, y: u64) -> S {
+        match self.path(x, y).1 {
+            Some(t) => self.nodes[t as usize].val.clone(),
+            None => S::id(),
+        }
+    }
+
+    /// Returns `true` if the point `(x, y)` is stored.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= width()` or `y >= height()` in debug builds.
+    pub fn contains(&self, x: u64, y: u64) -> bool {
+        self.path(x, y).1.is_some()
+    }
+
+    /// Removes the point `(x, y)`, and returns its value, if any.
+    ///
+    /// The nodes containing no other point are freed for reuse.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h))
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x >= width()` or `y >= height()` in debug builds.
+    pub fn remove(&mut self, x: u64, y: u64) -> Option<S> {
+        let (path, leaf) = self.path(x, y);
+        let leaf = leaf?;
+        let res = std::mem::replace(&mut self.nodes[leaf as usize].val, S::id());
+        self.free.push(leaf);
+        self.len -= 1;
+        let mut emptied = true;
+        for &(t, q) in path.iter().rev() {
+            if emptied {
+                self.nodes[t as usize].children[q] = NIL;
+                emptied = self.nodes[t as usize].children.iter().all(|&c| c == NIL);
+                if emptied {
+                    self.nodes[t as usize].val = S::id();
+                    self.free.push(t);
+                    continue;
+                }
+            }
+            self.update(t);
+        }
+        if emptied {
+            self.root = NIL;
+        }
+        Some(res)
+    }
+
+    /// Returns `op` of the values of the points in the rectangle `xs` by `ys`.
+    ///
+    /// Returns `S::id()` if the rectangle is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(min(k log(w + h), p + log(w + h))) for `k` stored points and a rectangle of
+    /// perimeter `p`
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is invalid or out of bounds in debug builds.
+    pub fn rect_fold(
+        &self,
+        xs: impl std::ops::RangeBounds<u64>,
+        ys: impl std::ops::RangeBounds<u64>,
+    ) -> S {
+        let (xl, xr) = bounds(xs, self.w);
+        let (yl, yr) = bounds(ys, self.h);
+        if xl == xr || yl == yr {
+            return S::id();
+        }
+        self.fold_rec(self.root, [0, self.w, 0, self.h], [xl, xr, yl, yr])
+    }
+
+    /// Returns `op` of the values of all stored points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        if self.root == NIL {
+            S::id()
+        } else {
+            self.nodes[self.root as usize].val.clone()
+        }
+    }
+
+    /// Returns an iterator over the stored points and their values, in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log(w + h)) per item
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64, &S)> {
+        let mut stack = Vec::new();
+        if self.root != NIL {
+            stack.push((self.root, [0, self.w, 0, self.h]));
+        }
+        std::iter::from_fn(move || {
+            while let Some((t, rect)) = stack.pop() {
+                let node = &self.nodes[t as usize];
+                if is_cell(rect) {
+                    return Some((rect[0], rect[2], &node.val));
+                }
+                for (q, &c) in node.children.iter().enumerate() {
+                    if c != NIL {
+                        stack.push((c, child_rect(rect, q)));
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Removes all points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(number of allocated nodes)
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = NIL;
+        self.len = 0;
+    }
+
+    /// Returns the number of columns `w` of the grid.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn width(&self) -> u64 {
+        self.w
+    }
+
+    /// Returns the number of rows `h` of the grid.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn height(&self) -> u64 {
+        self.h
+    }
+
+    /// Returns the number of stored points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no point is stored.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn check(&self, x: u64, y: u64) {
+        debug_assert!(x < self.w, "index out of bounds: x={}, width={}", x, self.w,);
+        debug_assert!(
+            y < self.h,
+            "index out of bounds: y={}, height={}",
+            y,
+            self.h,
+        );
+    }
+
+    /// Returns a new node holding `S::id()`, reusing a freed one if any.
+    #[inline]
+    fn alloc(&mut self) -> u32 {
+        if let Some(t) = self.free.pop() {
+            return t;
+        }
+        debug_assert!(
+            self.nodes.len() < NIL as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(Node {
+            val: S::id(),
+            children: [NIL; 4],
+        });
+        (self.nodes.len() - 1) as u32
+    }
+
+    #[inline]
+    fn update(&mut self, t: u32) {
+        let children = self.nodes[t as usize].children;
+        let val = children
+            .iter()
+            .filter(|&&c| c != NIL)
+            .fold(S::id(), |acc, &c| S::op(&acc, &self.nodes[c as usize].val));
+        self.nodes[t as usize].val = val;
+    }
+
+    /// Returns the nodes above the cell `(x, y)` with the children leading to it, and its
+    /// leaf, if any.
+    fn path(&self, x: u64, y: u64) -> (Vec<(u32, usize)>, Option<u32>) {
+        self.check(x, y);
+        let mut path = Vec::new();
+        let mut rect = [0, self.w, 0, self.h];
+        let mut t = self.root;
+        while t != NIL {
+            if is_cell(rect) {
+                return (path, Some(t));
+            }
+            let q = quadrant(rect, x, y);
+            rect = child_rect(rect, q);
+            path.push((t, q));
+            t = self.nodes[t as usize].children[q];
+        }
+        (path, None)
+    }
+
+    fn point_update<F>(&mut self, x: u64, y: u64, f: F)
+    where
+        F: FnOnce(&S) -> S,
+    {
+        self.check(x, y);
+        if self.root == NIL {
+            self.root = self.alloc();
+        }
+        // Both halvings end within 64 levels.
+        let mut path = [NIL; 64];
+        let mut depth = 0;
+        let mut rect = [0, self.w, 0, self.h];
+        let mut t = self.root;
+        while !is_cell(rect) {
+            path[depth] = t;
+            depth += 1;
+            let q = quadrant(rect, x, y);
+            rect = child_rect(rect, q);
+            let child = self.nodes[t as usize].children[q];
+            t = if child == NIL {
+                let c = self.alloc();
+                self.nodes[t as usize].children[q] = c;
+                if is_cell(rect) {
+                    self.len += 1;
+                }
+                c
+            } else {
+                child
+            };
+        }
+        if depth == 0 {
+            // The grid is a single cell, whose leaf is the root.
+            self.len = 1;
+        }
+        let node = &mut self.nodes[t as usize];
+        node.val = f(&node.val);
+        for &t in path[..depth].iter().rev() {
+            self.update(t);
+        }
+    }
+
+    /// Returns `op` of the values in node `t`, covering `rect`, within `query`.
+    fn fold_rec(&self, t: u32, rect: Rect, query: Rect) -> S {
+        let [x0, x1, y0, y1] = rect;
+        let [xl, xr, yl, yr] = query;
+        if t == NIL || x1 <= xl || xr <= x0 || y1 <= yl || yr <= y0 {
+            return S::id();
+        }
+        let node = &self.nodes[t as usize];
+        if xl <= x0 && x1 <= xr && yl <= y0 && y1 <= yr {
+            return node.val.clone();
+        }
+        let mut res = S::id();
+        for (q, &c) in node.children.iter().enumerate() {
+            res = S::op(&res, &self.fold_rec(c, child_rect(rect, q), query));
+        }
+        res
+    }
+}
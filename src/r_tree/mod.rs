@@ -0,0 +1,333 @@
+use crate::num::Numeric;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Maximum number of children of a node.
+const B: usize = 16;
+
+/// An axis-aligned box, as its lower and upper corners, both inclusive.
+type Rect<T, const D: usize> = ([T; D], [T; D]);
+
+#[inline(always)]
+fn cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+/// Returns `true` if the boxes `a` and `b` share a point.
+#[inline]
+fn intersects<T: Numeric, const D: usize>(a: &Rect<T, D>, b: &Rect<T, D>) -> bool {
+    (0..D).all(|d| a.0[d] <= b.1[d] && b.0[d] <= a.1[d])
+}
+
+/// Returns `true` if the box `a` contains the box `b`.
+#[inline]
+fn contains<T: Numeric, const D: usize>(a: &Rect<T, D>, b: &Rect<T, D>) -> bool {
+    (0..D).all(|d| a.0[d] <= b.0[d] && b.1[d] <= a.1[d])
+}
+
+/// Returns the squared Euclidean distance from the point `p` to the box `r`.
+#[inline]
+fn dist2<T: Numeric, const D: usize>(r: &Rect<T, D>, p: &[T; D]) -> T {
+    let mut res = T::ZERO;
+    for (d, &x) in p.iter().enumerate() {
+        let x = if x < r.0[d] {
+            r.0[d] - x
+        } else if r.1[d] < x {
+            x - r.1[d]
+        } else {
+            T::ZERO
+        };
+        res = res + x * x;
+    }
+    res
+}
+
+/// Returns the smallest box containing the boxes `v`.
+fn bounding<T: Numeric, const D: usize>(v: &[Rect<T, D>]) -> Rect<T, D> {
+    let mut res = v[0];
+    for r in &v[1..] {
+        for d in 0..D {
+            if r.0[d] < res.0[d] {
+                res.0[d] = r.0[d];
+            }
+            if res.1[d] < r.1[d] {
+                res.1[d] = r.1[d];
+            }
+        }
+    }
+    res
+}
+
+/// Orders `v` by the Sort-Tile-Recursive tiling along the dimensions `dim..D`, so that every
+/// run of `chunk` consecutive boxes is a compact tile.
+fn tile<T: Numeric, const D: usize>(v: &mut [(Rect<T, D>, usize)], chunk: usize, dim: usize) {
+    // Twice the center, ordered as the center.
+    let center = |r: &Rect<T, D>| r.0[dim] + r.1[dim];
+    v.sort_unstable_by(|a, b| cmp(&center(&a.0), &center(&b.0)));
+    if dim + 1 == D {
+        return;
+    }
+    // `slabs^(D - dim)` tiles cover the pages, split into `slabs` slabs along `dim`.
+    let pages = v.len().div_ceil(chunk);
+    let rest = (D - dim) as u32;
+    let mut slabs = (pages as f64).powf(1.0 / rest as f64).round().max(1.0) as usize;
+    while slabs.pow(rest) < pages {
+        slabs += 1;
+    }
+    for slab in v.chunks_mut(pages.div_ceil(slabs) * chunk) {
+        tile(slab, chunk, dim + 1);
+    }
+}
+
+/// Orders `v`, holding at most `cap` boxes for a power `cap` of `B`, so that every aligned
+/// run of `B^h` boxes is a compact tile, for every `h`.
+fn pack<T: Numeric, const D: usize>(v: &mut [(Rect<T, D>, usize)], cap: usize) {
+    if cap == 1 {
+        return;
+    }
+    let chunk = cap / B;
+    tile(v, chunk, 0);
+    for run in v.chunks_mut(chunk) {
+        pack(run, chunk);
+    }
+}
+
+/// A node or box at some level, ordered so that the smallest distance is the greatest.
+struct Candidate<T>(T, usize, usize);
+
+impl<T: PartialOrd> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for Candidate<T> {}
+
+impl<T: PartialOrd> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(&other.0, &self.0)
+    }
+}
+
+/// A static R-tree over axis-aligned boxes in `D` dimensions.
+///
+/// Given boxes `(lo, hi)` with lower and upper corners of a primitive numeric type `T`, both
+/// inclusive, this data structure supports:
+/// - Overlap: `intersecting(&q)` returns the boxes sharing a point with the box `q`
+/// - Containment: `contained_in(&q)` returns the boxes inside `q`, and `containing(&q)` the
+///   boxes around `q`, such as those containing a point for `q = (p, p)`
+/// - Nearest boxes: `nearest(&p)` and `k_nearest(&p, k)` return the boxes closest to the
+///   point `p` in Euclidean distance
+///
+/// Boxes are identified by their index in the input, and distances are returned squared, in
+/// `T`, as for [`KdTree`](crate::kd_tree::KdTree). Coordinates must not be NaN, and the sum
+/// of two coordinates must fit in `T`.
+///
+/// Every node holds the bounding box of up to 16 children. The tree is bulk-loaded by
+/// Sort-Tile-Recursive packing from the root down: the boxes are sorted along the first
+/// dimension and cut into slabs, each slab sorted along the next dimension and cut again,
+/// until each tile fills one child, and the tiles are packed in turn. Every node but the last
+/// of each level is then full, and the nodes are laid out level by level, so that the
+/// children of node `i` are the nodes `16i..16i + 16` of the level below. Built in
+/// O(n log² n) time. Unlike a [`KdTree`](crate::kd_tree::KdTree) of points, boxes may
+/// overlap, so that queries descend into every child whose bounding box may hold a result;
+/// on boxes that overlap little, queries visit O(log n + m) nodes for `m` results.
+#[derive(Clone, Debug)]
+pub struct RTree<T, const D: usize> {
+    /// `levels[0]` holds the boxes, and `levels[h][i]` the bounding box of
+    /// `levels[h - 1][B * i..B * (i + 1)]`; the last level is the root.
+    levels: Vec<Box<[Rect<T, D>]>>,
+    /// `index[i]` is the index in the input of the box `levels[0][i]`.
+    index: Box<[usize]>,
+}
+
+impl<T: Numeric, const D: usize> RTree<T, D> {
+    /// Creates a new tree from a vec of boxes `(lo, hi)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log² n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo[d] > hi[d]` for a box and a dimension `d` in debug builds.
+    pub fn from_vec(v: Vec<([T; D], [T; D])>) -> Self {
+        let mut entries: Vec<_> = v.into_iter().zip(0..).collect();
+        debug_assert!(
+            entries
+                .iter()
+                .all(|(r, _)| (0..D).all(|d| r.0[d] <= r.1[d])),
+            "box with a lower corner above its upper corner",
+        );
+        let mut cap = 1;
+        while cap < entries.len() {
+            cap *= B;
+        }
+        pack(&mut entries, cap);
+        let (boxes, index): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+        let mut levels = vec![boxes.into_boxed_slice()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels.last().unwrap().chunks(B).map(bounding).collect();
+            levels.push(next);
+        }
+        Self {
+            levels,
+            index: index.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new tree from a slice of boxes `(lo, hi)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log² n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo[d] > hi[d]` for a box and a dimension `d` in debug builds.
+    pub fn from_slice(v: &[([T; D], [T; D])]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Returns an iterator over the indices of the boxes sharing a point with `q`, in
+    /// arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n + m) for `m` results on boxes that overlap little
+    pub fn intersecting(&self, q: &([T; D], [T; D])) -> impl Iterator<Item = usize> {
+        let q = *q;
+        self.search(move |r| intersects(r, &q), move |r| intersects(r, &q))
+    }
+
+    /// Returns an iterator over the indices of the boxes inside `q`, in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n + m) for `m` results on boxes that overlap little
+    pub fn contained_in(&self, q: &([T; D], [T; D])) -> impl Iterator<Item = usize> {
+        let q = *q;
+        self.search(move |r| intersects(r, &q), move |r| contains(&q, r))
+    }
+
+    /// Returns an iterator over the indices of the boxes containing `q`, in arbitrary order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n + m) for `m` results on boxes that overlap little
+    pub fn containing(&self, q: &([T; D], [T; D])) -> impl Iterator<Item = usize> {
+        let q = *q;
+        self.search(move |r| contains(r, &q), move |r| contains(r, &q))
+    }
+
+    /// Returns the index of a box closest to the point `p` and its squared distance, zero if
+    /// the box contains `p`, or `None` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) on boxes that overlap little
+    #[inline]
+    pub fn nearest(&self, p: &[T; D]) -> Option<(usize, T)> {
+        self.k_nearest(p, 1).pop()
+    }
+
+    /// Returns the indices of the `min(k, n)` boxes closest to the point `p` and their
+    /// squared distances, in ascending order of distance, ties broken arbitrarily.
+    ///
+    /// # Time complexity
+    ///
+    /// O((k + log n) log n) on boxes that overlap little
+    pub fn k_nearest(&self, p: &[T; D], k: usize) -> Vec<(usize, T)> {
+        let mut res = Vec::with_capacity(k.min(self.len()));
+        let mut heap = BinaryHeap::new();
+        let top = self.levels.len() - 1;
+        if let Some(r) = self.levels[top].first() {
+            heap.push(Candidate(dist2(r, p), top, 0));
+        }
+        // Nodes are no farther than their descendants, so boxes leave in order of distance.
+        while res.len() < k
+            && let Some(Candidate(d, h, i)) = heap.pop()
+        {
+            if h == 0 {
+                res.push((self.index[i], d));
+                continue;
+            }
+            let below = &self.levels[h - 1];
+            for j in B * i..(B * (i + 1)).min(below.len()) {
+                heap.push(Candidate(dist2(&below[j], p), h - 1, j));
+            }
+        }
+        res
+    }
+
+    /// Returns the number of boxes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the indices of the boxes satisfying `hit`, descending into
+    /// the nodes whose bounding box satisfies `enter`.
+    fn search<E, H>(&self, enter: E, hit: H) -> impl Iterator<Item = usize>
+    where
+        E: Fn(&Rect<T, D>) -> bool,
+        H: Fn(&Rect<T, D>) -> bool,
+    {
+        let top = self.levels.len() - 1;
+        let mut stack = Vec::new();
+        if let Some(r) = self.levels[top].first()
+            && if top == 0 { hit(r) } else { enter(r) }
+        {
+            stack.push((top, 0));
+        }
+        std::iter::from_fn(move || {
+            while let Some((h, i)) = stack.pop() {
+                if h == 0 {
+                    return Some(self.index[i]);
+                }
+                let below = &self.levels[h - 1];
+                for j in B * i..(B * (i + 1)).min(below.len()) {
+                    if if h == 1 {
+                        hit(&below[j])
+                    } else {
+                        enter(&below[j])
+                    } {
+                        stack.push((h - 1, j));
+                    }
+                }
+            }
+            None
+        })
+    }
+}
+
+impl<T: Numeric, const D: usize> FromIterator<([T; D], [T; D])> for RTree<T, D> {
+    /// Creates a new tree from an iterator of boxes `(lo, hi)`, indexed in iteration order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log² n)
+    fn from_iter<I: IntoIterator<Item = ([T; D], [T; D])>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
@@ -0,0 +1,330 @@
+/// A node of a binary trie over the bits of the values, from the highest bit down.
+///
+/// Node 0 is a sentinel with count 0 whose children are itself, standing for every missing
+/// node.
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    child: [u32; 2],
+    /// Number of values in the subtrie.
+    count: u32,
+}
+
+/// A sequence of integers supporting point updates and range k-th smallest queries.
+///
+/// Given a sequence `a` of `n` values less than `2^bits`, this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Quantile: `kth_smallest(l..r, k)` returns the `k`-th smallest value in `a[l..r]`
+/// - Range frequency: `range_freq(l..r, lo..hi)` returns the number of values in `lo..hi`
+///
+/// This is the updatable counterpart of [`WaveletMatrix`](crate::wavelet_matrix::WaveletMatrix).
+/// A Fenwick tree over the indices stores, in each of its slots, a binary trie counting the
+/// values of the slot. A range query descends the O(log n) tries of both prefixes together,
+/// so all operations run in O(log n · bits) time.
+///
+/// Every update allocates up to `(bits + 1) · ceil(log2(n + 1))` trie nodes of 12 bytes, which
+/// are never freed. Compressing the values, including those of future updates, into a small
+/// range keeps `bits` and the memory small.
+#[derive(Clone, Debug)]
+pub struct RangeKth {
+    values: Box<[u64]>,
+    /// Root of the trie of each Fenwick slot, 1-indexed; slot `i` covers `a[i - lowbit(i)..i]`.
+    roots: Box<[u32]>,
+    nodes: Vec<Node>,
+    bits: u32,
+}
+
+impl RangeKth {
+    /// Creates a new sequence of `n` zeros, with values less than `2^bits`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`.
+    pub fn new(n: usize, bits: u32) -> Self {
+        Self::from_vec(vec![0; n], bits)
+    }
+
+    /// Creates a new sequence from a vec, with values less than `2^bits`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`, or if a value is at least `2^bits` in debug builds.
+    pub fn from_vec(v: Vec<u64>, bits: u32) -> Self {
+        assert!(bits <= 64, "bits must be at most 64: bits={}", bits);
+        let n = v.len();
+        let mut res = Self {
+            values: v.into_boxed_slice(),
+            roots: vec![0; n + 1].into_boxed_slice(),
+            nodes: vec![Node::default()],
+            bits,
+        };
+        for i in 0..n {
+            let x = res.values[i];
+            res.check(x);
+            res.modify(i, x, true);
+        }
+        res
+    }
+
+    /// Creates a new sequence from a slice, with values less than `2^bits`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits > 64`, or if a value is at least `2^bits` in debug builds.
+    pub fn from_slice(v: &[u64], bits: u32) -> Self {
+        Self::from_vec(v.to_vec(), bits)
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` or `x >= 2^bits` in debug builds.
+    pub fn set(&mut self, i: usize, x: u64) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.check(x);
+        let old = std::mem::replace(&mut self.values[i], x);
+        if old != x {
+            self.modify(i, old, false);
+            self.modify(i, x, true);
+        }
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> u64 {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.values[i]
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest value in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds, or `k` is not less than the length
+    /// of the range, in debug builds.
+    pub fn kth_smallest(&self, range: impl std::ops::RangeBounds<usize>, mut k: usize) -> u64 {
+        let (l, r) = self.bounds(range);
+        debug_assert!(k < r - l, "k out of bounds: k={}, len={}", k, r - l,);
+        let (mut plus, mut minus) = (self.prefix_roots(r), self.prefix_roots(l));
+        let mut x = 0;
+        for b in (0..self.bits).rev() {
+            let zeros = self.sum(&plus, 0) - self.sum(&minus, 0);
+            let c = usize::from(k >= zeros);
+            if c == 1 {
+                k -= zeros;
+                x |= 1 << b;
+            }
+            for t in plus.iter_mut().chain(minus.iter_mut()) {
+                *t = self.nodes[*t as usize].child[c];
+            }
+        }
+        x
+    }
+
+    /// Returns the number of values in `values` in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n · bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_freq(
+        &self,
+        range: impl std::ops::RangeBounds<usize>,
+        values: impl std::ops::RangeBounds<u64>,
+    ) -> usize {
+        let (l, r) = self.bounds(range);
+        let lo = match values.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x as u128,
+            std::ops::Bound::Excluded(&x) => x as u128 + 1,
+        };
+        let hi = match values.end_bound() {
+            std::ops::Bound::Unbounded => u128::MAX,
+            std::ops::Bound::Included(&x) => x as u128 + 1,
+            std::ops::Bound::Excluded(&x) => x as u128,
+        };
+        if hi <= lo {
+            return 0;
+        }
+        (self.count_less(r, hi) - self.count_less(l, hi))
+            - (self.count_less(r, lo) - self.count_less(l, lo))
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the sequence is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn check(&self, x: u64) {
+        debug_assert!(
+            self.bits == 64 || x >> self.bits == 0,
+            "value out of bounds: x={}, bits={}",
+            x,
+            self.bits,
+        );
+    }
+
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+
+    /// Inserts or removes `x` at index `i` in the tries of every slot covering `i`.
+    fn modify(&mut self, i: usize, x: u64, insert: bool) {
+        let mut j = i + 1;
+        while j < self.roots.len() {
+            if self.roots[j] == 0 {
+                self.roots[j] = self.alloc();
+            }
+            let mut t = self.roots[j];
+            for b in (0..=self.bits).rev() {
+                let node = &mut self.nodes[t as usize];
+                if insert {
+                    node.count += 1;
+                } else {
+                    node.count -= 1;
+                }
+                if b == 0 {
+                    break;
+                }
+                let c = ((x >> (b - 1)) & 1) as usize;
+                if node.child[c] == 0 {
+                    let child = self.alloc();
+                    self.nodes[t as usize].child[c] = child;
+                }
+                t = self.nodes[t as usize].child[c];
+            }
+            j += j & j.wrapping_neg();
+        }
+    }
+
+    fn alloc(&mut self) -> u32 {
+        debug_assert!(
+            self.nodes.len() < u32::MAX as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(Node::default());
+        (self.nodes.len() - 1) as u32
+    }
+
+    /// Returns the roots of the slots whose ranges partition `a[..r]`.
+    #[inline]
+    fn prefix_roots(&self, mut r: usize) -> Vec<u32> {
+        let mut roots = Vec::with_capacity(usize::BITS as usize);
+        while r > 0 {
+            roots.push(self.roots[r]);
+            r &= r - 1;
+        }
+        roots
+    }
+
+    /// Returns the total count of child `c` of the given nodes.
+    #[inline]
+    fn sum(&self, nodes: &[u32], c: usize) -> usize {
+        nodes
+            .iter()
+            .map(|&t| self.nodes[self.nodes[t as usize].child[c] as usize].count as usize)
+            .sum()
+    }
+
+    /// Returns the number of values less than `x` in `a[..r]`.
+    fn count_less(&self, r: usize, x: u128) -> usize {
+        let mut roots = self.prefix_roots(r);
+        if x >> self.bits != 0 {
+            return roots
+                .iter()
+                .map(|&t| self.nodes[t as usize].count as usize)
+                .sum();
+        }
+        let mut res = 0;
+        for b in (0..self.bits).rev() {
+            let c = ((x >> b) & 1) as usize;
+            if c == 1 {
+                res += self.sum(&roots, 0);
+            }
+            for t in &mut roots {
+                *t = self.nodes[*t as usize].child[c];
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,85 @@
+//! Traits for writing code generically over the range query structures of this crate.
+//!
+//! Every structure answering range folds over `usize` indices with `&self` implements
+//! [`RangeFold`], and those that can also overwrite an element implement [`PointUpdate`].
+//! The trait methods forward to the inherent methods of the same name, so swapping the
+//! backing structure only changes its type.
+
+use crate::algebra::{Action, Group, Idempotent, Monoid};
+use crate::fenwick_tree::FenwickTree;
+use crate::segment_tree::{
+    FrozenSegmentTree, LazySegmentTree, RangeAssignSegmentTree, SegmentTree, SegmentTreeWith,
+    VebSegmentTree,
+};
+use crate::sparse_table::SparseTable;
+use crate::sqrt_tree::SqrtTree;
+
+/// A sequence answering point and range fold queries.
+pub trait RangeFold<S> {
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the sequence is empty.
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value at index `i`.
+    fn get(&self, i: usize) -> S;
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S;
+}
+
+/// A [`RangeFold`] sequence whose elements can be overwritten.
+pub trait PointUpdate<S>: RangeFold<S> {
+    /// Sets the value at index `i` to `x`.
+    fn set(&mut self, i: usize, x: S);
+}
+
+/// Implements [`RangeFold`] by forwarding to the inherent methods.
+macro_rules! impl_range_fold {
+    ([$($g:tt)*] $t:ty) => {
+        impl<$($g)*> RangeFold<S> for $t {
+            #[inline(always)]
+            fn len(&self) -> usize {
+                <$t>::len(self)
+            }
+
+            #[inline(always)]
+            fn get(&self, i: usize) -> S {
+                <$t>::get(self, i)
+            }
+
+            #[inline(always)]
+            fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+                <$t>::range_fold(self, range)
+            }
+        }
+    };
+}
+
+/// Implements [`RangeFold`] and [`PointUpdate`] by forwarding to the inherent methods.
+macro_rules! impl_point_update {
+    ([$($g:tt)*] $t:ty) => {
+        impl_range_fold!([$($g)*] $t);
+
+        impl<$($g)*> PointUpdate<S> for $t {
+            #[inline(always)]
+            fn set(&mut self, i: usize, x: S) {
+                <$t>::set(self, i, x)
+            }
+        }
+    };
+}
+
+impl_point_update!([S: Group] FenwickTree<S>);
+impl_point_update!([S: Monoid] SegmentTree<S>);
+impl_point_update!([S: Clone, Op: Fn(&S, &S) -> S] SegmentTreeWith<S, Op>);
+impl_point_update!([S: Monoid, F: Action<S>] LazySegmentTree<S, F>);
+impl_point_update!([S: Monoid] RangeAssignSegmentTree<S>);
+impl_point_update!([S: Monoid] VebSegmentTree<S>);
+impl_point_update!([S: Monoid] SqrtTree<S>);
+impl_range_fold!([S: Monoid] FrozenSegmentTree<S>);
+impl_range_fold!([S: Idempotent] SparseTable<S>);
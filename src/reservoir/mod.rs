@@ -0,0 +1,361 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Seed of the samplers created by `new`.
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// A splitmix64 generator.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    #[inline]
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniform float in `(0, 1]`.
+    #[inline]
+    fn unit(&mut self) -> f64 {
+        ((self.next() >> 11) + 1) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns a uniform integer in `0..n`.
+    #[inline]
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// A uniform random sample of fixed size over a stream.
+///
+/// Given a stream of values pushed one at a time, this data structure supports:
+/// - Push: `push(x)` offers the next value of the stream
+/// - Sample: `sample()` returns `min(k, n)` of the `n` values pushed so far, every subset
+///   of that size being equally likely
+///
+/// Once `k` values are kept, the number of values to skip before the next replacement is
+/// drawn directly from its distribution (Li's algorithm L), so that a push runs in O(1)
+/// time and draws random numbers only O(k log(n/k)) times in total. The generator is
+/// seeded, by a fixed seed unless given one, so that runs are reproducible.
+#[derive(Clone, Debug)]
+pub struct ReservoirSampler<T> {
+    sample: Vec<T>,
+    k: usize,
+    /// Number of values pushed.
+    seen: u64,
+    /// Number of values to skip before the next replacement.
+    skip: u64,
+    /// The largest of `k` uniform random keys, of which the kept values hold the smallest.
+    w: f64,
+    rng: Rng,
+}
+
+impl<T> ReservoirSampler<T> {
+    /// Creates a new sampler of `k` values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(k: usize) -> Self {
+        Self::with_seed(k, DEFAULT_SEED)
+    }
+
+    /// Creates a new sampler of `k` values, with the random generator seeded by `seed`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn with_seed(k: usize, seed: u64) -> Self {
+        Self {
+            sample: Vec::with_capacity(k),
+            k,
+            seen: 0,
+            skip: 0,
+            w: 1.0,
+            rng: Rng(seed),
+        }
+    }
+
+    /// Offers the next value of the stream.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn push(&mut self, x: T) {
+        self.seen += 1;
+        if self.sample.len() < self.k {
+            self.sample.push(x);
+            if self.sample.len() == self.k {
+                self.next_skip();
+            }
+        } else if self.skip > 0 {
+            self.skip -= 1;
+        } else if self.k > 0 {
+            let i = self.rng.below(self.k);
+            self.sample[i] = x;
+            self.next_skip();
+        }
+    }
+
+    /// Returns the sampled values, in no particular order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn sample(&self) -> &[T] {
+        &self.sample
+    }
+
+    /// Consumes the sampler and returns the sampled values, in no particular order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn into_sample(self) -> Vec<T> {
+        self.sample
+    }
+
+    /// Returns the number of values pushed.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Returns the size `k` of a full sample.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the number of sampled values, `min(k, seen())`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.sample.len()
+    }
+
+    /// Returns `true` if no value is sampled.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Draws the largest key among the kept values, then the number of values until one
+    /// gets a smaller key.
+    fn next_skip(&mut self) {
+        self.w *= (self.rng.unit().ln() / self.k as f64).exp();
+        // The cast saturates once keys get too small to ever be replaced.
+        self.skip = (self.rng.unit().ln() / (-self.w).ln_1p()) as u64;
+    }
+}
+
+impl<T> Extend<T> for ReservoirSampler<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+/// A value with its random key, ordered so that the smallest key is the greatest.
+#[derive(Clone, Debug)]
+struct Keyed<T> {
+    key: f64,
+    value: T,
+}
+
+impl<T> PartialEq for Keyed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key.total_cmp(&other.key) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Keyed<T> {}
+
+impl<T> PartialOrd for Keyed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Keyed<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.total_cmp(&self.key)
+    }
+}
+
+/// A weighted random sample without replacement of fixed size over a stream.
+///
+/// Given a stream of values with positive weights pushed one at a time, this data structure
+/// supports:
+/// - Push: `push(x, w)` offers the next value of the stream with weight `w`
+/// - Sample: `sample()` returns `min(k, n)` of the `n` values pushed so far, distributed as
+///   if drawn one by one without replacement, each with probability proportional to its
+///   weight among the remaining ones
+///
+/// Every value gets the key `u^(1/w)` for a uniform random `u`, and the values with the `k`
+/// largest keys are kept in a heap (Efraimidis and Spirakis' algorithm A-Res). Keys are kept
+/// as logarithms `ln(u) / w`, which neither underflow nor lose precision for large weights.
+/// Rather than drawing a key for every value, the total weight to skip before the next
+/// replacement is drawn directly from its distribution (algorithm A-ExpJ), so that a push
+/// runs in O(1) time when skipped and O(log k) time when kept, and random numbers are drawn
+/// only O(k log(n/k)) times in total.
+#[derive(Clone, Debug)]
+pub struct WeightedReservoirSampler<T> {
+    /// The kept values, the one with the smallest key on top.
+    heap: BinaryHeap<Keyed<T>>,
+    k: usize,
+    /// Total weight to skip before the next replacement.
+    skip: f64,
+    rng: Rng,
+}
+
+impl<T> WeightedReservoirSampler<T> {
+    /// Creates a new sampler of `k` values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(k: usize) -> Self {
+        Self::with_seed(k, DEFAULT_SEED)
+    }
+
+    /// Creates a new sampler of `k` values, with the random generator seeded by `seed`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn with_seed(k: usize, seed: u64) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(k),
+            k,
+            skip: 0.0,
+            rng: Rng(seed),
+        }
+    }
+
+    /// Offers the next value of the stream, with weight `w`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) if `x` is skipped, and O(log k) otherwise
+    ///
+    /// # Panics
+    ///
+    /// Panics if `w` is not positive and finite in debug builds.
+    pub fn push(&mut self, x: T, w: f64) {
+        debug_assert!(
+            w > 0.0 && w.is_finite(),
+            "weight must be positive and finite: w={}",
+            w,
+        );
+        if self.heap.len() < self.k {
+            let key = self.rng.unit().ln() / w;
+            self.heap.push(Keyed { key, value: x });
+            if self.heap.len() == self.k {
+                self.next_skip();
+            }
+            return;
+        }
+        if self.k == 0 {
+            return;
+        }
+        self.skip -= w;
+        if self.skip > 0.0 {
+            return;
+        }
+        // The key of `x` is conditioned to exceed the threshold `t`: `u` is uniform in
+        // `(t^w, 1]`.
+        let t = self.heap.peek().unwrap().key;
+        let tw = (t * w).exp();
+        let u = tw + (1.0 - tw) * self.rng.unit();
+        let key = (u.ln() / w).max(t);
+        *self.heap.peek_mut().unwrap() = Keyed { key, value: x };
+        self.next_skip();
+    }
+
+    /// Returns an iterator over the sampled values, in no particular order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) per item
+    pub fn sample(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|e| &e.value)
+    }
+
+    /// Consumes the sampler and returns the sampled values, in no particular order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k)
+    pub fn into_sample(self) -> Vec<T> {
+        self.heap.into_iter().map(|e| e.value).collect()
+    }
+
+    /// Returns the size `k` of a full sample.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the number of sampled values.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if no value is sampled.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Draws the total weight until a value gets a key above the smallest kept one `t`,
+    /// which for weight `w` happens with probability `1 - t^w`.
+    fn next_skip(&mut self) {
+        let t = self.heap.peek().unwrap().key;
+        self.skip = self.rng.unit().ln() / t;
+    }
+}
+
+impl<T> Extend<(T, f64)> for WeightedReservoirSampler<T> {
+    fn extend<I: IntoIterator<Item = (T, f64)>>(&mut self, iter: I) {
+        for (x, w) in iter {
+            self.push(x, w);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::algebra::Monoid;
+use crate::monoids::{MaxIndex, MaxSubarray, MinIndex};
+use crate::num::Numeric;
+use crate::segment_tree::SegmentTree;
+
+/// The operation at a point of the timeline.
+#[derive(Clone, Copy, Debug)]
+enum Op<T> {
+    None,
+    /// Push of a value, which is either in the present queue or popped at some point.
+    Push {
+        value: T,
+        live: bool,
+    },
+    Pop,
+}
+
+/// A partially retroactive priority queue over a discrete timeline.
+///
+/// The queue is described by a timeline of `n` points, each holding nothing, a push of a
+/// value, or a pop of the minimum. This data structure supports:
+/// - Retroactive update: `insert_push(t, x)`, `insert_pop(t)` and `remove(t)` add or remove
+///   an operation at time `t`
+/// - Present query: `min()` returns the minimum of the queue after the whole timeline
+///
+/// All operations run in O(log n) time. An element is live if it is in the present queue,
+/// and dead if some pop removes it. A time is a bridge if every element pushed before it and
+/// still in the queue at that time is live. Every retroactive update changes the present
+/// queue by at most one element: the maximum dead element pushed after the last bridge
+/// before `t`, or the minimum live element pushed before the first bridge after `t`
+/// (Demaine, Iacono and Langerman). Segment trees over the timeline keep the live and dead
+/// elements, and the prefix sums locating the bridges.
+///
+/// Every pop must find a non-empty queue, also after later retroactive updates.
+#[derive(Clone, Debug)]
+pub struct RetroactivePriorityQueue<T: Numeric> {
+    ops: Vec<Op<T>>,
+    live: SegmentTree<MinIndex<T>>,
+    dead: SegmentTree<MaxIndex<T>>,
+    /// `+1` at dead pushes and `-1` at pops, so that the bridges are exactly the times with a
+    /// zero prefix sum, together with the negated weights.
+    weight: SegmentTree<(MaxSubarray<i64>, MaxSubarray<i64>)>,
+    len: usize,
+}
+
+impl<T: Numeric> RetroactivePriorityQueue<T> {
+    /// Creates a new priority queue over a timeline of `n` empty points.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        Self {
+            ops: vec![Op::None; n],
+            live: SegmentTree::new(n),
+            dead: SegmentTree::new(n),
+            weight: SegmentTree::from_fn(n, |_| (MaxSubarray::new(0), MaxSubarray::new(0))),
+            len: 0,
+        }
+    }
+
+    /// Inserts a push of `x` at time `t`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t >= horizon()`, or if there already is an operation at time `t`, in debug
+    /// builds.
+    pub fn insert_push(&mut self, t: usize, x: T) {
+        self.check_empty(t);
+        let d = self.dead.range_fold(self.last_bridge(t)..);
+        if d.index != usize::MAX && x < d.value {
+            self.set(
+                d.index,
+                Op::Push {
+                    value: d.value,
+                    live: true,
+                },
+            );
+            self.set(
+                t,
+                Op::Push {
+                    value: x,
+                    live: false,
+                },
+            );
+        } else {
+            self.set(
+                t,
+                Op::Push {
+                    value: x,
+                    live: true,
+                },
+            );
+        }
+        self.len += 1;
+    }
+
+    /// Inserts a pop of the minimum at time `t`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t >= horizon()`, if there already is an operation at time `t`, or if some
+    /// pop would find an empty queue, in debug builds.
+    pub fn insert_pop(&mut self, t: usize) {
+        self.check_empty(t);
+        self.kill_before(self.next_bridge(t + 1));
+        self.set(t, Op::Pop);
+        self.len -= 1;
+    }
+
+    /// Removes the operation at time `t`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t >= horizon()`, or if some pop would find an empty queue, in debug
+    /// builds.
+    pub fn remove(&mut self, t: usize) {
+        debug_assert!(
+            t < self.horizon(),
+            "index out of bounds: t={}, len={}",
+            t,
+            self.horizon(),
+        );
+        match self.ops[t] {
+            Op::None => {}
+            Op::Push { live: true, .. } => {
+                self.set(t, Op::None);
+                self.len -= 1;
+            }
+            Op::Push { live: false, .. } => {
+                let b = self.next_bridge(t + 1);
+                self.set(t, Op::None);
+                self.kill_before(b);
+                self.len -= 1;
+            }
+            Op::Pop => {
+                let d = self.dead.range_fold(self.last_bridge(t)..);
+                self.set(t, Op::None);
+                self.set(
+                    d.index,
+                    Op::Push {
+                        value: d.value,
+                        live: true,
+                    },
+                );
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Returns the minimum of the present queue, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn min(&self) -> Option<T> {
+        let m = self.live.all_fold();
+        (m.index != usize::MAX).then_some(m.value)
+    }
+
+    /// Returns the number of elements in the present queue.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the present queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of points of the timeline.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn horizon(&self) -> usize {
+        self.ops.len()
+    }
+
+    #[inline(always)]
+    fn check_empty(&self, t: usize) {
+        debug_assert!(
+            t < self.horizon(),
+            "index out of bounds: t={}, len={}",
+            t,
+            self.horizon(),
+        );
+        debug_assert!(
+            matches!(self.ops[t], Op::None),
+            "time already has an operation: t={}",
+            t,
+        );
+    }
+
+    /// Returns the last bridge at or before time `t`.
+    fn last_bridge(&self, t: usize) -> usize {
+        // Prefix sums are non-negative, so the last bridge `b` is the largest with
+        // `sum(b..t) == sum(..t)`, which is also the largest sum of a suffix of `..t`.
+        let s = self.weight.range_fold(..t).0.sum;
+        if s == 0 {
+            return t;
+        }
+        self.weight.find_last(..t, |w| w.0.suffix >= s).unwrap()
+    }
+
+    /// Returns the first bridge at or after time `t`, where the end of the timeline is always
+    /// a bridge.
+    fn next_bridge(&self, t: usize) -> usize {
+        // Likewise, the first bridge `b` is the smallest with `sum(t..b) == -sum(..t)`, which
+        // is also the smallest sum of a prefix of `t..`.
+        let s = self.weight.range_fold(..t).0.sum;
+        if s == 0 {
+            return t;
+        }
+        self.weight.find_first(t.., |w| w.1.prefix >= s).unwrap() + 1
+    }
+
+    /// Marks the minimum live element pushed before time `b` as dead.
+    fn kill_before(&mut self, b: usize) {
+        let m = self.live.range_fold(..b);
+        debug_assert!(m.index != usize::MAX, "pop from an empty queue");
+        self.set(
+            m.index,
+            Op::Push {
+                value: m.value,
+                live: false,
+            },
+        );
+    }
+
+    /// Sets the operation at time `t`, and its entries in the segment trees.
+    fn set(&mut self, t: usize, op: Op<T>) {
+        let (live, dead, w) = match op {
+            Op::None => (MinIndex::id(), MaxIndex::id(), 0),
+            Op::Push { value, live: true } => (MinIndex::new(value, t), MaxIndex::id(), 0),
+            Op::Push { value, live: false } => (MinIndex::id(), MaxIndex::new(value, t), 1),
+            Op::Pop => (MinIndex::id(), MaxIndex::id(), -1),
+        };
+        self.ops[t] = op;
+        self.live.set(t, live);
+        self.dead.set(t, dead);
+        self.weight
+            .set(t, (MaxSubarray::new(w), MaxSubarray::new(-w)));
+    }
+}
@@ -0,0 +1,268 @@
+use core::mem::MaybeUninit;
+
+/// A fixed-capacity double-ended queue stored inline, without heap allocation.
+///
+/// Given a capacity `N`, this data structure supports:
+/// - Update: `push_back(x)`, `push_front(x)`, `pop_back()` and `pop_front()`
+/// - Access: `get(i)` and indexing return the `i`-th element from the front
+///
+/// All operations run in O(1) time. The elements live in an array of `N` slots used
+/// circularly, so the whole queue can be placed on the stack or in a static, and this module
+/// depends only on `core`.
+pub struct RingBuffer<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    /// Slot of the front element.
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    /// Creates a new empty queue.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub const fn new() -> Self {
+        Self {
+            buf: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Appends `x` at the back, or returns it back if the queue is full.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn push_back(&mut self, x: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(x);
+        }
+        let slot = self.slot(self.len);
+        self.buf[slot].write(x);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends `x` at the front, or returns it back if the queue is full.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn push_front(&mut self, x: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(x);
+        }
+        self.head = if self.head == 0 { N - 1 } else { self.head - 1 };
+        self.buf[self.head].write(x);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the back element, or `None` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        // SAFETY: the slot of index `len` was initialized, and is now outside the queue.
+        Some(unsafe { self.buf[slot].assume_init_read() })
+    }
+
+    /// Removes and returns the front element, or `None` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let slot = self.head;
+        self.head = self.slot(1);
+        self.len -= 1;
+        // SAFETY: the front slot was initialized, and is now outside the queue.
+        Some(unsafe { self.buf[slot].assume_init_read() })
+    }
+
+    /// Returns the `i`-th element from the front, or `None` if `i >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i >= self.len {
+            return None;
+        }
+        // SAFETY: the slots of the indices less than `len` are initialized.
+        Some(unsafe { self.buf[self.slot(i)].assume_init_ref() })
+    }
+
+    /// Returns a mutable reference to the `i`-th element from the front, or `None` if
+    /// `i >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        if i >= self.len {
+            return None;
+        }
+        let slot = self.slot(i);
+        // SAFETY: the slots of the indices less than `len` are initialized.
+        Some(unsafe { self.buf[slot].assume_init_mut() })
+    }
+
+    /// Returns the front element, or `None` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the back element, or `None` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    /// Removes all elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+
+    /// Returns an iterator over the elements from front to back.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) for a full traversal
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        (0..self.len).map(|i| &self[i])
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the queue holds `N` elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// Returns the capacity `N`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the slot of index `i`, for `i < N`.
+    #[inline(always)]
+    fn slot(&self, i: usize) -> usize {
+        let j = self.head + i;
+        if j >= N { j - N } else { j }
+    }
+}
+
+impl<T, const N: usize> core::ops::Index<usize> for RingBuffer<T, N> {
+    type Output = T;
+
+    /// Returns the `i`-th element from the front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()`.
+    #[inline]
+    fn index(&self, i: usize) -> &T {
+        let len = self.len;
+        self.get(i)
+            .unwrap_or_else(|| panic!("index out of bounds: i={}, len={}", i, len))
+    }
+}
+
+impl<T, const N: usize> core::ops::IndexMut<usize> for RingBuffer<T, N> {
+    /// Returns a mutable reference to the `i`-th element from the front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()`.
+    #[inline]
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(i)
+            .unwrap_or_else(|| panic!("index out of bounds: i={}, len={}", i, len))
+    }
+}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for RingBuffer<T, N> {
+    fn clone(&self) -> Self {
+        let mut res = Self::new();
+        for x in self.iter() {
+            let _ = res.push_back(x.clone());
+        }
+        res
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    /// Creates a new empty queue.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::fmt::Debug, const N: usize> core::fmt::Debug for RingBuffer<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,153 @@
+use crate::order_stat_set::OrderStatSet;
+
+/// A multiset maintaining its median and quantiles.
+///
+/// Given elements of a totally ordered type `T`, this data structure supports:
+/// - Update: `insert(x)` inserts `x`, and `erase(&x)` removes one occurrence of `x`
+/// - Median: `median()` returns the lower median
+/// - Quantile: `quantile(p)` returns the element at rank `⌊p · (n - 1)⌋`, and `kth(k)` the
+///   element at rank `k`
+///
+/// All operations run in O(log n) time. Unlike a pair of heaps, which only tracks the median
+/// and needs lazy deletion to erase, this keeps every element in an
+/// [`OrderStatSet`], tagged with its insertion count to tell equal elements apart, so any
+/// quantile can be queried.
+#[derive(Clone)]
+pub struct RunningMedian<T> {
+    set: OrderStatSet<(T, u64)>,
+    /// Number of insertions so far, the tag of the next element.
+    count: u64,
+}
+
+impl<T: Ord + Clone> RunningMedian<T> {
+    /// Creates a new empty multiset.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            set: OrderStatSet::new(),
+            count: 0,
+        }
+    }
+
+    /// Inserts `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn insert(&mut self, x: T) {
+        self.set.insert((x, self.count));
+        self.count += 1;
+    }
+
+    /// Removes one occurrence of `x`, and returns `true` if it was present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn erase(&mut self, x: &T) -> bool {
+        let id = match self.set.predecessor(&(x.clone(), u64::MAX)) {
+            Some((y, id)) if y == x => *id,
+            _ => return false,
+        };
+        self.set.remove(&(x.clone(), id))
+    }
+
+    /// Returns the lower median, the element at rank `(n - 1) / 2`, or `None` if the multiset
+    /// is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn median(&self) -> Option<&T> {
+        self.kth(self.len().saturating_sub(1) / 2)
+    }
+
+    /// Returns the `p`-quantile, the element at rank `⌊p · (n - 1)⌋`, or `None` if the
+    /// multiset is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not in `[0, 1]` in debug builds.
+    #[inline]
+    pub fn quantile(&self, p: f64) -> Option<&T> {
+        debug_assert!((0.0..=1.0).contains(&p), "quantile out of bounds: p={}", p);
+        let k = (self.len().saturating_sub(1) as f64 * p).floor() as usize;
+        self.kth(k.min(self.len().saturating_sub(1)))
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        self.set.kth(k).map(|(x, _)| x)
+    }
+
+    /// Returns an iterator over the elements in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.set.iter().map(|(x, _)| x)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns `true` if the multiset is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord + Clone> Default for RunningMedian<T> {
+    /// Creates a new empty multiset.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for RunningMedian<T> {
+    /// Creates a new multiset from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.insert(x);
+        }
+        res
+    }
+}
+
+impl<T: Ord + Clone + std::fmt::Debug> std::fmt::Debug for RunningMedian<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,218 @@
+//! Prebuilt monoids and actions for `LazySegmentTree`.
+//!
+//! | Action           | [`Sum`] | [`Min`] | [`Max`] |
+//! |------------------|---------|---------|---------|
+//! | [`Add`]          | yes     | yes     | yes     |
+//! | [`Assign`]       | yes     | yes     | yes     |
+//! | [`AssignAdd`]    | yes     | yes     | yes     |
+//! | [`Affine`]       | yes     |         |         |
+//!
+//! [`Add`], [`Affine`], [`Min`] and [`Max`] are re-exported from [`monoids`](crate::monoids). An element
+//! equal to the identity of [`Min`] or [`Max`] is left unchanged by [`Add`].
+
+pub use crate::monoids::{Add, Affine, Max, Min};
+use crate::num::Numeric;
+use crate::segment_tree::codec::impl_codec;
+use crate::segment_tree::{Action, Codec, Monoid, Semigroup};
+
+/// The sum of a range together with its length, so that range actions can scale with it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Sum<T> {
+    /// The sum of the elements.
+    pub sum: T,
+    /// The number of elements.
+    pub len: T,
+}
+
+impl<T: Numeric> Sum<T> {
+    /// Returns the sum of a single element `x`.
+    #[inline(always)]
+    pub fn new(x: T) -> Self {
+        Self {
+            sum: x,
+            len: T::ONE,
+        }
+    }
+}
+
+impl<T: Numeric> Semigroup for Sum<T> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        Self {
+            sum: self.sum + other.sum,
+            len: self.len + other.len,
+        }
+    }
+}
+
+impl<T: Numeric> Monoid for Sum<T> {
+    #[inline(always)]
+    fn id() -> Self {
+        Self {
+            sum: T::ZERO,
+            len: T::ZERO,
+        }
+    }
+}
+
+impl_codec!([T: Codec] Sum<T>, |s| s.sum, s.len => |sum, len| Sum { sum, len });
+
+impl<T: Numeric> Action<Sum<T>> for Add<T> {
+    #[inline(always)]
+    fn act(&self, s: &Sum<T>) -> Sum<T> {
+        Sum {
+            sum: s.sum + self.0 * s.len,
+            len: s.len,
+        }
+    }
+}
+
+impl<T: Numeric> Action<Min<T>> for Add<T> {
+    #[inline(always)]
+    fn act(&self, s: &Min<T>) -> Min<T> {
+        // Keep the identity of empty ranges from overflowing.
+        if s.0 == T::MAX { *s } else { Min(s.0 + self.0) }
+    }
+}
+
+impl<T: Numeric> Action<Max<T>> for Add<T> {
+    #[inline(always)]
+    fn act(&self, s: &Max<T>) -> Max<T> {
+        if s.0 == T::MIN { *s } else { Max(s.0 + self.0) }
+    }
+}
+
+/// Assigns a constant to every element, or does nothing if `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Assign<T>(pub Option<T>);
+
+impl<T: Numeric> Semigroup for Assign<T> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        Self(self.0.or(other.0))
+    }
+}
+
+impl<T: Numeric> Monoid for Assign<T> {
+    #[inline(always)]
+    fn id() -> Self {
+        Self(None)
+    }
+}
+
+impl_codec!([T: Codec] Assign<T>, |s| s.0 => |x| Assign(x));
+
+impl<T: Numeric> Action<Sum<T>> for Assign<T> {
+    #[inline(always)]
+    fn act(&self, s: &Sum<T>) -> Sum<T> {
+        match self.0 {
+            Some(x) => Sum {
+                sum: x * s.len,
+                len: s.len,
+            },
+            None => *s,
+        }
+    }
+}
+
+impl<T: Numeric> Action<Min<T>> for Assign<T> {
+    #[inline(always)]
+    fn act(&self, s: &Min<T>) -> Min<T> {
+        self.0.map_or(*s, Min)
+    }
+}
+
+impl<T: Numeric> Action<Max<T>> for Assign<T> {
+    #[inline(always)]
+    fn act(&self, s: &Max<T>) -> Max<T> {
+        self.0.map_or(*s, Max)
+    }
+}
+
+/// Optionally assigns a constant to every element, then adds a constant to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AssignAdd<T> {
+    /// The value to assign first, if any.
+    pub assign: Option<T>,
+    /// The value to add afterwards.
+    pub add: T,
+}
+
+impl<T: Numeric> AssignAdd<T> {
+    /// Returns the action assigning `x` to every element.
+    #[inline(always)]
+    pub fn assign(x: T) -> Self {
+        Self {
+            assign: Some(x),
+            add: T::ZERO,
+        }
+    }
+
+    /// Returns the action adding `x` to every element.
+    #[inline(always)]
+    pub fn add(x: T) -> Self {
+        Self {
+            assign: None,
+            add: x,
+        }
+    }
+}
+
+impl<T: Numeric> Semigroup for AssignAdd<T> {
+    #[inline(always)]
+    fn op(&self, other: &Self) -> Self {
+        match self.assign {
+            Some(_) => *self,
+            None => Self {
+                assign: other.assign,
+                add: other.add + self.add,
+            },
+        }
+    }
+}
+
+impl<T: Numeric> Monoid for AssignAdd<T> {
+    #[inline(always)]
+    fn id() -> Self {
+        Self::add(T::ZERO)
+    }
+}
+
+impl_codec!(
+    [T: Codec] AssignAdd<T>,
+    |s| s.assign, s.add => |assign, add| AssignAdd { assign, add }
+);
+
+impl<T: Numeric> Action<Sum<T>> for AssignAdd<T> {
+    #[inline(always)]
+    fn act(&self, s: &Sum<T>) -> Sum<T> {
+        let s = Assign(self.assign).act(s);
+        Add(self.add).act(&s)
+    }
+}
+
+impl<T: Numeric> Action<Min<T>> for AssignAdd<T> {
+    #[inline(always)]
+    fn act(&self, s: &Min<T>) -> Min<T> {
+        let s = Assign(self.assign).act(s);
+        Add(self.add).act(&s)
+    }
+}
+
+impl<T: Numeric> Action<Max<T>> for AssignAdd<T> {
+    #[inline(always)]
+    fn act(&self, s: &Max<T>) -> Max<T> {
+        let s = Assign(self.assign).act(s);
+        Add(self.add).act(&s)
+    }
+}
+
+impl<T: Numeric> Action<Sum<T>> for Affine<T> {
+    #[inline(always)]
+    fn act(&self, s: &Sum<T>) -> Sum<T> {
+        Sum {
+            sum: self.a * s.sum + self.b * s.len,
+            len: s.len,
+        }
+    }
+}
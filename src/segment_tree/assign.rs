@@ -0,0 +1,351 @@
+use crate::segment_tree::Monoid;
+use std::rc::Rc;
+
+/// A segment tree for range assignments and range queries.
+///
+/// Given a monoid `(S, op, id)`, this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Range assignment: `range_assign(l..r, x)` sets `a[i] = x` for all `i` in range
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+///
+/// All operations run in O(log n) time. An assignment stores the powers `x`, `op(x, x)`,
+/// `op(x, x, x, x)`, ... once and shares them among the nodes it covers, so no action type
+/// or "unset" tag is needed, and `op` need not be commutative.
+#[derive(Clone, Debug)]
+pub struct RangeAssignSegmentTree<S: Monoid> {
+    /// Binary heap-like array storing the tree nodes.
+    /// Index 1 is the root, index `size + i` is the leaf for element `i`.
+    data: Box<[S]>,
+    /// Pending assignment of each internal node, as the powers `x^(2^k)` of the assigned
+    /// element `x`.
+    lazy: Box<[Option<Rc<[S]>>]>,
+    /// Number of elements in the original array.
+    n: usize,
+    /// log2(size), the height of the root.
+    log: usize,
+}
+
+impl<S: Monoid> RangeAssignSegmentTree<S> {
+    /// Creates a new segment tree with `n` elements, all initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        let size = n.next_power_of_two();
+        Self {
+            data: vec![S::id(); size << 1].into_boxed_slice(),
+            lazy: vec![None; size].into_boxed_slice(),
+            n,
+            log: size.trailing_zeros() as usize,
+        }
+    }
+
+    /// Creates a new segment tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let mut data = Vec::with_capacity(size << 1);
+        data.resize(size, S::id());
+        data.extend(v);
+        data.resize(size << 1, S::id());
+        for i in (1..size).rev() {
+            data[i] = S::op(&data[i << 1], &data[(i << 1) + 1]);
+        }
+        Self {
+            data: data.into_boxed_slice(),
+            lazy: vec![None; size].into_boxed_slice(),
+            n,
+            log: size.trailing_zeros() as usize,
+        }
+    }
+
+    /// Creates a new segment tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(v: &[S]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new segment tree with `n` elements, where the element at index `i` is
+    /// `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> S,
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn set(&mut self, mut i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        i += self.size();
+        for t in (1..=self.log).rev() {
+            self.push(i >> t);
+        }
+        self.data[i] = x;
+        while i > 1 {
+            i >>= 1;
+            self.update(i);
+        }
+    }
+
+    /// Sets every element in the given range to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_assign(&mut self, range: impl std::ops::RangeBounds<usize>, x: S) {
+        let (l, r) = self.bounds(range);
+        if l == r {
+            return;
+        }
+        // `pows[k]` is the fold of `2^k` copies of `x`, the value of a covered node of
+        // height `k`.
+        let mut pows = Vec::with_capacity(self.log + 1);
+        pows.push(x);
+        for k in 0..self.log {
+            pows.push(S::op(&pows[k], &pows[k]));
+        }
+        let pows: Rc<[S]> = pows.into();
+        self.assign_rec(1, self.log, 0, self.size(), l, r, &pows);
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.size();
+        // The topmost pending assignment on the path overrides everything below it.
+        for t in (1..=self.log).rev() {
+            if let Some(pows) = &self.lazy[i >> t] {
+                return pows[0].clone();
+            }
+        }
+        self.data[i].clone()
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let (l, r) = self.bounds(range);
+        if l == r {
+            return S::id();
+        }
+        self.fold_rec(1, 0, self.size(), l, r)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        self.data[1].clone()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn to_vec(&self) -> Vec<S> {
+        let mut v = Vec::with_capacity(self.len());
+        self.collect_rec(1, 0, self.size(), &mut v);
+        v
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.lazy.len()
+    }
+
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+
+    /// Assigns to node `k` of height `h`, whose children have height `h - 1`.
+    #[inline]
+    fn apply(&mut self, k: usize, h: usize, pows: &Rc<[S]>) {
+        self.data[k] = pows[h].clone();
+        if k < self.size() {
+            self.lazy[k] = Some(pows.clone());
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, k: usize) {
+        if let Some(pows) = self.lazy[k].take() {
+            let h = self.log - (usize::BITS - 1 - k.leading_zeros()) as usize;
+            self.apply(k << 1, h - 1, &pows);
+            self.apply((k << 1) + 1, h - 1, &pows);
+        }
+    }
+
+    #[inline(always)]
+    fn update(&mut self, k: usize) {
+        self.data[k] = S::op(&self.data[k << 1], &self.data[(k << 1) + 1]);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn assign_rec(
+        &mut self,
+        k: usize,
+        h: usize,
+        l: usize,
+        r: usize,
+        ql: usize,
+        qr: usize,
+        pows: &Rc<[S]>,
+    ) {
+        if qr <= l || r <= ql {
+            return;
+        }
+        if ql <= l && r <= qr {
+            self.apply(k, h, pows);
+            return;
+        }
+        self.push(k);
+        let m = (l + r) >> 1;
+        self.assign_rec(k << 1, h - 1, l, m, ql, qr, pows);
+        self.assign_rec((k << 1) + 1, h - 1, m, r, ql, qr, pows);
+        self.update(k);
+    }
+
+    fn fold_rec(&self, k: usize, l: usize, r: usize, ql: usize, qr: usize) -> S {
+        if ql <= l && r <= qr {
+            return self.data[k].clone();
+        }
+        if let Some(pows) = &self.lazy[k] {
+            // The whole node holds the assigned element, so fold its overlap by doubling.
+            let len = qr.min(r) - ql.max(l);
+            let mut res = S::id();
+            for (k, p) in pows.iter().enumerate() {
+                if len >> k & 1 == 1 {
+                    res = S::op(&res, p);
+                }
+            }
+            return res;
+        }
+        let m = (l + r) >> 1;
+        if qr <= m {
+            self.fold_rec(k << 1, l, m, ql, qr)
+        } else if m <= ql {
+            self.fold_rec((k << 1) + 1, m, r, ql, qr)
+        } else {
+            S::op(
+                &self.fold_rec(k << 1, l, m, ql, qr),
+                &self.fold_rec((k << 1) + 1, m, r, ql, qr),
+            )
+        }
+    }
+
+    fn collect_rec(&self, k: usize, l: usize, r: usize, v: &mut Vec<S>) {
+        if l >= self.n {
+            return;
+        }
+        if k >= self.size() {
+            v.push(self.data[k].clone());
+        } else if let Some(pows) = &self.lazy[k] {
+            v.extend(std::iter::repeat_n(pows[0].clone(), r.min(self.n) - l));
+        } else {
+            let m = (l + r) >> 1;
+            self.collect_rec(k << 1, l, m, v);
+            self.collect_rec((k << 1) + 1, m, r, v);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::bit_vector::RankSelectBitVec;
+
+/// A static merge sort tree answering range counting queries with fractional cascading.
+///
+/// Given a sequence `a` of ordered values, this data structure supports:
+/// - Rank query: `count_leq(l..r, x)` returns the number of `i` in `l..r` with `a[i] <= x`
+/// - Range count: `count_in(l..r, lo..hi)` returns the number of `i` in `l..r` with
+///   `a[i]` in `lo..hi`
+///
+/// Read as points `(i, a[i])`, these are orthogonal range counting queries in the plane;
+/// points with arbitrary x-coordinates are handled by sorting them by x first.
+///
+/// Unlike [`MergeSortTree`](super::MergeSortTree), which binary searches the sorted list of
+/// every node covering the range, only the sorted list of the root is stored and searched.
+/// Every node instead marks which entries of its sorted list belong to its left child, so
+/// that the position of a value in a child follows from its position in the parent by a
+/// rank query in O(1) time. This takes one bit per element and level, O(n log n) bits in
+/// total. Built in O(n log n) time, and both queries run in O(log n) time.
+#[derive(Clone, Debug)]
+pub struct CascadingMergeSortTree<T: Ord> {
+    /// The elements in sorted order, ties broken by index.
+    sorted: Box<[T]>,
+    /// Bit `j` of `left[d]` is set if the `j`-th entry at depth `d` belongs to the left child
+    /// of its node, where the entries at depth `d` are the sorted lists of the nodes of depth
+    /// `d`, concatenated from left to right.
+    left: Box<[RankSelectBitVec]>,
+}
+
+impl<T: Ord + Clone> CascadingMergeSortTree<T> {
+    /// Creates a new tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_vec(v: Vec<T>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&i, &j| v[i].cmp(&v[j]));
+        let mut left = Vec::with_capacity(log);
+        let mut next = Vec::with_capacity(n);
+        for d in 0..log {
+            let width = size >> d;
+            // The sorted list of the node starting at `lo` occupies `order[lo..hi]`, as every
+            // node to its left is full.
+            for lo in (0..n).step_by(width) {
+                let (hi, mid) = ((lo + width).min(n), lo + (width >> 1));
+                next.extend(order[lo..hi].iter().filter(|&&i| i < mid));
+                next.extend(order[lo..hi].iter().filter(|&&i| i >= mid));
+            }
+            left.push(RankSelectBitVec::from_bits(order.iter().enumerate().map(
+                |(j, &i)| {
+                    let lo = j & !(width - 1);
+                    i < lo + (width >> 1)
+                },
+            )));
+            std::mem::swap(&mut order, &mut next);
+            next.clear();
+        }
+        let mut sorted = v;
+        sorted.sort();
+        Self {
+            sorted: sorted.into_boxed_slice(),
+            left: left.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_slice(v: &[T]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new tree with `n` elements, where the element at index `i` is `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> T,
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for CascadingMergeSortTree<T> {
+    /// Creates a new tree from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> CascadingMergeSortTree<T> {
+    /// Returns the number of indices `i` in the given range with `a[i] <= x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn count_leq(&self, range: impl std::ops::RangeBounds<usize>, x: &T) -> usize {
+        let hi = self.sorted.partition_point(|y| y <= x);
+        self.count(range, 0, hi)
+    }
+
+    /// Returns the number of indices `i` in the given range with `a[i]` in `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn count_in(
+        &self,
+        range: impl std::ops::RangeBounds<usize>,
+        values: impl std::ops::RangeBounds<T>,
+    ) -> usize {
+        let v = &self.sorted;
+        let lo = match values.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(x) => v.partition_point(|y| y < x),
+            std::ops::Bound::Excluded(x) => v.partition_point(|y| y <= x),
+        };
+        let hi = match values.end_bound() {
+            std::ops::Bound::Unbounded => v.len(),
+            std::ops::Bound::Included(x) => v.partition_point(|y| y <= x),
+            std::ops::Bound::Excluded(x) => v.partition_point(|y| y < x),
+        };
+        self.count(range, lo, hi.max(lo))
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Counts the indices in the given range whose element has rank in `lo..hi`.
+    #[inline]
+    fn count(&self, range: impl std::ops::RangeBounds<usize>, lo: usize, hi: usize) -> usize {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        self.count_rec(0, 0, l, r, lo, hi)
+    }
+
+    /// Counts the entries at positions `a..b` of the sorted list of the node of depth `d`
+    /// starting at index `s` that lie in `ql..qr`.
+    fn count_rec(&self, d: usize, s: usize, ql: usize, qr: usize, a: usize, b: usize) -> usize {
+        let width = 1 << (self.left.len() - d);
+        if a == b || qr <= s || s + width <= ql {
+            return 0;
+        }
+        if ql <= s && s + width <= qr {
+            return b - a;
+        }
+        // Every node of depth `d` left of `s` is full, and sends half of its entries left.
+        let left = &self.left[d];
+        let (la, lb) = (left.rank1(s + a) - (s >> 1), left.rank1(s + b) - (s >> 1));
+        self.count_rec(d + 1, s, ql, qr, la, lb)
+            + self.count_rec(d + 1, s + (width >> 1), ql, qr, a - la, b - lb)
+    }
+}
@@ -0,0 +1,238 @@
+use crate::algebra::Componentwise;
+
+/// A value with a compact, platform-independent binary encoding.
+///
+/// Integers and floats are encoded in little-endian byte order, `usize` and `isize` as
+/// 64-bit integers, and sequences with a 64-bit length prefix.
+///
+/// # Laws
+///
+/// Implementations must satisfy the following law:
+///
+/// - **Round trip**: decoding the output of `x.encode(out)` yields `x` and consumes exactly
+///   the bytes written by `encode`
+pub trait Codec: Sized {
+    /// Appends the encoding of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the front of `bytes`, advancing it past the consumed bytes.
+    ///
+    /// Returns `None` if `bytes` does not start with a valid encoding.
+    fn decode(bytes: &mut &[u8]) -> Option<Self>;
+}
+
+/// Splits the first `N` bytes off `bytes`.
+#[inline]
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    let (head, tail) = bytes.split_first_chunk::<N>()?;
+    *bytes = tail;
+    Some(*head)
+}
+
+macro_rules! impl_codec_num {
+    ($($t:ty),*) => {
+        $(
+            impl Codec for $t {
+                #[inline]
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline]
+                fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                    take(bytes).map(<$t>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_num!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Codec for usize {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        u64::decode(bytes)?.try_into().ok()
+    }
+}
+
+impl Codec for isize {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as i64).encode(out);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        i64::decode(bytes)?.try_into().ok()
+    }
+}
+
+impl Codec for bool {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        match u8::decode(bytes)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for char {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode(out);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        char::from_u32(u32::decode(bytes)?)
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(x) => {
+                out.push(1);
+                x.encode(out);
+            }
+        }
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        match u8::decode(bytes)? {
+            0 => Some(None),
+            1 => T::decode(bytes).map(Some),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        for x in self {
+            x.encode(out);
+        }
+    }
+
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        decode_seq(bytes)
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let n = usize::decode(bytes)?;
+        if bytes.len() < n {
+            return None;
+        }
+        let (head, tail) = bytes.split_at(n);
+        *bytes = tail;
+        String::from_utf8(head.to_vec()).ok()
+    }
+}
+
+macro_rules! impl_codec_tuple {
+    ($($t:ident),*) => {
+        impl<$($t: Codec),*> Codec for ($($t,)*) {
+            #[inline]
+            #[allow(non_snake_case)]
+            fn encode(&self, out: &mut Vec<u8>) {
+                let ($($t,)*) = self;
+                $($t.encode(out);)*
+            }
+
+            #[inline]
+            fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                Some(($($t::decode(bytes)?,)*))
+            }
+        }
+    };
+}
+
+impl_codec_tuple!(A);
+impl_codec_tuple!(A, B);
+impl_codec_tuple!(A, B, C);
+impl_codec_tuple!(A, B, C, D);
+
+/// Decodes a length-prefixed sequence.
+pub(crate) fn decode_seq<T: Codec>(bytes: &mut &[u8]) -> Option<Vec<T>> {
+    let n = usize::decode(bytes)?;
+    // Do not trust the length for the allocation, as the input may be corrupt.
+    let mut v = Vec::with_capacity(n.min(bytes.len()));
+    for _ in 0..n {
+        v.push(T::decode(bytes)?);
+    }
+    Some(v)
+}
+
+impl<T: Codec, const N: usize> Codec for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for x in self {
+            x.encode(out);
+        }
+    }
+
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let mut v = Vec::with_capacity(N);
+        for _ in 0..N {
+            v.push(T::decode(bytes)?);
+        }
+        v.try_into().ok()
+    }
+}
+
+impl<F: Codec> Codec for Componentwise<F> {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+    }
+
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        F::decode(bytes).map(Componentwise)
+    }
+}
+
+/// Implements [`Codec`] for a struct by encoding the given fields in order, and decoding
+/// them in the same order into the given constructor.
+macro_rules! impl_codec {
+    ([$($g:tt)*] $t:ty, |$s:ident| $($field:expr),+ => |$($v:ident),+| $new:expr) => {
+        impl<$($g)*> $crate::segment_tree::Codec for $t {
+            #[inline]
+            fn encode(&self, out: &mut Vec<u8>) {
+                let $s = self;
+                $($crate::segment_tree::Codec::encode(&$field, out);)+
+            }
+
+            #[inline]
+            fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                $(let $v = $crate::segment_tree::Codec::decode(bytes)?;)+
+                Some($new)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_codec;
@@ -0,0 +1,376 @@
+use crate::segment_tree::{Action, Monoid};
+
+/// A lazy segment tree without power-of-two padding.
+///
+/// Given a monoid `(S, op, id)` and an action monoid `(F, compose, id)`,
+/// this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Point action: `apply(i, f)` sets `a[i] = f.act(a[i])`
+/// - Range action: `range_apply(l..r, f)` applies `f` to all elements in range
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+///
+/// All operations run in O(log n) time.
+///
+/// Unlike [`LazySegmentTree`](super::LazySegmentTree), the leaves are stored at `n..2n` for
+/// any `n`, so the tree holds exactly `2n` elements and `n` actions instead of up to twice as
+/// many. Some internal nodes then combine leaves out of order, but they are never used by a
+/// query. In exchange, `range_fold` propagates the actions on its boundary paths and takes
+/// `&mut self`.
+#[derive(Clone, Debug)]
+pub struct CompactLazySegmentTree<S: Monoid, F: Action<S>> {
+    /// Binary heap-like array storing the tree nodes.
+    /// Index 1 is the root, index `n + i` is the leaf for element `i`.
+    data: Box<[S]>,
+    /// Pending action of each internal node.
+    lazy: Box<[F]>,
+    /// Whether each internal node holds an action other than `F::id()`.
+    pending: Box<[bool]>,
+}
+
+impl<S: Monoid, F: Action<S>> CompactLazySegmentTree<S, F> {
+    /// Creates a new lazy segment tree with `n` elements, all initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        Self {
+            data: vec![S::id(); n << 1].into_boxed_slice(),
+            lazy: vec![F::id(); n].into_boxed_slice(),
+            pending: vec![false; n].into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new lazy segment tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let n = v.len();
+        let mut data = Vec::with_capacity(n << 1);
+        data.resize(n, S::id());
+        data.extend(v);
+        for i in (1..n).rev() {
+            data[i] = S::op(&data[i << 1], &data[(i << 1) + 1]);
+        }
+        Self {
+            data: data.into_boxed_slice(),
+            lazy: vec![F::id(); n].into_boxed_slice(),
+            pending: vec![false; n].into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new lazy segment tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(v: &[S]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new lazy segment tree with `n` elements, where the element at index `i` is
+    /// `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<G>(n: usize, f: G) -> Self
+    where
+        G: FnMut(usize) -> S,
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn set(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.len();
+        self.push_path(i);
+        self.data[i] = x;
+        self.update_path(i);
+    }
+
+    /// Applies action `f` to the element at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn apply(&mut self, i: usize, f: F) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.len();
+        self.push_path(i);
+        self.data[i] = f.act(&self.data[i]);
+        self.update_path(i);
+    }
+
+    /// Applies action `f` to all elements in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_apply(&mut self, range: impl std::ops::RangeBounds<usize>, f: F) {
+        let (l, r) = self.bounds(range);
+        if l == r {
+            return;
+        }
+        let (l, r) = (l + self.len(), r + self.len());
+        self.push_path(l);
+        self.push_path(r - 1);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    self.all_apply(l, &f);
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    self.all_apply(r, &f);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        self.update_path(l);
+        self.update_path(r - 1);
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// Pending actions on the ancestors are applied on the fly, so the tree is not modified.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.len();
+        let mut x = self.data[i].clone();
+        // Actions on deeper nodes are more recent, so they are applied first.
+        let mut k = i >> 1;
+        while k > 0 {
+            if self.pending[k] {
+                x = self.lazy[k].act(&x);
+            }
+            k >>= 1;
+        }
+        x
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty. Pending actions on the paths to both ends of
+    /// the range are propagated.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_fold(&mut self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let (l, r) = self.bounds(range);
+        if l == r {
+            return S::id();
+        }
+        let (mut l, mut r) = (l + self.len(), r + self.len());
+        self.push_path(l);
+        self.push_path(r - 1);
+        let mut left = S::id();
+        let mut right = S::id();
+        while l < r {
+            if l & 1 == 1 {
+                left = S::op(&left, &self.data[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = S::op(&self.data[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        S::op(&left, &right)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// The root does not hold the fold of all elements unless `n` is a power of two, so this
+    /// is a full range query.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn all_fold(&mut self) -> S {
+        self.range_fold(..)
+    }
+
+    /// Returns an iterator over the elements, in order.
+    ///
+    /// All pending actions are propagated to the leaves first.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&mut self) -> std::slice::Iter<'_, S> {
+        for k in 1..self.len() {
+            self.push(k);
+        }
+        self.data[self.len()..].iter()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// All pending actions are propagated to the leaves first.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn to_vec(&mut self) -> Vec<S> {
+        self.iter().cloned().collect()
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.lazy.len()
+    }
+
+    /// Returns `true` if the lazy segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+
+    /// Applies `f` to node `k`, deferring it for the children of an internal node.
+    #[inline]
+    fn all_apply(&mut self, k: usize, f: &F) {
+        self.data[k] = f.act(&self.data[k]);
+        if k < self.len() {
+            self.lazy[k] = F::op(f, &self.lazy[k]);
+            self.pending[k] = true;
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, k: usize) {
+        if !std::mem::take(&mut self.pending[k]) {
+            return;
+        }
+        let f = std::mem::replace(&mut self.lazy[k], F::id());
+        self.all_apply(k << 1, &f);
+        self.all_apply((k << 1) + 1, &f);
+    }
+
+    /// Propagates the pending actions of every ancestor of node `k`, from the root down.
+    #[inline]
+    fn push_path(&mut self, k: usize) {
+        for t in (1..usize::BITS - k.leading_zeros()).rev() {
+            self.push(k >> t);
+        }
+    }
+
+    /// Recomputes every ancestor of node `k`.
+    ///
+    /// An ancestor may have just received an action from `range_apply`, which is applied
+    /// again on top of its children.
+    #[inline]
+    fn update_path(&mut self, mut k: usize) {
+        while k > 1 {
+            k >>= 1;
+            self.data[k] = S::op(&self.data[k << 1], &self.data[(k << 1) + 1]);
+            if self.pending[k] {
+                self.data[k] = self.lazy[k].act(&self.data[k]);
+            }
+        }
+    }
+}
+
+impl<S: Monoid, F: Action<S>> FromIterator<S> for CompactLazySegmentTree<S, F> {
+    /// Creates a new lazy segment tree from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
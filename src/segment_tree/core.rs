@@ -1,3 +1,6 @@
+use crate::monoids::Reversed;
+use crate::segment_tree::codec::{Codec, decode_seq};
+use crate::segment_tree::levels::Levels;
 use crate::segment_tree::monoid::Monoid;
 
 /// A segment tree for efficient point updates and range queries.
@@ -7,12 +10,17 @@ use crate::segment_tree::monoid::Monoid;
 /// - Point operation: `operate(i, x)` sets `a[i] = op(a[i], x)`
 /// - Range query: `range_fold(l..r)` returns `op(a[l], op(a[l+1], ..., a[r-1]))`
 ///
-/// Both operations run in O(log n) time.
-pub struct SegmentTree<S: Monoid>(
+/// Both operations run in O(log n) time. The tree uses 2n nodes for any `n`, doubling its
+/// leaves when `push` fills them, and every fold combines elements in order, so `op` need
+/// not be commutative.
+#[derive(Clone)]
+pub struct SegmentTree<S: Monoid> {
     /// Binary heap-like array storing the tree nodes.
     /// Index 1 is the root, index `size + i` is the leaf for element `i`.
-    Box<[S]>,
-);
+    data: Box<[S]>,
+    /// Number of elements, at most `size`. Leaves past it hold `S::id()`.
+    n: usize,
+}
 
 impl<S: Monoid> SegmentTree<S> {
     /// Creates a new segment tree with `n` elements, all initialized to `S::id()`.
@@ -21,7 +29,10 @@ impl<S: Monoid> SegmentTree<S> {
     ///
     /// O(n)
     pub fn new(n: usize) -> Self {
-        Self(vec![S::id(); n << 1].into_boxed_slice())
+        Self {
+            data: vec![S::id(); n << 1].into_boxed_slice(),
+            n,
+        }
     }
 
     /// Creates a new segment tree from a vec.
@@ -31,6 +42,12 @@ impl<S: Monoid> SegmentTree<S> {
     /// O(n)
     pub fn from_vec(mut v: Vec<S>) -> Self {
         let n = v.len();
+        if n == 0 {
+            return Self {
+                data: Box::default(),
+                n,
+            };
+        }
         v.reserve(n);
         unsafe {
             let v = v.as_mut_ptr();
@@ -44,7 +61,10 @@ impl<S: Monoid> SegmentTree<S> {
         unsafe {
             v.set_len(n << 1);
         }
-        Self(v.into_boxed_slice())
+        Self {
+            data: v.into_boxed_slice(),
+            n,
+        }
     }
 
     /// Creates a new segment tree from a slice.
@@ -55,15 +75,32 @@ impl<S: Monoid> SegmentTree<S> {
     pub fn from_slice(v: &[S]) -> Self {
         let n = v.len();
         let mut data = vec![S::id(); n << 1];
+        data[n..].clone_from_slice(v);
         unsafe {
             let d = data.as_mut_ptr();
-            std::ptr::copy_nonoverlapping(v.as_ptr(), d.add(n), n);
             for i in (1..n).rev() {
                 *d.add(i) = S::op(&*d.add(i << 1), &*d.add((i << 1) + 1));
             }
         }
 
-        Self(data.into_boxed_slice())
+        Self {
+            data: data.into_boxed_slice(),
+            n,
+        }
+    }
+
+    /// Creates a new segment tree with `n` elements, where the element at index `i` is `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> S,
+    {
+        let mut v = Vec::with_capacity(n << 1);
+        v.extend((0..n).map(f));
+        Self::from_vec(v)
     }
 
     /// Sets the value at index `i` to `x`.
@@ -83,9 +120,9 @@ impl<S: Monoid> SegmentTree<S> {
             i,
             self.len(),
         );
-        i += self.len();
+        i += self.size();
         unsafe {
-            let d = self.0.as_mut_ptr();
+            let d = self.data.as_mut_ptr();
             *d.add(i) = x;
             while i > 1 {
                 i >>= 1;
@@ -94,6 +131,41 @@ impl<S: Monoid> SegmentTree<S> {
         }
     }
 
+    /// Sets `a[l + j] = v[j]` for every `j` in `0..v.len()`.
+    ///
+    /// Only the ancestors of the overwritten leaves are recomputed, level by level.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k + log n), where `k = v.len()`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l + v.len() > len()` in debug builds.
+    pub fn set_range(&mut self, l: usize, v: &[S]) {
+        debug_assert!(
+            l + v.len() <= self.len(),
+            "index out of bounds: r={}, len={}",
+            l + v.len(),
+            self.len(),
+        );
+        if v.is_empty() {
+            return;
+        }
+        let mut lo = self.size() + l;
+        let mut hi = lo + v.len() - 1;
+        self.data[lo..=hi].clone_from_slice(v);
+        while hi > 1 {
+            lo = (lo >> 1).max(1);
+            hi >>= 1;
+            // Unless `n` is a power of two, a parent and its child can both lie in the same
+            // run of indices; the child comes later and must be recomputed first.
+            for i in (lo..=hi).rev() {
+                self.data[i] = S::op(&self.data[i << 1], &self.data[(i << 1) + 1]);
+            }
+        }
+    }
+
     /// Applies `op(a[i], x)` to the element at index `i`.
     ///
     /// # Time complexity
@@ -111,9 +183,9 @@ impl<S: Monoid> SegmentTree<S> {
             i,
             self.len(),
         );
-        i += self.len();
+        i += self.size();
         unsafe {
-            let d = self.0.as_mut_ptr();
+            let d = self.data.as_mut_ptr();
             *d.add(i) = S::op(&*d.add(i), &x);
             while i > 1 {
                 i >>= 1;
@@ -122,6 +194,30 @@ impl<S: Monoid> SegmentTree<S> {
         }
     }
 
+    /// Returns a guard giving mutable access to the element at index `i`.
+    ///
+    /// The ancestors of the element are recomputed when the guard is dropped, so a large
+    /// element can be modified in place without cloning it out and calling `set`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) to create, O(log n) to drop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn entry(&mut self, i: usize) -> Entry<'_, S> {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.size();
+        Entry { tree: self, i }
+    }
+
     /// Returns the value at index `i`.
     ///
     /// # Time complexity
@@ -139,7 +235,7 @@ impl<S: Monoid> SegmentTree<S> {
             i,
             self.len(),
         );
-        unsafe { self.0.get_unchecked(self.len() + i).clone() }
+        unsafe { self.data.get_unchecked(self.size() + i).clone() }
     }
 
     /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
@@ -159,22 +255,22 @@ impl<S: Monoid> SegmentTree<S> {
             std::ops::Bound::Unbounded => 0,
             std::ops::Bound::Included(&x) => x,
             std::ops::Bound::Excluded(&x) => x + 1,
-        } + self.len();
+        } + self.size();
         let mut r = match range.end_bound() {
             std::ops::Bound::Unbounded => self.len(),
             std::ops::Bound::Included(&x) => x + 1,
             std::ops::Bound::Excluded(&x) => x,
-        } + self.len();
+        } + self.size();
         debug_assert!(
             l <= r,
             "left bound must be less than or equal to right bound: l={}, r={}",
-            l - self.len(),
-            r - self.len(),
+            l - self.size(),
+            r - self.size(),
         );
         debug_assert!(
-            r <= self.len() << 1,
+            r <= self.size() + self.len(),
             "index out of bounds: r={}, len={}",
-            r - self.len(),
+            r - self.size(),
             self.len(),
         );
         if l == r {
@@ -187,7 +283,7 @@ impl<S: Monoid> SegmentTree<S> {
         let mut right = S::id();
 
         unsafe {
-            let d = self.0.as_ptr();
+            let d = self.data.as_ptr();
             loop {
                 if l >= r {
                     left = S::op(&left, &*d.add(l));
@@ -210,25 +306,405 @@ impl<S: Monoid> SegmentTree<S> {
     ///
     /// # Time complexity
     ///
-    /// O(1)
+    /// O(1) if `n` is a power of two, O(log n) otherwise.
     pub fn all_fold(&self) -> S {
-        unsafe { self.0.get_unchecked(1).clone() }
+        if self.size().is_power_of_two() {
+            unsafe { self.data.get_unchecked(1).clone() }
+        } else {
+            // The root wraps around the leaves out of order unless `n` is a power of two.
+            self.range_fold(..)
+        }
     }
 
+    /// Returns an iterator over the elements, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) to create, O(n) to consume.
     #[inline]
-    pub fn max_right<P>(&self, _l: usize, _p: P) -> usize
+    pub fn iter(&self) -> std::slice::Iter<'_, S> {
+        self.data[self.size()..self.size() + self.len()].iter()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn to_vec(&self) -> Vec<S> {
+        self.data[self.size()..self.size() + self.len()].to_vec()
+    }
+
+    /// Returns the smallest `i` in the given range such that `p(op(a[l], ..., a[i]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[l], ..., a[i])`, it also holds
+    /// for every longer prefix starting at `l`. For example, the first zero in a range is
+    /// found with a minimum monoid and `p = |x| x == 0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_first<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let size = self.size();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        // Nodes covering the range, from left to right.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut right = [0; usize::BITS as usize];
+        let (mut len, mut right_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    nodes[len] = l;
+                    len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right[right_len] = r;
+                    right_len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in right[..right_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = S::id();
+        for &v in &nodes[..len] {
+            let t = S::op(&acc, &self.data[v]);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                v <<= 1;
+                let t = S::op(&acc, &self.data[v]);
+                if !p(&t) {
+                    acc = t;
+                    v += 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
+    }
+
+    /// Returns the largest `i` in the given range such that `p(op(a[i], ..., a[r - 1]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[i], ..., a[r - 1])`, it also
+    /// holds for every longer suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_last<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
     where
         P: Fn(&S) -> bool,
     {
-        todo!();
+        let size = self.size();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        // Nodes covering the range, from right to left.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut left = [0; usize::BITS as usize];
+        let (mut len, mut left_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    left[left_len] = l;
+                    left_len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    nodes[len] = r;
+                    len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in left[..left_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = S::id();
+        for &v in &nodes[..len] {
+            let t = S::op(&self.data[v], &acc);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                v = (v << 1) + 1;
+                let t = S::op(&self.data[v], &acc);
+                if !p(&t) {
+                    acc = t;
+                    v -= 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
+    }
+
+    /// Appends an element to the end.
+    ///
+    /// The leaves are doubled when full, so that the tree can track a growing array.
+    ///
+    /// # Time complexity
+    ///
+    /// Amortized O(log n)
+    pub fn push(&mut self, x: S) {
+        if self.len() == self.size() {
+            self.rebuild((self.size() << 1).max(1));
+        }
+        self.n += 1;
+        self.set(self.n - 1, x);
+    }
+
+    /// Removes the last element and returns it, or `None` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn pop(&mut self) -> Option<S> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut i = self.size() + self.len() - 1;
+        self.n -= 1;
+        let x = std::mem::replace(&mut self.data[i], S::id());
+        while i > 1 {
+            i >>= 1;
+            self.data[i] = S::op(&self.data[i << 1], &self.data[(i << 1) + 1]);
+        }
+        Some(x)
     }
 
+    /// Resizes the tree to `n` elements, filling new elements with `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n + len())
+    pub fn resize(&mut self, n: usize) {
+        if n > self.size() {
+            self.rebuild(n);
+        } else if n < self.len() {
+            let size = self.size();
+            for x in &mut self.data[size + n..size + self.n] {
+                *x = S::id();
+            }
+            for i in (1..size).rev() {
+                self.data[i] = S::op(&self.data[i << 1], &self.data[(i << 1) + 1]);
+            }
+        }
+        self.n = n;
+    }
+
+    /// Returns the largest `r` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(S::id())` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter prefix starting at `l`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > len()` or `p(S::id())` is false in debug builds.
     #[inline]
-    pub fn min_left<P>(&self, _r: usize, _p: P) -> usize
+    pub fn max_right<P>(&self, l: usize, p: P) -> usize
     where
         P: Fn(&S) -> bool,
     {
-        todo!();
+        debug_assert!(
+            l <= self.len(),
+            "index out of bounds: l={}, len={}",
+            l,
+            self.len(),
+        );
+        debug_assert!(p(&S::id()), "predicate must hold for the identity");
+        let size = self.size();
+        let (mut l, mut r) = (l + size, self.len() + size);
+        let mut right = [0; usize::BITS as usize];
+        let mut right_len = 0;
+        let mut acc = S::id();
+        unsafe {
+            let d = self.data.as_ptr();
+            let check = |mut v: usize, acc: &mut S| -> Option<usize> {
+                let t = S::op(acc, &*d.add(v));
+                if p(&t) {
+                    *acc = t;
+                    return None;
+                }
+                while v < size {
+                    v <<= 1;
+                    let t = S::op(acc, &*d.add(v));
+                    if p(&t) {
+                        *acc = t;
+                        v += 1;
+                    }
+                }
+                Some(v - size)
+            };
+            while l < r {
+                if l & 1 == 1 {
+                    if let Some(i) = check(l, &mut acc) {
+                        return i;
+                    }
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right[right_len] = r;
+                    right_len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+            for &v in right[..right_len].iter().rev() {
+                if let Some(i) = check(v, &mut acc) {
+                    return i;
+                }
+            }
+        }
+        self.len()
+    }
+
+    /// Returns the smallest `l` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(S::id())` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r > len()` or `p(S::id())` is false in debug builds.
+    #[inline]
+    pub fn min_left<P>(&self, r: usize, p: P) -> usize
+    where
+        P: Fn(&S) -> bool,
+    {
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        debug_assert!(p(&S::id()), "predicate must hold for the identity");
+        let size = self.size();
+        let (mut l, mut r) = (size, r + size);
+        let mut left = [0; usize::BITS as usize];
+        let mut left_len = 0;
+        let mut acc = S::id();
+        unsafe {
+            let d = self.data.as_ptr();
+            let check = |mut v: usize, acc: &mut S| -> Option<usize> {
+                let t = S::op(&*d.add(v), acc);
+                if p(&t) {
+                    *acc = t;
+                    return None;
+                }
+                while v < size {
+                    v = (v << 1) + 1;
+                    let t = S::op(&*d.add(v), acc);
+                    if p(&t) {
+                        *acc = t;
+                        v -= 1;
+                    }
+                }
+                Some(v + 1 - size)
+            };
+            while l < r {
+                if l & 1 == 1 {
+                    left[left_len] = l;
+                    left_len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    if let Some(i) = check(r, &mut acc) {
+                        return i;
+                    }
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+            for &v in left[..left_len].iter().rev() {
+                if let Some(i) = check(v, &mut acc) {
+                    return i;
+                }
+            }
+        }
+        0
     }
 
     /// Returns the number of elements.
@@ -238,7 +714,7 @@ impl<S: Monoid> SegmentTree<S> {
     /// O(1)
     #[inline(always)]
     pub fn len(&self) -> usize {
-        self.0.len() >> 1
+        self.n
     }
 
     /// Returns `true` if the segment tree is empty.
@@ -250,4 +726,364 @@ impl<S: Monoid> SegmentTree<S> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns the number of leaves.
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.data.len() >> 1
+    }
+
+    /// Moves the elements into a tree with `size` leaves.
+    fn rebuild(&mut self, size: usize) {
+        let leaves = self.size();
+        let old = std::mem::take(&mut self.data).into_vec();
+        let mut data = Vec::with_capacity(size << 1);
+        data.resize(size, S::id());
+        data.extend(old.into_iter().skip(leaves).take(self.n));
+        data.resize(size << 1, S::id());
+        for i in (1..size).rev() {
+            data[i] = S::op(&data[i << 1], &data[(i << 1) + 1]);
+        }
+        self.data = data.into_boxed_slice();
+    }
+}
+
+/// A guard giving mutable access to an element of a [`SegmentTree`], returned by
+/// [`SegmentTree::entry`].
+///
+/// The ancestors of the element are recomputed when the guard is dropped.
+pub struct Entry<'a, S: Monoid> {
+    tree: &'a mut SegmentTree<S>,
+    /// Index of the leaf in the tree.
+    i: usize,
+}
+
+impl<S: Monoid> std::ops::Deref for Entry<'_, S> {
+    type Target = S;
+
+    #[inline(always)]
+    fn deref(&self) -> &S {
+        unsafe { self.tree.data.get_unchecked(self.i) }
+    }
+}
+
+impl<S: Monoid> std::ops::DerefMut for Entry<'_, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut S {
+        unsafe { self.tree.data.get_unchecked_mut(self.i) }
+    }
+}
+
+impl<S: Monoid> Drop for Entry<'_, S> {
+    #[inline]
+    fn drop(&mut self) {
+        let mut i = self.i;
+        unsafe {
+            let d = self.tree.data.as_mut_ptr();
+            while i > 1 {
+                i >>= 1;
+                *d.add(i) = S::op(&*d.add(i << 1), &*d.add((i << 1) + 1));
+            }
+        }
+    }
+}
+
+impl<S: Monoid> FromIterator<S> for SegmentTree<S> {
+    /// Creates a new segment tree from an iterator.
+    ///
+    /// If the iterator reports an exact size, the tree is built in place without
+    /// reallocation.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut v = match iter.size_hint() {
+            (lo, Some(hi)) if lo == hi => Vec::with_capacity(lo << 1),
+            _ => Vec::new(),
+        };
+        v.extend(iter);
+        Self::from_vec(v)
+    }
+}
+
+impl<S: Monoid + PartialEq> PartialEq for SegmentTree<S> {
+    /// Returns `true` if both trees hold the same elements, regardless of their capacity.
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<S: Monoid + Eq> Eq for SegmentTree<S> {}
+
+impl<S: Monoid> Default for SegmentTree<S> {
+    /// Creates an empty segment tree.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<S: Monoid + std::fmt::Debug> std::fmt::Debug for SegmentTree<S> {
+    /// Formats the length and the nodes, grouped by depth from the root down.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SegmentTree")
+            .field("len", &self.n)
+            .field("levels", &Levels(&self.data))
+            .finish()
+    }
+}
+
+impl<S: Monoid + std::fmt::Debug> std::fmt::Display for SegmentTree<S> {
+    /// Writes the nodes one level per line, from the root down.
+    ///
+    /// When the number of leaves is not a power of two, the leaves span the last two levels.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&Levels(&self.data), f)
+    }
+}
+
+impl<S: Monoid> SegmentTree<(S, Reversed<S>)> {
+    /// Returns `op(a[r-1], a[r-2], ..., a[l])` for the given range.
+    ///
+    /// Each element `x` is stored as `(x, Reversed(x))`, so that every node holds its fold in
+    /// both directions. Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold_rev(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        self.range_fold(range).1.0
+    }
+}
+
+impl<S: Monoid + Codec> SegmentTree<S> {
+    /// Encodes the elements into a compact binary form.
+    ///
+    /// Only the elements are stored; internal nodes are rebuilt by
+    /// [`from_bytes`](Self::from_bytes).
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.len().encode(&mut out);
+        for x in self.iter() {
+            x.encode(&mut out);
+        }
+        out
+    }
+
+    /// Decodes a segment tree from the output of [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if `bytes` is not a valid encoding.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let v = decode_seq(&mut bytes)?;
+        bytes.is_empty().then(|| Self::from_vec(v))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::segment_tree::Semigroup;
+
+    const MOD: u64 = 998_244_353;
+
+    /// A 2x2 matrix modulo `MOD` under multiplication.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Mat([[u64; 2]; 2]);
+
+    impl Semigroup for Mat {
+        fn op(&self, other: &Self) -> Self {
+            let (a, b) = (&self.0, &other.0);
+            Mat(std::array::from_fn(|i| {
+                std::array::from_fn(|j| (a[i][0] * b[0][j] + a[i][1] * b[1][j]) % MOD)
+            }))
+        }
+    }
+
+    impl Monoid for Mat {
+        fn id() -> Self {
+            Mat([[1, 0], [0, 1]])
+        }
+    }
+
+    /// A string under concatenation.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Str(String);
+
+    impl Semigroup for Str {
+        fn op(&self, other: &Self) -> Self {
+            Str(self.0.clone() + &other.0)
+        }
+    }
+
+    impl Monoid for Str {
+        fn id() -> Self {
+            Str(String::new())
+        }
+    }
+
+    pub(crate) struct Rng(pub(crate) u64);
+
+    impl Rng {
+        pub(crate) fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        pub(crate) fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    pub(crate) fn fold<S: Monoid>(a: &[S]) -> S {
+        a.iter().fold(S::id(), |acc, x| S::op(&acc, x))
+    }
+
+    /// Checks every query of `t` against `a`, on all ranges if `exhaustive` and on random
+    /// ones otherwise.
+    fn check<S: Monoid + PartialEq + std::fmt::Debug>(
+        t: &SegmentTree<S>,
+        a: &[S],
+        exhaustive: bool,
+        rng: &mut Rng,
+    ) {
+        let n = a.len();
+        assert_eq!(t.len(), n);
+        assert_eq!(t.to_vec(), a);
+        assert_eq!(t.all_fold(), fold(a));
+        let ranges: Vec<(usize, usize)> = if exhaustive {
+            (0..=n).flat_map(|l| (l..=n).map(move |r| (l, r))).collect()
+        } else {
+            (0..20)
+                .map(|_| {
+                    let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                    (x.min(y), x.max(y))
+                })
+                .collect()
+        };
+        for &(l, r) in &ranges {
+            assert_eq!(t.range_fold(l..r), fold(&a[l..r]), "range_fold({l}..{r})");
+            // The folds of the test inputs are all distinct, so `p` holds exactly for the
+            // folds of `a[l..l]` to `a[l..r]`.
+            let prefixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().scan(S::id(), |acc, x| {
+                    *acc = S::op(acc, x);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(
+                t.max_right(l, |x| prefixes.contains(x)),
+                r,
+                "max_right({l})"
+            );
+            let suffixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().rev().scan(S::id(), |acc, x| {
+                    *acc = S::op(x, acc);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(t.min_left(r, |x| suffixes.contains(x)), l, "min_left({r})");
+        }
+    }
+
+    /// Checks trees built in every way from `a`.
+    fn check_all<S: Monoid + PartialEq + std::fmt::Debug>(
+        a: &[S],
+        exhaustive: bool,
+        rng: &mut Rng,
+    ) {
+        check(&SegmentTree::from_vec(a.to_vec()), a, exhaustive, rng);
+        check(&SegmentTree::from_slice(a), a, exhaustive, rng);
+        let mut t = SegmentTree::new(a.len());
+        for (i, x) in a.iter().enumerate() {
+            t.set(i, x.clone());
+        }
+        check(&t, a, exhaustive, rng);
+        let mut t = SegmentTree::new(0);
+        for x in a {
+            t.push(x.clone());
+        }
+        check(&t, a, exhaustive, rng);
+    }
+
+    pub(crate) fn random_str(rng: &mut Rng) -> Str {
+        Str(((b'a' + rng.below(3) as u8) as char).to_string())
+    }
+
+    pub(crate) fn random_mat(rng: &mut Rng) -> Mat {
+        Mat(std::array::from_fn(|_| {
+            std::array::from_fn(|_| rng.next() % MOD)
+        }))
+    }
+
+    #[test]
+    fn string_concat() {
+        let mut rng = Rng(88_172_645_463_325_252);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_str(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_str(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+
+    #[test]
+    fn matrix_product() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_mat(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_mat(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        use crate::monoids::{Add, MaxSubarray, StrConcat};
+
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for n in [0, 1, 5, 64, 100] {
+            let a: Vec<_> = (0..n).map(|_| Add(rng.next() as i64 >> 20)).collect();
+            let t = SegmentTree::from_vec(a.clone());
+            let bytes = t.to_bytes();
+            let u = SegmentTree::<Add<i64>>::from_bytes(&bytes).unwrap();
+            assert_eq!(u.to_vec(), a);
+            assert_eq!(u.all_fold(), t.all_fold());
+            // Truncated or extended inputs are rejected.
+            if let Some((_, init)) = bytes.split_last() {
+                assert!(SegmentTree::<Add<i64>>::from_bytes(init).is_none());
+            }
+            let mut longer = bytes.clone();
+            longer.push(0);
+            assert!(SegmentTree::<Add<i64>>::from_bytes(&longer).is_none());
+
+            let a: Vec<_> = (0..n)
+                .map(|_| MaxSubarray::new(rng.below(21) as i64 - 10))
+                .collect();
+            let t = SegmentTree::from_vec(a.clone());
+            let u = SegmentTree::<MaxSubarray<i64>>::from_bytes(&t.to_bytes()).unwrap();
+            assert_eq!(u.to_vec(), a);
+
+            let a: Vec<_> = (0..n).map(|_| StrConcat(random_str(&mut rng).0)).collect();
+            let t = SegmentTree::from_vec(a.clone());
+            let u = SegmentTree::<StrConcat>::from_bytes(&t.to_bytes()).unwrap();
+            assert_eq!(u.all_fold(), fold(&a));
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use crate::segment_tree::levels::Levels;
+
 /// A segment tree for efficient point updates and range queries with operator.
 ///
 /// Given a monoid `(S, op, id)`, this data structure supports:
@@ -5,7 +7,9 @@
 /// - Point operation: `operate(i, x)` sets `a[i] = op(a[i], x)`
 /// - Range query: `range_fold(l..r)` returns `op(a[l], op(a[l+1], ..., a[r-1]))`
 ///
-/// Both operations run in O(log n) time.
+/// Both operations run in O(log n) time. The tree uses 2n nodes for any `n`, and every fold
+/// combines elements in order, so `op` need not be commutative.
+#[derive(Clone)]
 #[repr(C)]
 pub struct SegmentTreeWith<S, Op>
 where
@@ -46,6 +50,9 @@ where
     /// O(n)
     pub fn from_vec(mut v: Vec<S>, id: S, op: Op) -> Self {
         let n = v.len();
+        if n == 0 {
+            return Self::new(0, id, op);
+        }
         v.reserve(n);
         unsafe {
             let v = v.as_mut_ptr();
@@ -73,9 +80,9 @@ where
     pub fn from_slice(v: &[S], id: S, op: Op) -> Self {
         let n = v.len();
         let mut data = vec![id.clone(); n << 1];
+        data[n..].clone_from_slice(v);
         unsafe {
             let d = data.as_mut_ptr();
-            std::ptr::copy_nonoverlapping(v.as_ptr(), d.add(n), n);
             for i in (1..n).rev() {
                 *d.add(i) = op(&*d.add(i << 1), &*d.add((i << 1) + 1));
             }
@@ -87,6 +94,20 @@ where
         }
     }
 
+    /// Creates a new segment tree with `n` elements, where the element at index `i` is `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<F>(n: usize, f: F, id: S, op: Op) -> Self
+    where
+        F: FnMut(usize) -> S,
+    {
+        let mut v = Vec::with_capacity(n << 1);
+        v.extend((0..n).map(f));
+        Self::from_vec(v, id, op)
+    }
+
     /// Sets the value at index `i` to `x`.
     ///
     /// # Time complexity
@@ -214,25 +235,351 @@ where
     ///
     /// # Time complexity
     ///
-    /// O(1)
+    /// O(1) if `n` is a power of two, O(log n) otherwise.
     pub fn all_fold(&self) -> S {
-        unsafe { self.data.get_unchecked(1).clone() }
+        if self.len().is_power_of_two() {
+            unsafe { self.data.get_unchecked(1).clone() }
+        } else {
+            // The root wraps around the leaves out of order unless `n` is a power of two.
+            self.range_fold(..)
+        }
     }
 
+    /// Returns an iterator over the elements, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) to create, O(n) to consume.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, S> {
+        self.data[self.len()..].iter()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
     #[inline]
-    pub fn max_right<P>(&self, _l: usize, _p: P) -> usize
+    pub fn to_vec(&self) -> Vec<S> {
+        self.data[self.len()..].to_vec()
+    }
+
+    /// Returns the largest `r` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(id)` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter prefix starting at `l`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > len()` or `p(id)` is false in debug builds.
+    #[inline]
+    pub fn max_right<P>(&self, l: usize, p: P) -> usize
     where
         P: Fn(&S) -> bool,
     {
-        todo!();
+        debug_assert!(
+            l <= self.len(),
+            "index out of bounds: l={}, len={}",
+            l,
+            self.len(),
+        );
+        debug_assert!(p(&self.id), "predicate must hold for the identity");
+        let n = self.len();
+        let (mut l, mut r) = (l + n, n << 1);
+        let mut right = [0; usize::BITS as usize];
+        let mut right_len = 0;
+        let mut acc = self.id.clone();
+        unsafe {
+            let d = self.data.as_ptr();
+            let check = |mut v: usize, acc: &mut S| -> Option<usize> {
+                let t = (self.op)(acc, &*d.add(v));
+                if p(&t) {
+                    *acc = t;
+                    return None;
+                }
+                while v < n {
+                    v <<= 1;
+                    let t = (self.op)(acc, &*d.add(v));
+                    if p(&t) {
+                        *acc = t;
+                        v += 1;
+                    }
+                }
+                Some(v - n)
+            };
+            while l < r {
+                if l & 1 == 1 {
+                    if let Some(i) = check(l, &mut acc) {
+                        return i;
+                    }
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right[right_len] = r;
+                    right_len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+            for &v in right[..right_len].iter().rev() {
+                if let Some(i) = check(v, &mut acc) {
+                    return i;
+                }
+            }
+        }
+        n
     }
 
+    /// Returns the smallest `l` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(id)` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r > len()` or `p(id)` is false in debug builds.
     #[inline]
-    pub fn min_left<P>(&self, _r: usize, _p: P) -> usize
+    pub fn min_left<P>(&self, r: usize, p: P) -> usize
     where
         P: Fn(&S) -> bool,
     {
-        todo!();
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        debug_assert!(p(&self.id), "predicate must hold for the identity");
+        let n = self.len();
+        let (mut l, mut r) = (n, r + n);
+        let mut left = [0; usize::BITS as usize];
+        let mut left_len = 0;
+        let mut acc = self.id.clone();
+        unsafe {
+            let d = self.data.as_ptr();
+            let check = |mut v: usize, acc: &mut S| -> Option<usize> {
+                let t = (self.op)(&*d.add(v), acc);
+                if p(&t) {
+                    *acc = t;
+                    return None;
+                }
+                while v < n {
+                    v = (v << 1) + 1;
+                    let t = (self.op)(&*d.add(v), acc);
+                    if p(&t) {
+                        *acc = t;
+                        v -= 1;
+                    }
+                }
+                Some(v + 1 - n)
+            };
+            while l < r {
+                if l & 1 == 1 {
+                    left[left_len] = l;
+                    left_len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    if let Some(i) = check(r, &mut acc) {
+                        return i;
+                    }
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+            for &v in left[..left_len].iter().rev() {
+                if let Some(i) = check(v, &mut acc) {
+                    return i;
+                }
+            }
+        }
+        0
+    }
+
+    /// Returns the smallest `i` in the given range such that `p(op(a[l], ..., a[i]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[l], ..., a[i])`, it also holds
+    /// for every longer prefix starting at `l`. For example, the first zero in a range is
+    /// found with a minimum monoid and `p = |x| x == 0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_first<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let size = self.len();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        // Nodes covering the range, from left to right.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut right = [0; usize::BITS as usize];
+        let (mut len, mut right_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    nodes[len] = l;
+                    len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right[right_len] = r;
+                    right_len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in right[..right_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = self.id.clone();
+        for &v in &nodes[..len] {
+            let t = (self.op)(&acc, &self.data[v]);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                v <<= 1;
+                let t = (self.op)(&acc, &self.data[v]);
+                if !p(&t) {
+                    acc = t;
+                    v += 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
+    }
+
+    /// Returns the largest `i` in the given range such that `p(op(a[i], ..., a[r - 1]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[i], ..., a[r - 1])`, it also
+    /// holds for every longer suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_last<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let size = self.len();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        // Nodes covering the range, from right to left.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut left = [0; usize::BITS as usize];
+        let (mut len, mut left_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    left[left_len] = l;
+                    left_len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    nodes[len] = r;
+                    len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in left[..left_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = self.id.clone();
+        for &v in &nodes[..len] {
+            let t = (self.op)(&self.data[v], &acc);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                v = (v << 1) + 1;
+                let t = (self.op)(&self.data[v], &acc);
+                if !p(&t) {
+                    acc = t;
+                    v -= 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
     }
 
     /// Returns the number of elements.
@@ -255,3 +602,143 @@ where
         self.len() == 0
     }
 }
+
+impl<S, Op> PartialEq for SegmentTreeWith<S, Op>
+where
+    S: Clone + PartialEq,
+    Op: Fn(&S, &S) -> S,
+{
+    /// Returns `true` if both trees hold the same elements.
+    ///
+    /// The identities and operations are not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<S, Op> Eq for SegmentTreeWith<S, Op>
+where
+    S: Clone + Eq,
+    Op: Fn(&S, &S) -> S,
+{
+}
+
+impl<S, Op> std::fmt::Debug for SegmentTreeWith<S, Op>
+where
+    S: Clone + std::fmt::Debug,
+    Op: Fn(&S, &S) -> S,
+{
+    /// Formats the length, the identity and the nodes, grouped by depth from the root down.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SegmentTreeWith")
+            .field("len", &self.len())
+            .field("id", &self.id)
+            .field("levels", &Levels(&self.data))
+            .finish()
+    }
+}
+
+impl<S, Op> std::fmt::Display for SegmentTreeWith<S, Op>
+where
+    S: Clone + std::fmt::Debug,
+    Op: Fn(&S, &S) -> S,
+{
+    /// Writes the nodes one level per line, from the root down.
+    ///
+    /// When the number of leaves is not a power of two, the leaves span the last two levels.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&Levels(&self.data), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment_tree::Monoid;
+    use crate::segment_tree::core::tests::{Rng, fold, random_mat, random_str};
+
+    /// Checks every query of `t` against `a`, on all ranges if `exhaustive` and on random
+    /// ones otherwise.
+    fn check<S, Op>(t: &SegmentTreeWith<S, Op>, a: &[S], exhaustive: bool, rng: &mut Rng)
+    where
+        S: Monoid + PartialEq + std::fmt::Debug,
+        Op: Fn(&S, &S) -> S,
+    {
+        let n = a.len();
+        assert_eq!(t.len(), n);
+        assert_eq!(t.to_vec(), a);
+        assert_eq!(t.all_fold(), fold(a));
+        let ranges: Vec<(usize, usize)> = if exhaustive {
+            (0..=n).flat_map(|l| (l..=n).map(move |r| (l, r))).collect()
+        } else {
+            (0..20)
+                .map(|_| {
+                    let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                    (x.min(y), x.max(y))
+                })
+                .collect()
+        };
+        for &(l, r) in &ranges {
+            assert_eq!(t.range_fold(l..r), fold(&a[l..r]), "range_fold({l}..{r})");
+            // The folds of the test inputs are all distinct, so `p` holds exactly for the
+            // folds of `a[l..l]` to `a[l..r]`.
+            let prefixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().scan(S::id(), |acc, x| {
+                    *acc = S::op(acc, x);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(
+                t.max_right(l, |x| prefixes.contains(x)),
+                r,
+                "max_right({l})"
+            );
+            let suffixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().rev().scan(S::id(), |acc, x| {
+                    *acc = S::op(x, acc);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(t.min_left(r, |x| suffixes.contains(x)), l, "min_left({r})");
+        }
+    }
+
+    /// Checks trees built in every way from `a`.
+    fn check_all<S: Monoid + PartialEq + std::fmt::Debug>(
+        a: &[S],
+        exhaustive: bool,
+        rng: &mut Rng,
+    ) {
+        let t = SegmentTreeWith::from_vec(a.to_vec(), S::id(), S::op);
+        check(&t, a, exhaustive, rng);
+        let t = SegmentTreeWith::from_slice(a, S::id(), S::op);
+        check(&t, a, exhaustive, rng);
+        let mut t = SegmentTreeWith::new(a.len(), S::id(), S::op);
+        for (i, x) in a.iter().enumerate() {
+            t.set(i, x.clone());
+        }
+        check(&t, a, exhaustive, rng);
+    }
+
+    #[test]
+    fn string_concat() {
+        let mut rng = Rng(88_172_645_463_325_252);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_str(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_str(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+
+    #[test]
+    fn matrix_product() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_mat(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_mat(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+}
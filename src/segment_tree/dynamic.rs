@@ -0,0 +1,376 @@
+use crate::segment_tree::Monoid;
+
+/// Index of a missing child.
+const NIL: u32 = u32::MAX;
+
+/// A node of a dynamic segment tree covering a half-open range of indices.
+#[derive(Clone, Debug)]
+struct Node<S> {
+    /// `op` of every element in the range of this node.
+    val: S,
+    /// Index of the left child in the arena, or `NIL`.
+    left: u32,
+    /// Index of the right child in the arena, or `NIL`.
+    right: u32,
+}
+
+/// A segment tree over a huge index space that allocates nodes on demand.
+///
+/// Given a monoid `(S, op, id)` and a length `n` up to `u64::MAX`, this data structure
+/// supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Point operation: `operate(i, x)` sets `a[i] = op(a[i], x)`
+/// - Range query: `range_fold(l..r)` returns `op(a[l], op(a[l+1], ..., a[r-1]))`
+///
+/// Every element is initially `S::id()`. Both operations run in O(log n) time, and each
+/// update allocates at most O(log n) nodes, so no coordinate compression is needed.
+///
+/// Nodes live in a single arena and refer to their children by `u32` indices, so a node
+/// costs no allocation of its own and fewer than `2^32 - 1` nodes can be allocated.
+#[derive(Clone, Debug)]
+pub struct DynamicSegmentTree<S: Monoid> {
+    /// Arena of all nodes, including nodes no longer reachable after a merge.
+    nodes: Vec<Node<S>>,
+    /// Index of the root in the arena, or `NIL`.
+    root: u32,
+    n: u64,
+}
+
+impl<S: Monoid> DynamicSegmentTree<S> {
+    /// Creates a new dynamic segment tree with `n` elements, all initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(n: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            n,
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more nodes.
+    ///
+    /// Each point update allocates at most `ceil(log2(n)) + 1` nodes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(number of allocated nodes) if the arena grows, O(1) otherwise.
+    #[inline]
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn set(&mut self, i: u64, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.point_update(i, move |_| x);
+    }
+
+    /// Applies `op(a[i], x)` to the element at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn operate(&mut self, i: u64, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.point_update(i, move |a| S::op(a, &x));
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: u64) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let (mut l, mut r) = (0, self.n);
+        let mut t = self.root;
+        while t != NIL {
+            let node = &self.nodes[t as usize];
+            if r - l == 1 {
+                return node.val.clone();
+            }
+            let m = l + ((r - l) >> 1);
+            if i < m {
+                t = node.left;
+                r = m;
+            } else {
+                t = node.right;
+                l = m;
+            }
+        }
+        S::id()
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<u64>) -> S {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return S::id();
+        }
+        self.fold_rec(self.root, 0, self.n, l, r)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        if self.root == NIL {
+            S::id()
+        } else {
+            self.nodes[self.root as usize].val.clone()
+        }
+    }
+
+    /// Merges two dynamic segment trees of the same length into one whose element `i` is
+    /// `op(a[i], b[i])`.
+    ///
+    /// The arena of the smaller tree is appended to that of the larger one, and then only
+    /// nodes present in both trees are visited, each visit retiring one node. Hence, starting
+    /// from trees built by k point updates in total, any sequence of merges (e.g. merging the
+    /// value-indexed trees of children up a rooted tree) runs in O(k log n log k) total time.
+    /// Retired nodes stay in the arena until the tree is dropped.
+    ///
+    /// # Time complexity
+    ///
+    /// O(size of the smaller arena + number of nodes present in both trees)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a.len() != b.len()` in debug builds.
+    pub fn merge(a: Self, b: Self) -> Self {
+        debug_assert_eq!(
+            a.len(),
+            b.len(),
+            "length mismatch: a.len()={}, b.len()={}",
+            a.len(),
+            b.len(),
+        );
+        let swapped = a.nodes.len() < b.nodes.len();
+        let (mut dst, src) = if swapped { (b, a) } else { (a, b) };
+        let offset = dst.nodes.len() as u32;
+        debug_assert!(
+            dst.nodes.len() + src.nodes.len() < NIL as usize,
+            "too many nodes: {}",
+            dst.nodes.len() + src.nodes.len(),
+        );
+        let shift = |t: u32| if t == NIL { NIL } else { t + offset };
+        dst.nodes.extend(src.nodes.into_iter().map(|t| Node {
+            left: shift(t.left),
+            right: shift(t.right),
+            ..t
+        }));
+        let (ra, rb) = if swapped {
+            (shift(src.root), dst.root)
+        } else {
+            (dst.root, shift(src.root))
+        };
+        dst.root = dst.merge_rec(ra, rb, 0, dst.n);
+        dst
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns `true` if the segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a new node holding `S::id()` and returns its index.
+    #[inline]
+    fn alloc(&mut self) -> u32 {
+        debug_assert!(
+            self.nodes.len() < NIL as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(Node {
+            val: S::id(),
+            left: NIL,
+            right: NIL,
+        });
+        (self.nodes.len() - 1) as u32
+    }
+
+    #[inline]
+    fn update(&mut self, t: u32) {
+        let Node { left, right, .. } = self.nodes[t as usize];
+        let val = match (left, right) {
+            (NIL, NIL) => S::id(),
+            (l, NIL) => self.nodes[l as usize].val.clone(),
+            (NIL, r) => self.nodes[r as usize].val.clone(),
+            (l, r) => S::op(&self.nodes[l as usize].val, &self.nodes[r as usize].val),
+        };
+        self.nodes[t as usize].val = val;
+    }
+
+    fn merge_rec(&mut self, a: u32, b: u32, l: u64, r: u64) -> u32 {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if r - l == 1 {
+            let val = S::op(&self.nodes[a as usize].val, &self.nodes[b as usize].val);
+            self.nodes[a as usize].val = val;
+            return a;
+        }
+        let m = l + ((r - l) >> 1);
+        let Node { left, right, .. } = self.nodes[b as usize];
+        let t = self.merge_rec(self.nodes[a as usize].left, left, l, m);
+        self.nodes[a as usize].left = t;
+        let t = self.merge_rec(self.nodes[a as usize].right, right, m, r);
+        self.nodes[a as usize].right = t;
+        self.update(a);
+        a
+    }
+
+    fn point_update<F>(&mut self, i: u64, f: F)
+    where
+        F: FnOnce(&S) -> S,
+    {
+        if self.root == NIL {
+            self.root = self.alloc();
+        }
+        // Indices are at most 64 levels deep.
+        let mut path = [NIL; 64];
+        let mut depth = 0;
+        let (mut l, mut r) = (0, self.n);
+        let mut t = self.root;
+        while r - l > 1 {
+            path[depth] = t;
+            depth += 1;
+            let m = l + ((r - l) >> 1);
+            let child = if i < m {
+                r = m;
+                self.nodes[t as usize].left
+            } else {
+                l = m;
+                self.nodes[t as usize].right
+            };
+            t = if child == NIL {
+                let c = self.alloc();
+                let node = &mut self.nodes[t as usize];
+                if i < m {
+                    node.left = c;
+                } else {
+                    node.right = c;
+                }
+                c
+            } else {
+                child
+            };
+        }
+        let node = &mut self.nodes[t as usize];
+        node.val = f(&node.val);
+        for &t in path[..depth].iter().rev() {
+            self.update(t);
+        }
+    }
+
+    fn fold_rec(&self, t: u32, l: u64, r: u64, ql: u64, qr: u64) -> S {
+        if t == NIL {
+            return S::id();
+        }
+        let node = &self.nodes[t as usize];
+        if ql <= l && r <= qr {
+            return node.val.clone();
+        }
+        let m = l + ((r - l) >> 1);
+        if qr <= m {
+            self.fold_rec(node.left, l, m, ql, qr)
+        } else if m <= ql {
+            self.fold_rec(node.right, m, r, ql, qr)
+        } else {
+            S::op(
+                &self.fold_rec(node.left, l, m, ql, qr),
+                &self.fold_rec(node.right, m, r, ql, qr),
+            )
+        }
+    }
+}
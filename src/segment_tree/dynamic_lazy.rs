@@ -0,0 +1,404 @@
+use crate::segment_tree::{Action, Monoid};
+
+/// Index of a missing child.
+const NIL: u32 = u32::MAX;
+
+/// A node of a dynamic lazy segment tree covering a half-open range of indices.
+#[derive(Clone, Debug)]
+struct Node<S, F> {
+    /// `op` of every element in the range of this node, with `lazy` already applied.
+    val: S,
+    /// Action pending to be propagated to the children.
+    lazy: F,
+    /// Index of the left child in the arena, or `NIL`.
+    left: u32,
+    /// Index of the right child in the arena, or `NIL`.
+    right: u32,
+}
+
+impl<S: Monoid, F: Action<S>> Node<S, F> {
+    #[inline]
+    fn all_apply(&mut self, f: &F) {
+        self.val = f.act(&self.val);
+        self.lazy = F::op(f, &self.lazy);
+    }
+}
+
+/// A lazy segment tree over a huge index space that allocates nodes on demand.
+///
+/// Given a monoid `(S, op, id)`, an action monoid `(F, compose, id)` and a length `n` up to
+/// `u64::MAX`, this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Point action: `apply(i, f)` sets `a[i] = f.act(a[i])`
+/// - Range action: `range_apply(l..r, f)` applies `f` to all elements in range
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+///
+/// All operations run in O(log n) time, and each update allocates at most O(log n) nodes.
+///
+/// Nodes live in a single arena and refer to their children by `u32` indices, so a node
+/// costs no allocation of its own and fewer than `2^32 - 1` nodes can be allocated.
+#[derive(Clone, Debug)]
+pub struct DynamicLazySegmentTree<S: Monoid, F: Action<S>> {
+    /// Arena of all nodes.
+    nodes: Vec<Node<S, F>>,
+    /// Index of the root in the arena, or `NIL`.
+    root: u32,
+    n: u64,
+    /// Returns `op(a[l], ..., a[r-1])` for a range `l..r` that has never been updated.
+    init: fn(u64, u64) -> S,
+}
+
+impl<S: Monoid, F: Action<S>> DynamicLazySegmentTree<S, F> {
+    /// Creates a new dynamic lazy segment tree with `n` elements, all initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new(n: u64) -> Self {
+        Self::with_init(n, |_, _| S::id())
+    }
+
+    /// Creates a new dynamic lazy segment tree with `n` elements, where `init(l, r)` returns
+    /// `op(a[l], ..., a[r-1])` of the initial values.
+    ///
+    /// This is needed whenever the initial values are not `S::id()`, e.g. when `S` carries
+    /// the length of its range for a range-add action over sums.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn with_init(n: u64, init: fn(u64, u64) -> S) -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            n,
+            init,
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more nodes.
+    ///
+    /// Each update allocates at most `4 * ceil(log2(n)) + 1` nodes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(number of allocated nodes) if the arena grows, O(1) otherwise.
+    #[inline]
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn set(&mut self, i: u64, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.point_update(i, move |_| x);
+    }
+
+    /// Applies `S::op(a[i], x)` to the element at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn operate(&mut self, i: u64, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.point_update(i, move |a| S::op(a, &x));
+    }
+
+    /// Applies action `f` to the element at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn apply(&mut self, i: u64, f: F) {
+        self.range_apply(i..i + 1, f);
+    }
+
+    /// Applies action `f` to all elements in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_apply(&mut self, range: impl std::ops::RangeBounds<u64>, f: F) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return;
+        }
+        let root = self.root();
+        self.apply_rec(root, 0, self.n, l, r, &f);
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: u64) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.fold_rec(self.root, 0, self.n, i, i + 1)
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<u64>) -> S {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return S::id();
+        }
+        self.fold_rec(self.root, 0, self.n, l, r)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        match self.root {
+            NIL if self.n == 0 => S::id(),
+            NIL => (self.init)(0, self.n),
+            t => self.nodes[t as usize].val.clone(),
+        }
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    /// Returns `true` if the lazy segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a new node for the range `l..r`, initialized from `init`, and returns its
+    /// index.
+    #[inline]
+    fn alloc(&mut self, l: u64, r: u64) -> u32 {
+        debug_assert!(
+            self.nodes.len() < NIL as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(Node {
+            val: (self.init)(l, r),
+            lazy: F::id(),
+            left: NIL,
+            right: NIL,
+        });
+        (self.nodes.len() - 1) as u32
+    }
+
+    /// Returns the index of the root, creating it if it is missing.
+    #[inline]
+    fn root(&mut self) -> u32 {
+        if self.root == NIL {
+            self.root = self.alloc(0, self.n);
+        }
+        self.root
+    }
+
+    /// Propagates the pending action of node `t`, covering `l..r`, to both children,
+    /// creating them if they are missing.
+    #[inline]
+    fn push(&mut self, t: u32, l: u64, r: u64) {
+        let m = l + ((r - l) >> 1);
+        if self.nodes[t as usize].left == NIL {
+            let c = self.alloc(l, m);
+            self.nodes[t as usize].left = c;
+        }
+        if self.nodes[t as usize].right == NIL {
+            let c = self.alloc(m, r);
+            self.nodes[t as usize].right = c;
+        }
+        let node = &mut self.nodes[t as usize];
+        let f = std::mem::replace(&mut node.lazy, F::id());
+        let (left, right) = (node.left, node.right);
+        self.nodes[left as usize].all_apply(&f);
+        self.nodes[right as usize].all_apply(&f);
+    }
+
+    fn point_update<G>(&mut self, i: u64, g: G)
+    where
+        G: FnOnce(&S) -> S,
+    {
+        let root = self.root();
+        self.point_rec(root, 0, self.n, i, g);
+    }
+
+    fn point_rec<G>(&mut self, t: u32, l: u64, r: u64, i: u64, g: G)
+    where
+        G: FnOnce(&S) -> S,
+    {
+        if r - l == 1 {
+            let node = &mut self.nodes[t as usize];
+            node.val = g(&node.val);
+            return;
+        }
+        self.push(t, l, r);
+        let m = l + ((r - l) >> 1);
+        let node = &self.nodes[t as usize];
+        if i < m {
+            self.point_rec(node.left, l, m, i, g);
+        } else {
+            self.point_rec(node.right, m, r, i, g);
+        }
+        self.update(t);
+    }
+
+    fn apply_rec(&mut self, t: u32, l: u64, r: u64, ql: u64, qr: u64, f: &F) {
+        if ql <= l && r <= qr {
+            self.nodes[t as usize].all_apply(f);
+            return;
+        }
+        self.push(t, l, r);
+        let m = l + ((r - l) >> 1);
+        let Node { left, right, .. } = self.nodes[t as usize];
+        if ql < m {
+            self.apply_rec(left, l, m, ql, qr, f);
+        }
+        if m < qr {
+            self.apply_rec(right, m, r, ql, qr, f);
+        }
+        self.update(t);
+    }
+
+    fn fold_rec(&self, t: u32, l: u64, r: u64, ql: u64, qr: u64) -> S {
+        if t == NIL {
+            return (self.init)(ql.max(l), qr.min(r));
+        }
+        let node = &self.nodes[t as usize];
+        if ql <= l && r <= qr {
+            return node.val.clone();
+        }
+        let m = l + ((r - l) >> 1);
+        let res = if qr <= m {
+            self.fold_rec(node.left, l, m, ql, qr)
+        } else if m <= ql {
+            self.fold_rec(node.right, m, r, ql, qr)
+        } else {
+            S::op(
+                &self.fold_rec(node.left, l, m, ql, qr),
+                &self.fold_rec(node.right, m, r, ql, qr),
+            )
+        };
+        node.lazy.act(&res)
+    }
+
+    /// Recomputes node `t`, whose children both exist after a push.
+    #[inline(always)]
+    fn update(&mut self, t: u32) {
+        let Node { left, right, .. } = self.nodes[t as usize];
+        let val = S::op(
+            &self.nodes[left as usize].val,
+            &self.nodes[right as usize].val,
+        );
+        self.nodes[t as usize].val = val;
+    }
+}
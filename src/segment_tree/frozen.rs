@@ -0,0 +1,156 @@
+use crate::segment_tree::Monoid;
+
+/// A read-only segment tree, returned by `LazySegmentTree::freeze`.
+///
+/// Given a monoid `(S, op, id)`, this data structure supports:
+/// - Point query: `get(i)` returns `a[i]`
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+///
+/// Every pending action has been propagated, so all queries take `&self` and perform no
+/// interior mutation. The tree is `Send + Sync` whenever `S` is, and can be shared across
+/// threads for read-heavy phases.
+#[derive(Clone, Debug)]
+pub struct FrozenSegmentTree<S: Monoid> {
+    /// Binary heap-like array storing the tree nodes.
+    /// Index 1 is the root, index `size + i` is the leaf for element `i`.
+    data: Box<[S]>,
+    /// Number of elements in the original array.
+    n: usize,
+}
+
+impl<S: Monoid> FrozenSegmentTree<S> {
+    /// Creates a frozen tree from the nodes of a tree with power-of-two leaf count and no
+    /// pending actions.
+    pub(crate) fn from_nodes(data: Box<[S]>, n: usize) -> Self {
+        Self { data, n }
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        unsafe { self.data.get_unchecked(self.size() + i).clone() }
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let mut l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        } + self.size();
+        let mut r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        } + self.size();
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l - self.size(),
+            r - self.size(),
+        );
+        debug_assert!(
+            r <= self.len() + self.size(),
+            "index out of bounds: r={}, len={}",
+            r - self.size(),
+            self.len(),
+        );
+        let mut left = S::id();
+        let mut right = S::id();
+        unsafe {
+            while l < r {
+                if l & 1 == 1 {
+                    left = S::op(&left, self.data.get_unchecked(l));
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right = S::op(self.data.get_unchecked(r), &right);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        S::op(&left, &right)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        unsafe { self.data.get_unchecked(1).clone() }
+    }
+
+    /// Returns an iterator over the elements, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) to create, O(n) to consume.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, S> {
+        self.data[self.size()..self.size() + self.len()].iter()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn to_vec(&self) -> Vec<S> {
+        self.iter().cloned().collect()
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.data.len() >> 1
+    }
+}
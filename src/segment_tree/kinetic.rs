@@ -0,0 +1,310 @@
+/// A line `a * t + b`, stored as `(a, b)`.
+type Line = (i64, i64);
+
+/// The line stored in padding leaves, never smaller than a real line.
+const NONE: Line = (0, i64::MAX);
+
+/// A kinetic segment tree over linear functions of a common time `t`.
+///
+/// Given a sequence of lines `a[i] * t + b[i]`, this data structure supports:
+/// - Point update: `set(i, (a, b))` replaces the line at index `i`
+/// - Time advance: `advance(t)` moves the current time forward to `t`
+/// - Range query: `range_min(l..r)` returns the minimum value of the lines in range at the
+///   current time
+///
+/// Every node keeps the line that is minimal at the current time, together with the
+/// earliest future time at which this certificate may fail anywhere in its subtree. On
+/// `advance`, only nodes whose certificates failed are recomputed.
+///
+/// Queries and updates run in O(log n) time, and `advance` runs in O(log² n) amortized
+/// time per update. For maximum queries, negate both coefficients.
+///
+/// All values `a * t + b` must fit in `i64`.
+#[derive(Clone, Debug)]
+pub struct KineticSegmentTree {
+    /// Binary heap-like array storing the minimal line of each node at the current time.
+    /// Index 1 is the root, index `size + i` is the leaf for element `i`.
+    lines: Box<[Line]>,
+    /// `melt[i]` is the earliest time at which the minimal line of some node in the
+    /// subtree of `i` may change.
+    melt: Box<[i64]>,
+    /// Number of elements in the original array.
+    n: usize,
+    /// The current time.
+    t: i64,
+}
+
+impl KineticSegmentTree {
+    /// Creates a new kinetic segment tree from a vec of lines `(a, b)`, at time `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_vec(v: Vec<(i64, i64)>) -> Self {
+        Self::from_slice(&v)
+    }
+
+    /// Creates a new kinetic segment tree from a slice of lines `(a, b)`, at time `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(v: &[(i64, i64)]) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let mut lines = vec![NONE; size << 1];
+        lines[size..size + n].copy_from_slice(v);
+        let mut tree = Self {
+            lines: lines.into_boxed_slice(),
+            melt: vec![i64::MAX; size << 1].into_boxed_slice(),
+            n,
+            t: 0,
+        };
+        for i in (1..size).rev() {
+            tree.update(i);
+        }
+        tree
+    }
+
+    /// Creates a new kinetic segment tree with `n` lines, where the line at index `i` is
+    /// `f(i)`, at time `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> (i64, i64),
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+
+    /// Sets the line at index `i` to `a * t + b`, given as `(a, b)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn set(&mut self, i: usize, line: (i64, i64)) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let mut i = i + self.size();
+        self.lines[i] = line;
+        while i > 1 {
+            i >>= 1;
+            self.update(i);
+        }
+    }
+
+    /// Moves the current time forward to `t`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log² n) amortized per update
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t` is less than the current time in debug builds.
+    pub fn advance(&mut self, t: i64) {
+        debug_assert!(
+            self.t <= t,
+            "time must not go backwards: current={}, t={}",
+            self.t,
+            t,
+        );
+        self.t = t;
+        self.heaten(1);
+    }
+
+    /// Returns the line at index `i` as `(a, b)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn line(&self, i: usize) -> (i64, i64) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.lines[self.size() + i]
+    }
+
+    /// Returns the value of the line at index `i` at the current time.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> i64 {
+        self.eval(self.line(i))
+    }
+
+    /// Returns the minimum value at the current time of the lines in the given range.
+    ///
+    /// Returns `i64::MAX` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_min(&self, range: impl std::ops::RangeBounds<usize>) -> i64 {
+        let mut l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        } + self.size();
+        let mut r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        } + self.size();
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l - self.size(),
+            r - self.size(),
+        );
+        debug_assert!(
+            r <= self.len() + self.size(),
+            "index out of bounds: r={}, len={}",
+            r - self.size(),
+            self.len(),
+        );
+        let mut res = i64::MAX;
+        while l < r {
+            if l & 1 == 1 {
+                res = res.min(self.eval(self.lines[l]));
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                res = res.min(self.eval(self.lines[r]));
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        res
+    }
+
+    /// Returns the minimum value of all lines at the current time.
+    ///
+    /// Returns `i64::MAX` if the tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_min(&self) -> i64 {
+        self.eval(self.lines[1])
+    }
+
+    /// Returns the current time.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn time(&self) -> i64 {
+        self.t
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the kinetic segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.lines.len() >> 1
+    }
+
+    #[inline(always)]
+    fn eval(&self, (a, b): Line) -> i64 {
+        if (a, b) == NONE {
+            return i64::MAX;
+        }
+        a * self.t + b
+    }
+
+    /// Recomputes every node under `i` whose certificate has failed at the current time.
+    fn heaten(&mut self, i: usize) {
+        if i >= self.size() || self.melt[i] > self.t {
+            return;
+        }
+        self.heaten(i << 1);
+        self.heaten((i << 1) + 1);
+        self.update(i);
+    }
+
+    /// Recomputes the minimal line of node `i` and the time its certificate fails.
+    #[inline]
+    fn update(&mut self, i: usize) {
+        let (x, y) = (self.lines[i << 1], self.lines[(i << 1) + 1]);
+        let (vx, vy) = (self.eval(x) as i128, self.eval(y) as i128);
+        // On ties, keep the line with the smaller slope, which stays minimal for longer.
+        let (min, other) = if vx < vy || (vx == vy && x.0 <= y.0) {
+            (x, y)
+        } else {
+            (y, x)
+        };
+        let mut melt = self.melt[i << 1].min(self.melt[(i << 1) + 1]);
+        if min.0 > other.0 && other != NONE {
+            // `other` becomes minimal at the first time `s` with `other(s) <= min(s)`.
+            let (da, db) = (
+                min.0 as i128 - other.0 as i128,
+                other.1 as i128 - min.1 as i128,
+            );
+            let s = db.div_euclid(da) + (db.rem_euclid(da) != 0) as i128;
+            melt = melt.min(s.min(i64::MAX as i128) as i64);
+        }
+        self.lines[i] = min;
+        self.melt[i] = melt;
+    }
+}
+
+impl FromIterator<(i64, i64)> for KineticSegmentTree {
+    /// Creates a new kinetic segment tree from an iterator of lines `(a, b)`, at time `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = (i64, i64)>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
@@ -1,3 +1,7 @@
+use crate::monoids::{Bidirectional, Reversed};
+use crate::segment_tree::codec::{Codec, decode_seq};
+use crate::segment_tree::frozen::FrozenSegmentTree;
+use crate::segment_tree::levels::Levels;
 use crate::segment_tree::{Action, Monoid};
 
 /// A lazy segment tree for efficient range updates and range queries.
@@ -10,6 +14,15 @@ use crate::segment_tree::{Action, Monoid};
 /// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
 ///
 /// All operations run in O(log n) time.
+///
+/// Queries taking `&self` (`get`, `range_fold`, `all_fold`) apply pending actions on the
+/// fly and never modify the tree, so a shared tree can be queried from several threads at
+/// once. Pending actions are only propagated by methods taking `&mut self`.
+///
+/// Each internal node records whether it holds a pending action, so nodes untouched by range
+/// actions are neither pushed nor acted on by queries. Sparse range updates thus cost no
+/// `act` or `op` calls away from the updated ranges.
+#[derive(Clone)]
 #[repr(C)]
 pub struct LazySegmentTree<S: Monoid, F: Action<S>> {
     /// Binary heap-like array storing the tree nodes.
@@ -17,10 +30,24 @@ pub struct LazySegmentTree<S: Monoid, F: Action<S>> {
     data: Box<[S]>,
     /// Lazy heap-like array storing the tree nodes.
     lazy: Box<[F]>,
+    /// Whether each internal node holds an action other than `F::id()`, so that pushing and
+    /// querying can skip identity actions.
+    pending: Box<[bool]>,
     /// Number of elements in the original array.
     n: usize,
     /// log2(size), used for iteration bounds.
     log: usize,
+    /// Previous contents of the nodes written since the first snapshot, oldest first.
+    journal: Vec<Undo<S, F>>,
+    /// Whether writes are recorded in the journal.
+    recording: bool,
+}
+
+/// A journal entry, holding the contents of a node before a write.
+#[derive(Clone)]
+enum Undo<S, F> {
+    Data(usize, S),
+    Lazy(usize, F, bool),
 }
 
 impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
@@ -34,8 +61,11 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         Self {
             data: vec![S::id(); size << 1].into_boxed_slice(),
             lazy: vec![F::id(); size].into_boxed_slice(),
+            pending: vec![false; size].into_boxed_slice(),
             n,
             log: size.trailing_zeros() as usize,
+            journal: Vec::new(),
+            recording: false,
         }
     }
 
@@ -60,8 +90,11 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         Self {
             data: v.into_boxed_slice(),
             lazy: vec![F::id(); size].into_boxed_slice(),
+            pending: vec![false; size].into_boxed_slice(),
             n,
             log: size.trailing_zeros() as usize,
+            journal: Vec::new(),
+            recording: false,
         }
     }
 
@@ -74,9 +107,9 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         let n = v.len();
         let size = n.next_power_of_two();
         let mut data = vec![S::id(); size << 1];
+        data[size..size + n].clone_from_slice(v);
         unsafe {
             let d = data.as_mut_ptr();
-            std::ptr::copy_nonoverlapping(v.as_ptr(), d.add(size), n);
             for i in (1..size).rev() {
                 *d.add(i) = S::op(&*d.add(2 * i), &*d.add(2 * i + 1));
             }
@@ -84,11 +117,29 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         Self {
             data: data.into_boxed_slice(),
             lazy: vec![F::id(); size].into_boxed_slice(),
+            pending: vec![false; size].into_boxed_slice(),
             n,
             log: size.trailing_zeros() as usize,
+            journal: Vec::new(),
+            recording: false,
         }
     }
 
+    /// Creates a new lazy segment tree with `n` elements, where the element at index `i` is
+    /// `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<G>(n: usize, f: G) -> Self
+    where
+        G: FnMut(usize) -> S,
+    {
+        let mut v = Vec::with_capacity(n.next_power_of_two() << 1);
+        v.extend((0..n).map(f));
+        Self::from_vec(v)
+    }
+
     /// Sets the value at index `i` to `x`.
     ///
     /// # Time complexity
@@ -106,6 +157,7 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         for t in (1..=self.log).rev() {
             self.push(i >> t);
         }
+        self.record_data(i);
         unsafe {
             *self.data.get_unchecked_mut(i) = x;
         }
@@ -115,6 +167,30 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         }
     }
 
+    /// Returns a guard giving mutable access to the element at index `i`.
+    ///
+    /// Pending actions above the element are propagated first, and its ancestors are
+    /// recomputed when the guard is dropped.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) to create, O(log n) to drop.
+    #[inline]
+    pub fn entry(&mut self, i: usize) -> LazyEntry<'_, S, F> {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let i = i + self.size();
+        for t in (1..=self.log).rev() {
+            self.push(i >> t);
+        }
+        self.record_data(i);
+        LazyEntry { tree: self, i }
+    }
+
     /// Applies `S::op(a[i], x)` to the element at index `i`.
     ///
     /// # Time complexity
@@ -132,6 +208,7 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         for t in (1..=self.log).rev() {
             self.push(i >> t);
         }
+        self.record_data(i);
         unsafe {
             *self.data.get_unchecked_mut(i) = S::op(self.data.get_unchecked(i), &x);
         }
@@ -158,6 +235,7 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         for t in (1..=self.log).rev() {
             self.push(i >> t);
         }
+        self.record_data(i);
         unsafe {
             *self.data.get_unchecked_mut(i) = f.act(self.data.get_unchecked(i));
         }
@@ -211,28 +289,18 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
 
         {
             let (mut l, mut r) = (l, r);
-            unsafe {
-                let data = self.data.as_mut_ptr();
-                let lazy = self.lazy.as_mut_ptr();
-                loop {
-                    if l >= r {
-                        *data.add(l) = f.act(&*data.add(l));
-                        if l < self.size() {
-                            *lazy.add(l) = F::op(&f, &*lazy.add(l));
-                        }
-                        l += 1;
-                        l >>= l.trailing_zeros();
-                    } else {
-                        r -= 1;
-                        *data.add(r) = f.act(&*data.add(r));
-                        if r < self.size() {
-                            *lazy.add(r) = F::op(&f, &*lazy.add(r));
-                        }
-                        r >>= r.trailing_zeros();
-                    }
-                    if l == r {
-                        break;
-                    }
+            loop {
+                if l >= r {
+                    self.all_apply(l, &f);
+                    l += 1;
+                    l >>= l.trailing_zeros();
+                } else {
+                    r -= 1;
+                    self.all_apply(r, &f);
+                    r >>= r.trailing_zeros();
+                }
+                if l == r {
+                    break;
                 }
             }
         }
@@ -250,24 +318,28 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
 
     /// Returns the value at index `i`.
     ///
+    /// Pending actions on the ancestors are applied on the fly, so the tree is not modified.
+    ///
     /// # Time complexity
     ///
     /// O(log n)
     #[inline]
-    pub fn get(&mut self, mut i: usize) -> S {
+    pub fn get(&self, i: usize) -> S {
         debug_assert!(
             i < self.len(),
             "index out of bounds: i={}, len={}",
             i,
             self.len(),
         );
-        i += self.size();
-        for t in (1..=self.log).rev() {
-            if (i >> t) << t != i {
-                self.push(i >> t);
+        let i = i + self.size();
+        let mut x = unsafe { self.data.get_unchecked(i).clone() };
+        // Actions on deeper nodes are more recent, so they are applied first.
+        for t in 1..=self.log {
+            if unsafe { *self.pending.get_unchecked(i >> t) } {
+                x = unsafe { self.lazy.get_unchecked(i >> t) }.act(&x);
             }
         }
-        unsafe { self.data.get_unchecked(i).clone() }
+        x
     }
 
     /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
@@ -311,25 +383,30 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         let mut right = S::id();
 
         unsafe {
-            let data = self.data.as_ptr();
-            let lazy = self.lazy.as_ptr();
+            let data = &self.data;
+            let lazy = &self.lazy;
+            let pending = &self.pending;
             loop {
                 if l >= r {
                     let mut i = l >> 1;
-                    left = S::op(&left, &*data.add(l));
+                    left = S::op(&left, data.get_unchecked(l));
                     l += 1;
                     l >>= l.trailing_zeros();
                     while i > l >> 1 {
-                        left = (*lazy.add(i)).act(&left);
+                        if *pending.get_unchecked(i) {
+                            left = lazy.get_unchecked(i).act(&left);
+                        }
                         i >>= 1;
                     }
                 } else {
                     let mut i = r >> 1;
                     r -= 1;
-                    right = S::op(&*data.add(r), &right);
+                    right = S::op(data.get_unchecked(r), &right);
                     r >>= r.trailing_zeros();
                     while i > r >> 1 {
-                        right = (*lazy.add(i)).act(&right);
+                        if *pending.get_unchecked(i) {
+                            right = lazy.get_unchecked(i).act(&right);
+                        }
                         i >>= 1;
                     }
                 }
@@ -341,9 +418,12 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         let mut res = S::op(&left, &right);
         let mut i = l >> 1;
         unsafe {
-            let lazy = self.lazy.as_ptr();
+            let lazy = &self.lazy;
+            let pending = &self.pending;
             while i > 0 {
-                res = (*lazy.add(i)).act(&res);
+                if *pending.get_unchecked(i) {
+                    res = lazy.get_unchecked(i).act(&res);
+                }
                 i >>= 1;
             }
         }
@@ -360,6 +440,296 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         unsafe { self.data.get_unchecked(1).clone() }
     }
 
+    /// Returns an iterator over the elements, in order.
+    ///
+    /// All pending actions are propagated to the leaves first.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&mut self) -> std::slice::Iter<'_, S> {
+        self.push_all();
+        self.data[self.size()..self.size() + self.len()].iter()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// All pending actions are propagated to the leaves first.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn to_vec(&mut self) -> Vec<S> {
+        self.iter().cloned().collect()
+    }
+
+    /// Returns the smallest `i` in the given range such that `p(op(a[l], ..., a[i]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[l], ..., a[i])`, it also holds
+    /// for every longer prefix starting at `l`. For example, the first zero in a range is
+    /// found with a minimum monoid and `p = |x| x == 0`.
+    ///
+    /// Pending actions on the visited nodes are propagated.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_first<P>(&mut self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let size = self.size();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        for t in (1..=self.log).rev() {
+            if (l >> t) << t != l {
+                self.push(l >> t);
+            }
+            if (r >> t) << t != r {
+                self.push((r - 1) >> t);
+            }
+        }
+        // Nodes covering the range, from left to right.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut right = [0; usize::BITS as usize];
+        let (mut len, mut right_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    nodes[len] = l;
+                    len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    right[right_len] = r;
+                    right_len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in right[..right_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = S::id();
+        for &v in &nodes[..len] {
+            let t = S::op(&acc, &self.data[v]);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                self.push(v);
+                v <<= 1;
+                let t = S::op(&acc, &self.data[v]);
+                if !p(&t) {
+                    acc = t;
+                    v += 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
+    }
+
+    /// Returns the largest `i` in the given range such that `p(op(a[i], ..., a[r - 1]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[i], ..., a[r - 1])`, it also
+    /// holds for every longer suffix ending at `r`.
+    ///
+    /// Pending actions on the visited nodes are propagated.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_last<P>(&mut self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let size = self.size();
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let (l, r) = (l + size, r + size);
+        for t in (1..=self.log).rev() {
+            if (l >> t) << t != l {
+                self.push(l >> t);
+            }
+            if (r >> t) << t != r {
+                self.push((r - 1) >> t);
+            }
+        }
+        // Nodes covering the range, from right to left.
+        let mut nodes = [0; usize::BITS as usize * 2];
+        let mut left = [0; usize::BITS as usize];
+        let (mut len, mut left_len) = (0, 0);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    left[left_len] = l;
+                    left_len += 1;
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    nodes[len] = r;
+                    len += 1;
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for &v in left[..left_len].iter().rev() {
+            nodes[len] = v;
+            len += 1;
+        }
+        let mut acc = S::id();
+        for &v in &nodes[..len] {
+            let t = S::op(&self.data[v], &acc);
+            if !p(&t) {
+                acc = t;
+                continue;
+            }
+            let mut v = v;
+            while v < size {
+                self.push(v);
+                v = (v << 1) + 1;
+                let t = S::op(&self.data[v], &acc);
+                if !p(&t) {
+                    acc = t;
+                    v -= 1;
+                }
+            }
+            return Some(v - size);
+        }
+        None
+    }
+
+    /// Returns a checkpoint of the current contents, to be restored by
+    /// [`rollback_to`](Self::rollback_to).
+    ///
+    /// From the first snapshot on, every write to the tree is recorded in an undo journal
+    /// until [`commit`](Self::commit) is called, so that updates can be applied tentatively,
+    /// e.g. in backtracking searches.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn snapshot(&mut self) -> usize {
+        self.recording = true;
+        self.journal.len()
+    }
+
+    /// Restores the contents at the time `snapshot` returned `id`.
+    ///
+    /// Snapshots taken after `id` are invalidated; `id` itself remains valid.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k), where `k` is the number of nodes written since the snapshot, which is O(log n)
+    /// per update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not a valid snapshot in debug builds.
+    pub fn rollback_to(&mut self, id: usize) {
+        debug_assert!(
+            self.recording && id <= self.journal.len(),
+            "invalid snapshot: id={}, journal={}",
+            id,
+            self.journal.len(),
+        );
+        while self.journal.len() > id {
+            match self.journal.pop() {
+                Some(Undo::Data(i, x)) => self.data[i] = x,
+                Some(Undo::Lazy(i, f, p)) => {
+                    self.lazy[i] = f;
+                    self.pending[i] = p;
+                }
+                None => unreachable!(),
+            }
+        }
+    }
+
+    /// Discards every snapshot and stops recording writes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(k), where `k` is the number of recorded writes.
+    #[inline]
+    pub fn commit(&mut self) {
+        self.journal.clear();
+        self.recording = false;
+    }
+
+    /// Propagates every pending action and returns a read-only tree over the elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn freeze(mut self) -> FrozenSegmentTree<S> {
+        self.push_all();
+        FrozenSegmentTree::from_nodes(self.data, self.n)
+    }
+
     #[inline]
     pub fn max_right<P>(&self, _l: usize, _p: P) -> usize
     where
@@ -378,6 +748,21 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
 
     #[inline(always)]
     fn push(&mut self, i: usize) {
+        if !unsafe { *self.pending.get_unchecked(i) } {
+            return;
+        }
+        if self.recording {
+            self.record_lazy(i);
+            self.record_data(i << 1);
+            self.record_data((i << 1) + 1);
+            if i << 1 < self.size() {
+                self.record_lazy(i << 1);
+                self.record_lazy((i << 1) + 1);
+            }
+        }
+        unsafe {
+            *self.pending.get_unchecked_mut(i) = false;
+        }
         let data = self.data.as_mut_ptr();
         let lazy = self.lazy.as_mut_ptr();
         unsafe {
@@ -387,23 +772,88 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
             if i << 1 < self.size() {
                 *lazy.add(i << 1) = F::op(&f, &*lazy.add(i << 1));
                 *lazy.add((i << 1) + 1) = F::op(&f, &*lazy.add((i << 1) + 1));
+                *self.pending.get_unchecked_mut(i << 1) = true;
+                *self.pending.get_unchecked_mut((i << 1) + 1) = true;
             }
         }
     }
 
+    /// Propagates every pending action down to the leaves.
+    fn push_all(&mut self) {
+        for i in 1..self.size() {
+            self.push(i);
+        }
+    }
+
     #[inline(always)]
     fn update(&mut self, i: usize) {
+        self.record_data(i);
         let data = self.data.as_mut_ptr();
         unsafe {
             *data.add(i) = S::op(&*data.add(i << 1), &*data.add((i << 1) + 1));
         }
     }
 
+    /// Applies `f` to node `i`, deferring it for the children of an internal node.
+    #[inline(always)]
+    fn all_apply(&mut self, i: usize, f: &F) {
+        self.record_data(i);
+        unsafe {
+            *self.data.get_unchecked_mut(i) = f.act(self.data.get_unchecked(i));
+        }
+        if i < self.size() {
+            self.record_lazy(i);
+            unsafe {
+                *self.lazy.get_unchecked_mut(i) = F::op(f, self.lazy.get_unchecked(i));
+                *self.pending.get_unchecked_mut(i) = true;
+            }
+        }
+    }
+
+    /// Records the contents of node `i` before a write, if a snapshot is active.
+    #[inline(always)]
+    fn record_data(&mut self, i: usize) {
+        if self.recording {
+            self.journal.push(Undo::Data(i, self.data[i].clone()));
+        }
+    }
+
+    /// Records the action of internal node `i` before a write, if a snapshot is active.
+    #[inline(always)]
+    fn record_lazy(&mut self, i: usize) {
+        if self.recording {
+            let undo = Undo::Lazy(i, self.lazy[i].clone(), self.pending[i]);
+            self.journal.push(undo);
+        }
+    }
+
     #[inline(always)]
     fn size(&self) -> usize {
         self.lazy.len()
     }
 
+    /// Returns every node with the pending actions of its ancestors applied, so that each
+    /// holds the fold of its range.
+    fn resolved(&self) -> Vec<S> {
+        let size = self.size();
+        // `acc[k]` is the composition of the pending actions of the ancestors of node `k`.
+        let mut acc = vec![F::id(); size];
+        let mut nodes = self.data.to_vec();
+        for k in 2..size << 1 {
+            let p = k >> 1;
+            let f = if self.pending[p] {
+                F::op(&acc[p], &self.lazy[p])
+            } else {
+                acc[p].clone()
+            };
+            nodes[k] = f.act(&nodes[k]);
+            if k < size {
+                acc[k] = f;
+            }
+        }
+        nodes
+    }
+
     /// Returns the number of elements.
     ///
     /// # Time complexity
@@ -424,3 +874,339 @@ impl<S: Monoid, F: Action<S>> LazySegmentTree<S, F> {
         self.len() == 0
     }
 }
+
+/// A guard giving mutable access to an element of a [`LazySegmentTree`], returned by
+/// [`LazySegmentTree::entry`].
+///
+/// The ancestors of the element are recomputed when the guard is dropped.
+pub struct LazyEntry<'a, S: Monoid, F: Action<S>> {
+    tree: &'a mut LazySegmentTree<S, F>,
+    /// Index of the leaf in the tree.
+    i: usize,
+}
+
+impl<S: Monoid, F: Action<S>> std::ops::Deref for LazyEntry<'_, S, F> {
+    type Target = S;
+
+    #[inline(always)]
+    fn deref(&self) -> &S {
+        unsafe { self.tree.data.get_unchecked(self.i) }
+    }
+}
+
+impl<S: Monoid, F: Action<S>> std::ops::DerefMut for LazyEntry<'_, S, F> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut S {
+        unsafe { self.tree.data.get_unchecked_mut(self.i) }
+    }
+}
+
+impl<S: Monoid, F: Action<S>> Drop for LazyEntry<'_, S, F> {
+    #[inline]
+    fn drop(&mut self) {
+        let mut i = self.i;
+        while i > 1 {
+            i >>= 1;
+            self.tree.update(i);
+        }
+    }
+}
+
+impl<S: Monoid, F: Action<S>> FromIterator<S> for LazySegmentTree<S, F> {
+    /// Creates a new lazy segment tree from an iterator.
+    ///
+    /// If the iterator reports an exact size, the tree is built in place without
+    /// reallocation.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut v = match iter.size_hint() {
+            (lo, Some(hi)) if lo == hi => Vec::with_capacity(lo.next_power_of_two() << 1),
+            _ => Vec::new(),
+        };
+        v.extend(iter);
+        Self::from_vec(v)
+    }
+}
+
+impl<S: Monoid + PartialEq, F: Action<S>> PartialEq for LazySegmentTree<S, F> {
+    /// Returns `true` if both trees hold the same elements, once all pending actions are
+    /// applied.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn eq(&self, other: &Self) -> bool {
+        if self.n != other.n {
+            return false;
+        }
+        let (a, b) = (self.resolved(), other.resolved());
+        a[self.size()..self.size() + self.n] == b[other.size()..other.size() + other.n]
+    }
+}
+
+impl<S: Monoid + Eq, F: Action<S>> Eq for LazySegmentTree<S, F> {}
+
+impl<S: Monoid, F: Action<S>> Default for LazySegmentTree<S, F> {
+    /// Creates an empty lazy segment tree.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<S: Monoid + std::fmt::Debug, F: Action<S>> std::fmt::Debug for LazySegmentTree<S, F> {
+    /// Formats the length and the nodes, grouped by depth from the root down.
+    ///
+    /// Pending actions are applied to the nodes below them for display, without modifying
+    /// the tree.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazySegmentTree")
+            .field("len", &self.n)
+            .field("levels", &Levels(&self.resolved()))
+            .finish()
+    }
+}
+
+impl<S: Monoid + std::fmt::Debug, F: Action<S>> std::fmt::Display for LazySegmentTree<S, F> {
+    /// Writes the nodes one level per line, from the root down.
+    ///
+    /// Pending actions are applied to the nodes below them for display, without modifying
+    /// the tree.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&Levels(&self.resolved()), f)
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<S: Monoid + Send + Sync, F: Action<S>> LazySegmentTree<S, F> {
+    /// Creates a new lazy segment tree from a slice, using all available threads.
+    ///
+    /// The leaves are copied and every level of internal nodes is computed in parallel,
+    /// from the bottom up. Levels with few nodes are computed on the calling thread.
+    ///
+    /// Enabled by the `parallel` feature. It runs on [`std::thread::scope`] rather than
+    /// rayon, so that the crate keeps no dependencies, hence the feature name.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n / p + p log n), where `p` is the number of threads.
+    pub fn par_from_slice(v: &[S]) -> Self {
+        /// Number of nodes below which a level is not worth splitting across threads.
+        const SEQUENTIAL: usize = 1 << 14;
+
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let threads = std::thread::available_parallelism().map_or(1, |p| p.get());
+        let mut data = vec![S::id(); size << 1];
+        let chunk = n.div_ceil(threads).max(SEQUENTIAL);
+        std::thread::scope(|scope| {
+            for (dst, src) in data[size..size + n].chunks_mut(chunk).zip(v.chunks(chunk)) {
+                scope.spawn(move || dst.clone_from_slice(src));
+            }
+        });
+        let mut w = size >> 1;
+        while w > SEQUENTIAL {
+            // Nodes `w..2w` are the parents of nodes `2w..4w`.
+            let (upper, lower) = data.split_at_mut(w << 1);
+            let (parents, children) = (&mut upper[w..], &lower[..w << 1]);
+            let chunk = w.div_ceil(threads).max(SEQUENTIAL);
+            std::thread::scope(|scope| {
+                for (parents, children) in
+                    parents.chunks_mut(chunk).zip(children.chunks(chunk << 1))
+                {
+                    scope.spawn(move || {
+                        for (p, c) in parents.iter_mut().zip(children.chunks_exact(2)) {
+                            *p = S::op(&c[0], &c[1]);
+                        }
+                    });
+                }
+            });
+            w >>= 1;
+        }
+        for i in (1..w << 1).rev() {
+            data[i] = S::op(&data[i << 1], &data[(i << 1) + 1]);
+        }
+        Self {
+            data: data.into_boxed_slice(),
+            lazy: vec![F::id(); size].into_boxed_slice(),
+            pending: vec![false; size].into_boxed_slice(),
+            n,
+            log: size.trailing_zeros() as usize,
+            journal: Vec::new(),
+            recording: false,
+        }
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range, using all available
+    /// threads.
+    ///
+    /// The range is split into one piece per thread, the pieces are folded concurrently
+    /// with [`range_fold`](Self::range_fold), and the partial folds are combined in order.
+    /// Pending actions above the piece boundaries are applied on the fly by each fold, as
+    /// in `range_fold`, so the tree is not modified. Each piece costs O(log n) `op` and
+    /// `act` calls, so this only pays off over `range_fold` when those calls are expensive
+    /// enough to outweigh spawning the threads.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n + p), where `p` is the number of threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn par_range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S
+    where
+        F: Sync,
+    {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        let threads = std::thread::available_parallelism().map_or(1, |p| p.get());
+        let chunk = (r - l).div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let folds: Vec<_> = (l..r)
+                .step_by(chunk)
+                .map(|a| scope.spawn(move || self.range_fold(a..r.min(a + chunk))))
+                .collect();
+            folds.into_iter().fold(S::id(), |acc, fold| {
+                S::op(&acc, &fold.join().expect("fold thread panicked"))
+            })
+        })
+    }
+}
+
+impl<S: Monoid, F: Action<S>> LazySegmentTree<(S, Reversed<S>), Bidirectional<F>> {
+    /// Returns `op(a[r-1], a[r-2], ..., a[l])` for the given range.
+    ///
+    /// Each element `x` is stored as `(x, Reversed(x))`, and actions `f` are applied as
+    /// `Bidirectional(f)`, so that every node holds its fold in both directions. Returns
+    /// `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold_rev(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        self.range_fold(range).1.0
+    }
+}
+
+impl<S: Monoid + Codec, F: Action<S>> LazySegmentTree<S, F> {
+    /// Encodes the elements into a compact binary form.
+    ///
+    /// Only the elements are stored, with every pending action applied; internal nodes are rebuilt by
+    /// [`from_bytes`](Self::from_bytes).
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn to_bytes(&mut self) -> Vec<u8> {
+        let mut out = vec![];
+        self.len().encode(&mut out);
+        for x in self.iter() {
+            x.encode(&mut out);
+        }
+        out
+    }
+
+    /// Decodes a lazy segment tree from the output of [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns `None` if `bytes` is not a valid encoding.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let v = decode_seq(&mut bytes)?;
+        bytes.is_empty().then(|| Self::from_vec(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment_tree::actions::{AssignAdd, Sum};
+    use crate::segment_tree::core::tests::Rng;
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for n in [0, 1, 5, 64, 100] {
+            let a: Vec<_> = (0..n).map(|_| Sum::new(rng.below(100) as i64)).collect();
+            let mut t = LazySegmentTree::<Sum<i64>, AssignAdd<i64>>::from_vec(a);
+            for _ in 0..n {
+                let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                let c = rng.below(100) as i64;
+                let f = if rng.below(2) == 0 {
+                    AssignAdd::assign(c)
+                } else {
+                    AssignAdd::add(c)
+                };
+                t.range_apply(x.min(y)..x.max(y), f);
+            }
+            // Pending actions are applied before encoding.
+            let bytes = t.to_bytes();
+            let mut u = LazySegmentTree::<Sum<i64>, AssignAdd<i64>>::from_bytes(&bytes).unwrap();
+            assert_eq!(u.to_vec(), t.to_vec());
+            for l in 0..=n {
+                assert_eq!(u.range_fold(l..), t.range_fold(l..));
+            }
+            let mut longer = bytes.clone();
+            longer.push(0);
+            assert!(LazySegmentTree::<Sum<i64>, AssignAdd<i64>>::from_bytes(&longer).is_none());
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for n in [0, 1, 5, 64, 100, 1 << 15] {
+            let a: Vec<_> = (0..n).map(|_| Sum::new(rng.below(100) as i64)).collect();
+            let mut t = LazySegmentTree::<Sum<i64>, AssignAdd<i64>>::par_from_slice(&a);
+            assert_eq!(t, LazySegmentTree::from_slice(&a));
+            for _ in 0..20 {
+                let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                let c = rng.below(100) as i64;
+                let f = if rng.below(2) == 0 {
+                    AssignAdd::assign(c)
+                } else {
+                    AssignAdd::add(c)
+                };
+                t.range_apply(x.min(y)..x.max(y), f);
+                let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                let (l, r) = (x.min(y), x.max(y));
+                assert_eq!(t.par_range_fold(l..r), t.range_fold(l..r), "{l}..{r}");
+            }
+            assert_eq!(t.par_range_fold(..), t.all_fold());
+        }
+    }
+}
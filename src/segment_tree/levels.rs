@@ -0,0 +1,38 @@
+//! Level-by-level formatting of heap-ordered segment tree nodes.
+
+use std::fmt;
+
+/// The nodes `1..nodes.len()` of a heap-ordered tree, grouped by depth.
+///
+/// When the number of leaves is not a power of two, the leaves span the last two levels.
+pub(crate) struct Levels<'a, S>(pub(crate) &'a [S]);
+
+impl<'a, S> Levels<'a, S> {
+    /// Returns the nodes of every level, from the root down.
+    fn iter(&self) -> impl Iterator<Item = &'a [S]> {
+        let nodes = self.0;
+        (0..usize::BITS)
+            .map(|d| 1usize << d)
+            .take_while(move |&lo| lo < nodes.len())
+            .map(move |lo| &nodes[lo..(lo << 1).min(nodes.len())])
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Levels<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<S: fmt::Debug> fmt::Display for Levels<'_, S> {
+    /// Writes one line per level, prefixed by its depth.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (d, level) in self.iter().enumerate() {
+            if d > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {:?}", d, level)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use crate::monoids::MajorityCandidate;
+use crate::segment_tree::SegmentTree;
+use std::collections::HashMap;
+
+/// A static structure answering range majority queries.
+///
+/// Given a sequence `a`, this data structure supports:
+/// - Range majority: `majority(l..r)` returns the value occurring more than `(r - l) / 2`
+///   times in `a[l..r]`, if any
+/// - Range frequency: `count(l..r, x)` returns the number of `i` in `l..r` with `a[i] == x`
+///
+/// A segment tree of [`MajorityCandidate`] yields the only possible majority of a range,
+/// which is then verified by binary searching the sorted list of positions of the candidate.
+/// Built in O(n) expected time and space, and both queries run in O(log n) time.
+#[derive(Clone, Debug)]
+pub struct RangeMajority<T: Clone + Eq + std::hash::Hash> {
+    votes: SegmentTree<MajorityCandidate<T>>,
+    /// The sorted positions of every value.
+    positions: HashMap<T, Vec<usize>>,
+}
+
+impl<T: Clone + Eq + std::hash::Hash> RangeMajority<T> {
+    /// Creates a new range majority structure from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_vec(v: Vec<T>) -> Self {
+        let mut positions: HashMap<T, Vec<usize>> = HashMap::new();
+        for (i, x) in v.iter().enumerate() {
+            positions.entry(x.clone()).or_default().push(i);
+        }
+        Self {
+            votes: v.into_iter().map(MajorityCandidate::new).collect(),
+            positions,
+        }
+    }
+
+    /// Creates a new range majority structure from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(v: &[T]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Returns the value occurring in more than half of the given range, or `None` if there
+    /// is no such value.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn majority(&self, range: impl std::ops::RangeBounds<usize>) -> Option<&T> {
+        let (l, r) = self.bounds(range);
+        let x = self.votes.range_fold(l..r).value?;
+        let (x, p) = self.positions.get_key_value(&x)?;
+        let count = p.partition_point(|&i| i < r) - p.partition_point(|&i| i < l);
+        (count > (r - l) / 2).then_some(x)
+    }
+
+    /// Returns the number of indices `i` in the given range with `a[i] == x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn count(&self, range: impl std::ops::RangeBounds<usize>, x: &T) -> usize {
+        let (l, r) = self.bounds(range);
+        self.positions.get(x).map_or(0, |p| {
+            p.partition_point(|&i| i < r) - p.partition_point(|&i| i < l)
+        })
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.votes.len()
+    }
+
+    /// Returns `true` if the sequence is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+}
+
+impl<T: Clone + Eq + std::hash::Hash> FromIterator<T> for RangeMajority<T> {
+    /// Creates a new range majority structure from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
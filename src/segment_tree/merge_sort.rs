@@ -0,0 +1,192 @@
+/// A static segment tree whose nodes store the sorted elements of their ranges.
+///
+/// Given a sequence `a` of ordered values, this data structure supports:
+/// - Rank query: `count_leq(l..r, x)` returns the number of `i` in `l..r` with `a[i] <= x`
+/// - Range count: `count_in(l..r, lo..hi)` returns the number of `i` in `l..r` with
+///   `a[i]` in `lo..hi`
+///
+/// Built in O(n log n) time and space, and both queries run in O(log² n) time.
+#[derive(Clone, Debug)]
+pub struct MergeSortTree<T: Ord> {
+    /// Binary heap-like array storing the sorted elements of each node.
+    /// Index 1 is the root, index `n + i` is the leaf for element `i`.
+    data: Box<[Box<[T]>]>,
+}
+
+impl<T: Ord + Clone> MergeSortTree<T> {
+    /// Creates a new merge sort tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_vec(v: Vec<T>) -> Self {
+        let n = v.len();
+        let mut data: Vec<Box<[T]>> = vec![Box::default(); n << 1];
+        for (i, x) in v.into_iter().enumerate() {
+            data[n + i] = Box::new([x]);
+        }
+        for i in (1..n).rev() {
+            let (a, b) = (&data[i << 1], &data[(i << 1) + 1]);
+            let mut merged = Vec::with_capacity(a.len() + b.len());
+            let (mut p, mut q) = (0, 0);
+            while p < a.len() && q < b.len() {
+                if b[q] < a[p] {
+                    merged.push(b[q].clone());
+                    q += 1;
+                } else {
+                    merged.push(a[p].clone());
+                    p += 1;
+                }
+            }
+            merged.extend_from_slice(&a[p..]);
+            merged.extend_from_slice(&b[q..]);
+            data[i] = merged.into_boxed_slice();
+        }
+        Self {
+            data: data.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new merge sort tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_slice(v: &[T]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new merge sort tree with `n` elements, where the element at index `i` is
+    /// `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> T,
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for MergeSortTree<T> {
+    /// Creates a new merge sort tree from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> MergeSortTree<T> {
+    /// Returns the number of indices `i` in the given range with `a[i] <= x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log² n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn count_leq(&self, range: impl std::ops::RangeBounds<usize>, x: &T) -> usize {
+        self.count(range, |v| v.partition_point(|y| y <= x))
+    }
+
+    /// Returns the number of indices `i` in the given range with `a[i]` in `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log² n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn count_in(
+        &self,
+        range: impl std::ops::RangeBounds<usize>,
+        values: impl std::ops::RangeBounds<T>,
+    ) -> usize {
+        self.count(range, |v| {
+            let lo = match values.start_bound() {
+                std::ops::Bound::Unbounded => 0,
+                std::ops::Bound::Included(x) => v.partition_point(|y| y < x),
+                std::ops::Bound::Excluded(x) => v.partition_point(|y| y <= x),
+            };
+            let hi = match values.end_bound() {
+                std::ops::Bound::Unbounded => v.len(),
+                std::ops::Bound::Included(x) => v.partition_point(|y| y <= x),
+                std::ops::Bound::Excluded(x) => v.partition_point(|y| y < x),
+            };
+            hi.saturating_sub(lo)
+        })
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.data.len() >> 1
+    }
+
+    /// Returns `true` if the merge sort tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sums `f` over the sorted elements of the nodes covering the given range.
+    #[inline]
+    fn count<C>(&self, range: impl std::ops::RangeBounds<usize>, f: C) -> usize
+    where
+        C: Fn(&[T]) -> usize,
+    {
+        let mut l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        } + self.len();
+        let mut r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        } + self.len();
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l - self.len(),
+            r - self.len(),
+        );
+        debug_assert!(
+            r <= self.len() << 1,
+            "index out of bounds: r={}, len={}",
+            r - self.len(),
+            self.len(),
+        );
+        let mut res = 0;
+        while l < r {
+            if l & 1 == 1 {
+                res += f(&self.data[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                res += f(&self.data[r]);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        res
+    }
+}
@@ -1,9 +1,33 @@
+pub mod actions;
+pub mod assign;
+pub mod cascading;
+pub mod codec;
+pub mod compact;
 pub mod core;
 pub mod core_with;
+pub mod dynamic;
+pub mod dynamic_lazy;
+pub mod frozen;
+pub mod kinetic;
 pub mod lazy;
+mod levels;
+pub mod majority;
+pub mod merge_sort;
 pub mod monoid;
+pub mod veb;
 
+pub use assign::RangeAssignSegmentTree;
+pub use cascading::CascadingMergeSortTree;
+pub use codec::Codec;
+pub use compact::CompactLazySegmentTree;
 pub use core::SegmentTree;
 pub use core_with::SegmentTreeWith;
+pub use dynamic::DynamicSegmentTree;
+pub use dynamic_lazy::DynamicLazySegmentTree;
+pub use frozen::FrozenSegmentTree;
+pub use kinetic::KineticSegmentTree;
 pub use lazy::LazySegmentTree;
-pub use monoid::{Action, Monoid};
+pub use majority::RangeMajority;
+pub use merge_sort::MergeSortTree;
+pub use monoid::{Action, Componentwise, Idempotent, Monoid, Semigroup};
+pub use veb::VebSegmentTree;
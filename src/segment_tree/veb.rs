@@ -0,0 +1,683 @@
+use crate::segment_tree::Monoid;
+
+/// A segment tree stored in van Emde Boas order for cache efficiency on huge inputs.
+///
+/// Given a monoid `(S, op, id)`, this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+///
+/// Both operations run in O(log n) time, like [`SegmentTree`](super::SegmentTree). Instead of
+/// the heap order, where every level of a root-to-leaf walk touches a different cache line,
+/// the tree is split at half its height into a top tree followed by its bottom trees, each
+/// laid out recursively. A root-to-leaf walk then touches O(log n / log B) blocks of any
+/// size `B`, at the cost of some index arithmetic per level, which only pays off when cache
+/// misses dominate, e.g. for trees far larger than the last-level cache. Benchmark against
+/// the heap order before switching.
+///
+/// This is a separate type rather than a layout option of [`SegmentTree`](super::SegmentTree):
+/// the heap order finds parents and siblings with shifts alone, which its bottom-up loops do
+/// at every step, while the position of a node here depends on the positions of its
+/// ancestors, so every walk goes from the root down. A runtime layout switch would cost the
+/// common case a branch per level. The queries mirror those of `SegmentTree`, and both
+/// implement [`RangeFold`](crate::range_query::RangeFold), so switching only changes the
+/// type.
+///
+/// The leaf count is rounded up to a power of two.
+#[derive(Clone, Debug)]
+pub struct VebSegmentTree<S: Monoid> {
+    /// Nodes in van Emde Boas order.
+    data: Box<[S]>,
+    /// Number of elements in the original array.
+    n: usize,
+    /// log2(size), the depth of the leaves.
+    log: usize,
+    /// Layout of the levels, see [`Level`].
+    levels: Box<[Level]>,
+}
+
+/// How the nodes at one depth are placed relative to their ancestors.
+///
+/// Every depth `d > 0` is the root depth of the bottom trees in exactly one split of the
+/// recursion, whose top tree is rooted at depth `top`. The node with heap index `i` at depth
+/// `d` is then placed at `pos[top] + top_size + (i & top_size) * bottom_size`, where
+/// `pos[top]` is the position of its ancestor at depth `top`.
+#[derive(Clone, Copy, Debug, Default)]
+struct Level {
+    top: usize,
+    top_size: usize,
+    bottom_size: usize,
+}
+
+impl<S: Monoid> VebSegmentTree<S> {
+    /// Creates a new segment tree with `n` elements, all initialized to `S::id()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new(n: usize) -> Self {
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        Self {
+            data: vec![S::id(); (size << 1) - 1].into_boxed_slice(),
+            n,
+            log,
+            levels: Self::levels(log),
+        }
+    }
+
+    /// Creates a new segment tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        // Build in heap order first, then move every node to its position.
+        let mut heap = vec![S::id(); size << 1];
+        for (h, x) in heap[size..].iter_mut().zip(v) {
+            *h = x;
+        }
+        for i in (1..size).rev() {
+            heap[i] = S::op(&heap[i << 1], &heap[(i << 1) + 1]);
+        }
+        let levels = Self::levels(log);
+        let mut data = vec![S::id(); (size << 1) - 1];
+        let mut pos = [0; usize::BITS as usize];
+        // Preorder walk, so that the ancestors of every node are placed before it.
+        let mut stack = vec![(1usize, 0usize)];
+        while let Some((i, d)) = stack.pop() {
+            if d > 0 {
+                pos[d] = Self::place(&levels, &pos, i, d);
+            }
+            data[pos[d]] = std::mem::replace(&mut heap[i], S::id());
+            if d < log {
+                stack.push(((i << 1) + 1, d + 1));
+                stack.push((i << 1, d + 1));
+            }
+        }
+        Self {
+            data: data.into_boxed_slice(),
+            n,
+            log,
+            levels,
+        }
+    }
+
+    /// Creates a new segment tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_slice(v: &[S]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new segment tree with `n` elements, where the element at index `i` is `f(i)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> S,
+    {
+        Self::from_vec((0..n).map(f).collect())
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn set(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let pos = self.path(i);
+        let leaf = (1 << self.log) | i;
+        self.data[pos[self.log]] = x;
+        for d in (0..self.log).rev() {
+            // The sibling of the path node one level down.
+            let sibling = Self::place(&self.levels, &pos, (leaf >> (self.log - d - 1)) ^ 1, d + 1);
+            let (l, r) = if (leaf >> (self.log - d - 1)) & 1 == 0 {
+                (pos[d + 1], sibling)
+            } else {
+                (sibling, pos[d + 1])
+            };
+            self.data[pos[d]] = S::op(&self.data[l], &self.data[r]);
+        }
+    }
+
+    /// Sets the value at index `i` to `op(a[i], x)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn operate(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let y = S::op(&self.get(i), &x);
+        self.set(i, y);
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.data[self.path(i)[self.log]].clone()
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return S::id();
+        }
+        let (l, r) = (l, r - 1);
+        let (leaf_l, leaf_r) = ((1 << self.log) | l, (1 << self.log) | r);
+        let mut pl = [0; usize::BITS as usize];
+        let mut pr = [0; usize::BITS as usize];
+        let mut left = S::id();
+        let mut right = S::id();
+        // Walk down both boundary paths, which split below their lowest common ancestor, and
+        // add the siblings lying between them.
+        let split = self.log + 1 - (usize::BITS - (l ^ r).leading_zeros()) as usize;
+        for d in 1..=self.log {
+            let (il, ir) = (leaf_l >> (self.log - d), leaf_r >> (self.log - d));
+            pl[d] = Self::place(&self.levels, &pl, il, d);
+            pr[d] = Self::place(&self.levels, &pr, ir, d);
+            if d > split {
+                if il & 1 == 0 {
+                    let sibling = Self::place(&self.levels, &pl, il ^ 1, d);
+                    left = S::op(&self.data[sibling], &left);
+                }
+                if ir & 1 == 1 {
+                    let sibling = Self::place(&self.levels, &pr, ir ^ 1, d);
+                    right = S::op(&right, &self.data[sibling]);
+                }
+            }
+        }
+        if l == r {
+            return self.data[pl[self.log]].clone();
+        }
+        left = S::op(&self.data[pl[self.log]], &left);
+        right = S::op(&right, &self.data[pr[self.log]]);
+        S::op(&left, &right)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        self.data[0].clone()
+    }
+
+    /// Returns the elements as a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn to_vec(&self) -> Vec<S> {
+        let mut v = Vec::with_capacity(self.len());
+        let mut pos = [0; usize::BITS as usize];
+        // Preorder walk, so that the leaves are visited from left to right.
+        let mut stack = vec![(1usize, 0usize)];
+        while let Some((i, d)) = stack.pop() {
+            if d > 0 {
+                pos[d] = Self::place(&self.levels, &pos, i, d);
+            }
+            if d < self.log {
+                // Subtrees starting at or after `n` hold only padding.
+                if (((i << 1) + 1) << (self.log - d - 1)) - (1 << self.log) < self.len() {
+                    stack.push(((i << 1) + 1, d + 1));
+                }
+                stack.push((i << 1, d + 1));
+            } else if v.len() < self.len() {
+                v.push(self.data[pos[d]].clone());
+            }
+        }
+        v
+    }
+
+    /// Returns the smallest `i` in the given range such that `p(op(a[l], ..., a[i]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[l], ..., a[i])`, it also holds
+    /// for every longer prefix starting at `l`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_first<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let (l, r) = self.bounds(range);
+        let mut pos = [0; usize::BITS as usize];
+        self.search_right(1, 0, &mut pos, l, r, &mut S::id(), &|x| !p(x))
+    }
+
+    /// Returns the largest `i` in the given range such that `p(op(a[i], ..., a[r - 1]))` is
+    /// true, or `None` if there is none.
+    ///
+    /// Assumes that `p` is monotonic: if `p` holds for `op(a[i], ..., a[r - 1])`, it also
+    /// holds for every longer suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn find_last<P>(&self, range: impl std::ops::RangeBounds<usize>, p: P) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let (l, r) = self.bounds(range);
+        let mut pos = [0; usize::BITS as usize];
+        self.search_left(1, 0, &mut pos, l, r, &mut S::id(), &|x| !p(x))
+    }
+
+    /// Returns the largest `r` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(S::id())` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter prefix starting at `l`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > len()` or `p(S::id())` is false in debug builds.
+    pub fn max_right<P>(&self, l: usize, p: P) -> usize
+    where
+        P: Fn(&S) -> bool,
+    {
+        debug_assert!(
+            l <= self.len(),
+            "index out of bounds: l={}, len={}",
+            l,
+            self.len(),
+        );
+        debug_assert!(p(&S::id()), "predicate must hold for the identity");
+        let mut pos = [0; usize::BITS as usize];
+        self.search_right(1, 0, &mut pos, l, self.len(), &mut S::id(), &p)
+            .unwrap_or(self.len())
+    }
+
+    /// Returns the smallest `l` such that `p(op(a[l], ..., a[r - 1]))` is true.
+    ///
+    /// Assumes that `p(S::id())` is true and that `p` is monotonic: if `p` holds for
+    /// `op(a[l], ..., a[r - 1])`, it also holds for every shorter suffix ending at `r`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r > len()` or `p(S::id())` is false in debug builds.
+    pub fn min_left<P>(&self, r: usize, p: P) -> usize
+    where
+        P: Fn(&S) -> bool,
+    {
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        debug_assert!(p(&S::id()), "predicate must hold for the identity");
+        let mut pos = [0; usize::BITS as usize];
+        self.search_left(1, 0, &mut pos, 0, r, &mut S::id(), &p)
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the segment tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the half-open bounds of `range`.
+    #[inline]
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+
+    /// Folds the leaves of `l..r` below the node with heap index `i` at depth `d` into `acc`
+    /// from left to right, and returns the first leaf whose fold fails `p`.
+    ///
+    /// `pos` holds the positions of the ancestors of the node, and its own at `pos[d]`.
+    #[allow(clippy::too_many_arguments)]
+    fn search_right<P>(
+        &self,
+        i: usize,
+        d: usize,
+        pos: &mut [usize; usize::BITS as usize],
+        l: usize,
+        r: usize,
+        acc: &mut S,
+        p: &P,
+    ) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let a = (i << (self.log - d)) - (1 << self.log);
+        let b = a + (1 << (self.log - d));
+        if b <= l || r <= a {
+            return None;
+        }
+        if l <= a && b <= r {
+            let t = S::op(acc, &self.data[pos[d]]);
+            if p(&t) {
+                *acc = t;
+                return None;
+            }
+            if d == self.log {
+                return Some(a);
+            }
+        }
+        for c in [i << 1, (i << 1) + 1] {
+            pos[d + 1] = Self::place(&self.levels, pos, c, d + 1);
+            if let Some(k) = self.search_right(c, d + 1, pos, l, r, acc, p) {
+                return Some(k);
+            }
+        }
+        None
+    }
+
+    /// Folds the leaves of `l..r` below the node with heap index `i` at depth `d` into `acc`
+    /// from right to left, and returns the first leaf whose fold fails `p`.
+    ///
+    /// `pos` holds the positions of the ancestors of the node, and its own at `pos[d]`.
+    #[allow(clippy::too_many_arguments)]
+    fn search_left<P>(
+        &self,
+        i: usize,
+        d: usize,
+        pos: &mut [usize; usize::BITS as usize],
+        l: usize,
+        r: usize,
+        acc: &mut S,
+        p: &P,
+    ) -> Option<usize>
+    where
+        P: Fn(&S) -> bool,
+    {
+        let a = (i << (self.log - d)) - (1 << self.log);
+        let b = a + (1 << (self.log - d));
+        if b <= l || r <= a {
+            return None;
+        }
+        if l <= a && b <= r {
+            let t = S::op(&self.data[pos[d]], acc);
+            if p(&t) {
+                *acc = t;
+                return None;
+            }
+            if d == self.log {
+                return Some(a);
+            }
+        }
+        for c in [(i << 1) + 1, i << 1] {
+            pos[d + 1] = Self::place(&self.levels, pos, c, d + 1);
+            if let Some(k) = self.search_left(c, d + 1, pos, l, r, acc, p) {
+                return Some(k);
+            }
+        }
+        None
+    }
+
+    /// Computes the layout of every depth of a tree with leaves at depth `log`.
+    fn levels(log: usize) -> Box<[Level]> {
+        fn split(levels: &mut [Level], root: usize, height: usize) {
+            if height <= 1 {
+                return;
+            }
+            let top = height >> 1;
+            let bottom = height - top;
+            levels[root + top] = Level {
+                top: root,
+                top_size: (1 << top) - 1,
+                bottom_size: (1 << bottom) - 1,
+            };
+            split(levels, root, top);
+            split(levels, root + top, bottom);
+        }
+        let mut levels = vec![Level::default(); log + 1];
+        split(&mut levels, 0, log + 1);
+        levels.into_boxed_slice()
+    }
+
+    /// Returns the position of the node with heap index `i` at depth `d > 0`, given the
+    /// positions `pos` of its ancestors.
+    #[inline(always)]
+    fn place(levels: &[Level], pos: &[usize], i: usize, d: usize) -> usize {
+        let level = levels[d];
+        pos[level.top] + level.top_size + (i & level.top_size) * level.bottom_size
+    }
+
+    /// Returns the positions of the nodes on the path from the root to the leaf of `i`.
+    #[inline]
+    fn path(&self, i: usize) -> [usize; usize::BITS as usize] {
+        let leaf = (1 << self.log) | i;
+        let mut pos = [0; usize::BITS as usize];
+        for d in 1..=self.log {
+            pos[d] = Self::place(&self.levels, &pos, leaf >> (self.log - d), d);
+        }
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::segment_tree::core::tests::{Rng, fold, random_mat, random_str};
+
+    /// Checks every query of `t` against `a`, on all ranges if `exhaustive` and on random
+    /// ones otherwise.
+    fn check<S: Monoid + PartialEq + std::fmt::Debug>(
+        t: &VebSegmentTree<S>,
+        a: &[S],
+        exhaustive: bool,
+        rng: &mut Rng,
+    ) {
+        let n = a.len();
+        assert_eq!(t.len(), n);
+        assert_eq!(t.to_vec(), a);
+        assert_eq!(t.all_fold(), fold(a));
+        let ranges: Vec<(usize, usize)> = if exhaustive {
+            (0..=n).flat_map(|l| (l..=n).map(move |r| (l, r))).collect()
+        } else {
+            (0..20)
+                .map(|_| {
+                    let (x, y) = (rng.below(n + 1), rng.below(n + 1));
+                    (x.min(y), x.max(y))
+                })
+                .collect()
+        };
+        for &(l, r) in &ranges {
+            assert_eq!(t.range_fold(l..r), fold(&a[l..r]), "range_fold({l}..{r})");
+            // The folds of the test inputs are all distinct, so `p` holds exactly for the
+            // folds of `a[l..l]` to `a[l..r]`.
+            let prefixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().scan(S::id(), |acc, x| {
+                    *acc = S::op(acc, x);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(
+                t.max_right(l, |x| prefixes.contains(x)),
+                r,
+                "max_right({l})"
+            );
+            let suffixes: Vec<S> = std::iter::once(S::id())
+                .chain(a[l..r].iter().rev().scan(S::id(), |acc, x| {
+                    *acc = S::op(x, acc);
+                    Some(acc.clone())
+                }))
+                .collect();
+            assert_eq!(t.min_left(r, |x| suffixes.contains(x)), l, "min_left({r})");
+            // `p` holds from the middle of the range on.
+            let m = (l + r) / 2;
+            if l < r {
+                assert_eq!(
+                    t.find_first(l..r, |x| !prefixes[..=m - l].contains(x)),
+                    Some(m),
+                    "find_first({l}..{r})"
+                );
+                assert_eq!(
+                    t.find_last(l..r, |x| !suffixes[..r - m].contains(x)),
+                    Some(m),
+                    "find_last({l}..{r})"
+                );
+            } else {
+                assert_eq!(t.find_first(l..r, |_| true), None);
+                assert_eq!(t.find_last(l..r, |_| true), None);
+            }
+        }
+    }
+
+    /// Checks trees built in every way from `a`.
+    fn check_all<S: Monoid + PartialEq + std::fmt::Debug>(
+        a: &[S],
+        exhaustive: bool,
+        rng: &mut Rng,
+    ) {
+        check(&VebSegmentTree::from_vec(a.to_vec()), a, exhaustive, rng);
+        check(&VebSegmentTree::from_slice(a), a, exhaustive, rng);
+        let mut t = VebSegmentTree::new(a.len());
+        for (i, x) in a.iter().enumerate() {
+            t.operate(i, x.clone());
+        }
+        check(&t, a, exhaustive, rng);
+    }
+
+    #[test]
+    fn string_concat() {
+        let mut rng = Rng(88_172_645_463_325_252);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_str(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_str(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+
+    #[test]
+    fn matrix_product() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for n in 0..=33 {
+            let a: Vec<_> = (0..n).map(|_| random_mat(&mut rng)).collect();
+            check_all(&a, true, &mut rng);
+        }
+        let a: Vec<_> = (0..1000).map(|_| random_mat(&mut rng)).collect();
+        check_all(&a, false, &mut rng);
+    }
+}
@@ -0,0 +1,308 @@
+/// Index of a missing node.
+const NIL: u32 = u32::MAX;
+
+/// Maximum number of levels.
+const MAX_LEVEL: usize = 32;
+
+/// A forward link of a node at some level.
+#[derive(Clone, Copy, Debug)]
+struct Link {
+    /// Index of the next node at this level in the arena, or `NIL`.
+    next: u32,
+    /// Difference of the positions of the next node and this node, where the head is at
+    /// position 0 and a missing node at position `len + 1`.
+    width: usize,
+}
+
+/// A node of a skip list, linked at levels `0..links.len()`.
+#[derive(Clone, Debug)]
+struct Node<T> {
+    /// The element, or `None` for the head and freed nodes.
+    value: Option<T>,
+    links: Vec<Link>,
+}
+
+/// A sorted multiset supporting order statistics.
+///
+/// Given elements of a totally ordered type `T`, this data structure supports:
+/// - Insertion and deletion: `insert(x)` and `remove(&x)`
+/// - Membership: `contains(&x)`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest element, and `rank(&x)` returns
+///   the number of elements less than `x`
+///
+/// All operations run in O(log n) expected time. Every node is linked at a random number
+/// of levels, each level skipping about half of the nodes of the level below, and every link
+/// stores the number of elements it skips, so that positions are tracked without rotations.
+/// Nodes live in a single arena and refer to each other by `u32` indices, and the slots of
+/// removed nodes are reused.
+#[derive(Clone)]
+pub struct SkipList<T> {
+    /// Arena of all nodes; node 0 is the head, linked at every level.
+    nodes: Vec<Node<T>>,
+    /// Indices of freed nodes.
+    free: Vec<u32>,
+    len: usize,
+    /// Number of levels at which some element is linked, at least 1.
+    level: usize,
+    /// State of the xorshift generator drawing the levels of new nodes.
+    rng: u64,
+}
+
+impl<T: Ord> SkipList<T> {
+    /// Creates a new empty skip list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                value: None,
+                links: vec![
+                    Link {
+                        next: NIL,
+                        width: 1
+                    };
+                    MAX_LEVEL
+                ],
+            }],
+            free: Vec::new(),
+            len: 0,
+            level: 1,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Inserts `x`, after any elements equal to it.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn insert(&mut self, x: T) {
+        let (update, pos) = self.path(|y| y <= &x);
+        let h = self.height();
+        self.level = self.level.max(h);
+        let p = pos[0] + 1;
+        let mut links = Vec::with_capacity(h);
+        let t = match self.free.pop() {
+            Some(t) => t,
+            None => {
+                debug_assert!(
+                    self.nodes.len() < NIL as usize,
+                    "too many nodes: {}",
+                    self.nodes.len(),
+                );
+                self.nodes.push(Node {
+                    value: None,
+                    links: Vec::new(),
+                });
+                (self.nodes.len() - 1) as u32
+            }
+        };
+        for l in 0..MAX_LEVEL {
+            let link = &mut self.nodes[update[l] as usize].links[l];
+            if l < h {
+                links.push(Link {
+                    next: link.next,
+                    width: link.width + pos[l] + 1 - p,
+                });
+                *link = Link {
+                    next: t,
+                    width: p - pos[l],
+                };
+            } else {
+                link.width += 1;
+            }
+        }
+        self.nodes[t as usize] = Node {
+            value: Some(x),
+            links,
+        };
+        self.len += 1;
+    }
+
+    /// Removes one element equal to `x`, and returns `true` if there was one.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn remove(&mut self, x: &T) -> bool {
+        let (update, _) = self.path(|y| y < x);
+        let t = self.nodes[update[0] as usize].links[0].next;
+        if t == NIL || self.nodes[t as usize].value.as_ref() != Some(x) {
+            return false;
+        }
+        let node = std::mem::replace(
+            &mut self.nodes[t as usize],
+            Node {
+                value: None,
+                links: Vec::new(),
+            },
+        );
+        for (l, &u) in update.iter().enumerate() {
+            let link = &mut self.nodes[u as usize].links[l];
+            if l < node.links.len() {
+                *link = Link {
+                    next: node.links[l].next,
+                    width: link.width + node.links[l].width - 1,
+                };
+            } else {
+                link.width -= 1;
+            }
+        }
+        self.free.push(t);
+        self.len -= 1;
+        while self.level > 1 && self.nodes[0].links[self.level - 1].next == NIL {
+            self.level -= 1;
+        }
+        true
+    }
+
+    /// Returns `true` if the skip list contains an element equal to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn contains(&self, x: &T) -> bool {
+        let (update, _) = self.path(|y| y < x);
+        let t = self.nodes[update[0] as usize].links[0].next;
+        t != NIL && self.nodes[t as usize].value.as_ref() == Some(x)
+    }
+
+    /// Returns the number of elements less than `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn rank(&self, x: &T) -> usize {
+        self.path(|y| y < x).1[0]
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        if k >= self.len {
+            return None;
+        }
+        let (mut t, mut pos) = (0, 0);
+        for l in (0..self.level).rev() {
+            loop {
+                let link = self.nodes[t as usize].links[l];
+                if pos + link.width > k + 1 {
+                    break;
+                }
+                pos += link.width;
+                t = link.next;
+            }
+        }
+        self.nodes[t as usize].value.as_ref()
+    }
+
+    /// Returns an iterator over the elements, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut t = self.nodes[0].links[0].next;
+        std::iter::from_fn(move || {
+            if t == NIL {
+                return None;
+            }
+            let node = &self.nodes[t as usize];
+            t = node.links[0].next;
+            node.value.as_ref()
+        })
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the skip list is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns, for every level, the last node whose element satisfies `pred`, or the head,
+    /// together with its position.
+    ///
+    /// `pred` must hold for a prefix of the elements.
+    fn path<P>(&self, pred: P) -> ([u32; MAX_LEVEL], [usize; MAX_LEVEL])
+    where
+        P: Fn(&T) -> bool,
+    {
+        let (mut update, mut pos) = ([0; MAX_LEVEL], [0; MAX_LEVEL]);
+        let (mut t, mut p) = (0, 0);
+        for l in (0..self.level).rev() {
+            loop {
+                let link = self.nodes[t as usize].links[l];
+                if link.next == NIL
+                    || !self.nodes[link.next as usize]
+                        .value
+                        .as_ref()
+                        .is_some_and(&pred)
+                {
+                    break;
+                }
+                p += link.width;
+                t = link.next;
+            }
+            update[l] = t;
+            pos[l] = p;
+        }
+        (update, pos)
+    }
+
+    /// Returns a random level count, `h` with probability `2^-h`.
+    #[inline]
+    fn height(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<T: Ord> Default for SkipList<T> {
+    /// Creates a new empty skip list.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipList<T> {
+    /// Creates a new skip list from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n) expected
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for x in iter {
+            res.insert(x);
+        }
+        res
+    }
+}
+
+impl<T: Ord + std::fmt::Debug> std::fmt::Debug for SkipList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
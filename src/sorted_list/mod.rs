@@ -0,0 +1,292 @@
+/// The target ratio of the bucket size to the number of buckets when rebuilding.
+const BUCKET_RATIO: usize = 16;
+
+/// A bucket is split when its size exceeds this ratio times the number of buckets.
+const SPLIT_RATIO: usize = 24;
+
+/// A sorted multiset stored as a list of sorted buckets.
+///
+/// Given elements of a totally ordered type `T`, this data structure supports:
+/// - Insertion and deletion: `insert(x)`, `remove(&x)` and `remove_kth(k)`
+/// - Membership: `contains(&x)` and `count(&x)`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest element
+/// - Bisection: `bisect_left(&x)` and `bisect_right(&x)` return the number of elements less
+///   than, and less than or equal to, `x`
+///
+/// The elements are split into sorted buckets of about `√(n · 16)` elements, which are
+/// located by binary search on their last elements. Updates and order statistics run in
+/// O(√n) time, but only shift elements within one bucket and scan the bucket lengths, so
+/// this often outperforms balanced trees in practice.
+#[derive(Clone)]
+pub struct SortedList<T> {
+    /// The non-empty buckets, in ascending order.
+    buckets: Vec<Vec<T>>,
+    len: usize,
+}
+
+impl<T: Ord> SortedList<T> {
+    /// Creates a new empty sorted list.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            buckets: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a new sorted list from a vec, in any order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_vec(mut v: Vec<T>) -> Self {
+        v.sort();
+        let n = v.len();
+        let k = n.div_ceil(BUCKET_RATIO).isqrt().max(1);
+        let mut buckets = Vec::with_capacity(k);
+        let mut iter = v.into_iter();
+        for i in 0..k {
+            let b: Vec<T> = iter.by_ref().take(n * (i + 1) / k - n * i / k).collect();
+            if !b.is_empty() {
+                buckets.push(b);
+            }
+        }
+        Self { buckets, len: n }
+    }
+
+    /// Creates a new sorted list from a slice, in any order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_slice(v: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Inserts `x`, after any elements equal to it.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n) amortized
+    pub fn insert(&mut self, x: T) {
+        self.len += 1;
+        if self.buckets.is_empty() {
+            self.buckets.push(vec![x]);
+            return;
+        }
+        let i = self
+            .buckets
+            .partition_point(|b| b.last().is_some_and(|y| *y <= x))
+            .min(self.buckets.len() - 1);
+        let limit = self.buckets.len() * SPLIT_RATIO;
+        let b = &mut self.buckets[i];
+        let j = b.partition_point(|y| *y <= x);
+        b.insert(j, x);
+        if b.len() > limit {
+            let rest = b.split_off(b.len() / 2);
+            self.buckets.insert(i + 1, rest);
+        }
+    }
+
+    /// Removes one element equal to `x`, and returns `true` if there was one.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    pub fn remove(&mut self, x: &T) -> bool {
+        let Some((i, j)) = self.find(x) else {
+            return false;
+        };
+        self.remove_at(i, j);
+        true
+    }
+
+    /// Removes and returns the `k`-th (0-indexed) smallest element, or `None` if
+    /// `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    pub fn remove_kth(&mut self, k: usize) -> Option<T> {
+        let (i, j) = self.locate(k)?;
+        Some(self.remove_at(i, j))
+    }
+
+    /// Returns `true` if the sorted list contains an element equal to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool {
+        self.find(x).is_some()
+    }
+
+    /// Returns the number of elements equal to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    #[inline]
+    pub fn count(&self, x: &T) -> usize {
+        self.bisect_right(x) - self.bisect_left(x)
+    }
+
+    /// Returns the number of elements less than `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    #[inline]
+    pub fn bisect_left(&self, x: &T) -> usize {
+        self.bisect(|y| y < x)
+    }
+
+    /// Returns the number of elements less than or equal to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    #[inline]
+    pub fn bisect_right(&self, x: &T) -> usize {
+        self.bisect(|y| y <= x)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest element, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    #[inline]
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        let (i, j) = self.locate(k)?;
+        Some(&self.buckets[i][j])
+    }
+
+    /// Returns the smallest element, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.buckets.first()?.first()
+    }
+
+    /// Returns the largest element, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.buckets.last()?.last()
+    }
+
+    /// Returns an iterator over the elements, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.iter().flatten()
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the sorted list is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bucket and the position in it of the first element equal to `x`, if any.
+    #[inline]
+    fn find(&self, x: &T) -> Option<(usize, usize)> {
+        let i = self
+            .buckets
+            .partition_point(|b| b.last().is_some_and(|y| y < x));
+        let b = self.buckets.get(i)?;
+        let j = b.partition_point(|y| y < x);
+        (b[j] == *x).then_some((i, j))
+    }
+
+    /// Returns the bucket and the position in it of the `k`-th smallest element, if any.
+    #[inline]
+    fn locate(&self, mut k: usize) -> Option<(usize, usize)> {
+        for (i, b) in self.buckets.iter().enumerate() {
+            if k < b.len() {
+                return Some((i, k));
+            }
+            k -= b.len();
+        }
+        None
+    }
+
+    /// Returns the number of elements satisfying `pred`, which must hold for a prefix of the
+    /// elements.
+    #[inline]
+    fn bisect<P>(&self, pred: P) -> usize
+    where
+        P: Fn(&T) -> bool,
+    {
+        let i = self
+            .buckets
+            .partition_point(|b| b.last().is_some_and(&pred));
+        let before: usize = self.buckets[..i].iter().map(Vec::len).sum();
+        before + self.buckets.get(i).map_or(0, |b| b.partition_point(&pred))
+    }
+
+    #[inline]
+    fn remove_at(&mut self, i: usize, j: usize) -> T {
+        self.len -= 1;
+        let x = self.buckets[i].remove(j);
+        if self.buckets[i].is_empty() {
+            self.buckets.remove(i);
+        }
+        x
+    }
+}
+
+impl<T: Ord> Default for SortedList<T> {
+    /// Creates a new empty sorted list.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedList<T> {
+    /// Creates a new sorted list from an iterator, in any order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord + std::fmt::Debug> std::fmt::Debug for SortedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,136 @@
+use crate::algebra::Idempotent;
+
+/// A sparse table for static range queries over an idempotent monoid.
+///
+/// Given an idempotent monoid `(S, op, id)` such as min, max, gcd, bitwise and or bitwise or,
+/// this data structure supports:
+/// - Range query: `range_fold(l..r)` returns `op(a[l], op(a[l+1], ..., a[r-1]))`
+///
+/// Built in O(n log n) time and space, and queries run in O(1) time.
+/// The range is covered by two possibly overlapping blocks whose length is a power of two,
+/// so `op` does not need to be commutative.
+#[derive(Clone, Debug)]
+pub struct SparseTable<S: Idempotent> {
+    /// `table[k][i]` is `op(a[i], ..., a[i + 2^k - 1])`.
+    table: Box<[Box<[S]>]>,
+}
+
+impl<S: Idempotent> SparseTable<S> {
+    /// Creates a new sparse table from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let n = v.len();
+        let mut table = Vec::with_capacity(n.max(1).ilog2() as usize + 1);
+        table.push(v.into_boxed_slice());
+        let mut k = 1;
+        while (1 << k) <= n {
+            let prev: &[S] = &table[k - 1];
+            let half = 1 << (k - 1);
+            let row: Box<[S]> = (0..=n - (1 << k))
+                .map(|i| S::op(&prev[i], &prev[i + half]))
+                .collect();
+            table.push(row);
+            k += 1;
+        }
+        Self {
+            table: table.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a new sparse table from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n)
+    pub fn from_slice(v: &[S]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        unsafe { self.table.get_unchecked(0).get_unchecked(i).clone() }
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return S::id();
+        }
+        let k = (r - l).ilog2() as usize;
+        unsafe {
+            let row = self.table.get_unchecked(k);
+            S::op(row.get_unchecked(l), row.get_unchecked(r - (1 << k)))
+        }
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    /// Returns `true` if the sparse table is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,286 @@
+/// The summary of one block of a [`SqrtDecomposition`].
+///
+/// A block summarizes a contiguous run of elements, e.g. their sum, their sorted copy, or
+/// a frequency table. It may also hold a lazy tag that applies to every element of the
+/// block and is already reflected in the summary.
+pub trait Block<S>: Sized {
+    /// Builds the summary of a block from its elements.
+    fn build(elems: &[S]) -> Self;
+
+    /// Applies the pending lazy tag of this block to its elements and clears the tag.
+    ///
+    /// The summary must stay valid for the updated elements. Blocks without lazy tags can
+    /// use the default, which does nothing.
+    #[inline(always)]
+    fn push(&mut self, _elems: &mut [S]) {}
+}
+
+/// A sequence split into blocks of about √n elements, each with a user-defined summary.
+///
+/// Given a block summary `B` implementing [`Block<S>`], this data structure supports:
+/// - Range update: `update(l..r, partial, full)` calls `partial` on each element of the
+///   blocks partially covered by the range, and `full` on each block fully covered by it
+/// - Range query: `fold(l..r, init, partial, full)` folds `partial` over the elements of the
+///   blocks partially covered by the range, and `full` over the blocks fully covered by it
+///
+/// Partially covered blocks are pushed before their elements are visited, and rebuilt after
+/// they are updated. With blocks of size b, each operation makes O(b) element calls and
+/// O(n / b) block calls, plus O(b) for the rebuild.
+#[derive(Clone, Debug)]
+pub struct SqrtDecomposition<S, B: Block<S>> {
+    data: Vec<S>,
+    blocks: Vec<B>,
+    block_size: usize,
+}
+
+impl<S, B: Block<S>> SqrtDecomposition<S, B> {
+    /// Creates a new sqrt decomposition from a vec, with blocks of about √n elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) plus the cost of building every block.
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let block_size = v.len().isqrt().max(1);
+        Self::with_block_size(v, block_size)
+    }
+
+    /// Creates a new sqrt decomposition from a vec, with blocks of `block_size` elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) plus the cost of building every block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size == 0`.
+    pub fn with_block_size(v: Vec<S>, block_size: usize) -> Self {
+        assert!(block_size > 0, "block size must be positive");
+        let blocks = v.chunks(block_size).map(B::build).collect();
+        Self {
+            data: v,
+            blocks,
+            block_size,
+        }
+    }
+
+    /// Returns the element at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(b) to push the block containing `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn get(&mut self, i: usize) -> &S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let k = i / self.block_size;
+        self.push(k);
+        &self.data[i]
+    }
+
+    /// Sets the element at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(b) plus the cost of rebuilding the block containing `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn set(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let k = i / self.block_size;
+        self.push(k);
+        self.data[i] = x;
+        self.rebuild(k);
+    }
+
+    /// Updates the given range, calling `partial` on every element in a partially covered
+    /// block, and `full` on every fully covered block.
+    ///
+    /// `full` must keep the summary consistent with the updated elements, typically by
+    /// updating the summary and composing a lazy tag to be applied later by [`Block::push`].
+    ///
+    /// # Time complexity
+    ///
+    /// O(b + n / b) calls, plus the cost of rebuilding at most two blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn update<P, F>(
+        &mut self,
+        range: impl std::ops::RangeBounds<usize>,
+        mut partial: P,
+        mut full: F,
+    ) where
+        P: FnMut(&mut S),
+        F: FnMut(&mut B),
+    {
+        let (l, r) = self.bounds(range);
+        if l == r {
+            return;
+        }
+        for k in l / self.block_size..=(r - 1) / self.block_size {
+            let (start, end) = self.block_range(k);
+            if l <= start && end <= r {
+                full(&mut self.blocks[k]);
+            } else {
+                self.push(k);
+                self.data[start.max(l)..end.min(r)]
+                    .iter_mut()
+                    .for_each(&mut partial);
+                self.rebuild(k);
+            }
+        }
+    }
+
+    /// Folds the given range from left to right, starting from `init`, calling `partial` on
+    /// every element in a partially covered block, and `full` on every fully covered block.
+    ///
+    /// # Time complexity
+    ///
+    /// O(b + n / b) calls, plus O(b) to push at most two blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn fold<R, P, F>(
+        &mut self,
+        range: impl std::ops::RangeBounds<usize>,
+        init: R,
+        mut partial: P,
+        mut full: F,
+    ) -> R
+    where
+        P: FnMut(R, &S) -> R,
+        F: FnMut(R, &B) -> R,
+    {
+        let (l, r) = self.bounds(range);
+        let mut acc = init;
+        if l == r {
+            return acc;
+        }
+        for k in l / self.block_size..=(r - 1) / self.block_size {
+            let (start, end) = self.block_range(k);
+            if l <= start && end <= r {
+                acc = full(acc, &self.blocks[k]);
+            } else {
+                self.push(k);
+                acc = self.data[start.max(l)..end.min(r)]
+                    .iter()
+                    .fold(acc, &mut partial);
+            }
+        }
+        acc
+    }
+
+    /// Returns the block summaries, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn blocks(&self) -> &[B] {
+        &self.blocks
+    }
+
+    /// Returns the number of elements per block. Only the last block may be shorter.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Consumes the sqrt decomposition and returns its elements, with every lazy tag pushed.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn into_vec(mut self) -> Vec<S> {
+        for k in 0..self.blocks.len() {
+            self.push(k);
+        }
+        self.data
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the sqrt decomposition is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the range of indices covered by block `k`.
+    #[inline(always)]
+    fn block_range(&self, k: usize) -> (usize, usize) {
+        let start = k * self.block_size;
+        (start, (start + self.block_size).min(self.len()))
+    }
+
+    #[inline]
+    fn push(&mut self, k: usize) {
+        let (start, end) = self.block_range(k);
+        self.blocks[k].push(&mut self.data[start..end]);
+    }
+
+    #[inline]
+    fn rebuild(&mut self, k: usize) {
+        let (start, end) = self.block_range(k);
+        self.blocks[k] = B::build(&self.data[start..end]);
+    }
+
+    #[inline]
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+}
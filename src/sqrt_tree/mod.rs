@@ -0,0 +1,324 @@
+use crate::algebra::Monoid;
+
+/// A sqrt tree for range queries with O(1) query time.
+///
+/// Given a monoid `(S, op, id)`, this data structure supports:
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Range query: `range_fold(l..r)` returns `op(a[l], op(a[l+1], ..., a[r-1]))`
+///
+/// The array is split into blocks of size about √n, recursively. Every layer stores the
+/// prefix and suffix folds inside each block, and the folds between every pair of blocks,
+/// so each query is answered by combining at most three precomputed values.
+///
+/// Built in O(n log log n) time and space. Queries run in O(1) time, and point updates
+/// run in O(√n) time.
+#[derive(Clone, Debug)]
+pub struct SqrtTree<S: Monoid> {
+    /// The elements, followed by the suffix fold of every top-level block.
+    /// The latter form the index part, itself a sqrt tree used for layer 0.
+    v: Vec<S>,
+    n: usize,
+    /// log2 of the padded size.
+    lg: usize,
+    /// Number of top-level blocks.
+    index_size: usize,
+    /// `bit_len[x]` is the number of bits needed to represent `x`.
+    bit_len: Box<[usize]>,
+    /// log2 of the size of the ranges split by each layer.
+    layers: Box<[usize]>,
+    /// `on_layer[k]` is the layer answering queries whose endpoints first differ at bit `k - 1`.
+    on_layer: Box<[usize]>,
+    pref: Box<[Box<[S]>]>,
+    suf: Box<[Box<[S]>]>,
+    between: Box<[Box<[S]>]>,
+}
+
+impl<S: Monoid> SqrtTree<S> {
+    /// Creates a new sqrt tree from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log log n)
+    pub fn from_vec(v: Vec<S>) -> Self {
+        let n = v.len();
+        let lg = n.next_power_of_two().trailing_zeros() as usize;
+        let mut bit_len = vec![0; 1 << lg];
+        for i in 1..bit_len.len() {
+            bit_len[i] = bit_len[i >> 1] + 1;
+        }
+        let mut layers = vec![];
+        let mut on_layer = vec![0; lg + 1];
+        let mut t = lg;
+        while t > 1 {
+            on_layer[t] = layers.len();
+            layers.push(t);
+            t = (t + 1) >> 1;
+        }
+        for i in (0..lg).rev() {
+            on_layer[i] = on_layer[i].max(on_layer[i + 1]);
+        }
+        let between_layers = layers.len().saturating_sub(1);
+        let b_size_log = (lg + 1) >> 1;
+        let b_size = 1 << b_size_log;
+        let index_size = (n + b_size - 1) >> b_size_log;
+        let mut v = v;
+        v.resize(n + index_size, S::id());
+        let row = |len: usize| vec![S::id(); len].into_boxed_slice();
+        let mut tree = Self {
+            v,
+            n,
+            lg,
+            index_size,
+            bit_len: bit_len.into_boxed_slice(),
+            pref: (0..layers.len()).map(|_| row(n + index_size)).collect(),
+            suf: (0..layers.len()).map(|_| row(n + index_size)).collect(),
+            between: (0..between_layers)
+                .map(|_| row((1 << lg) + b_size))
+                .collect(),
+            layers: layers.into_boxed_slice(),
+            on_layer: on_layer.into_boxed_slice(),
+        };
+        tree.build(0, 0, n, 0);
+        tree
+    }
+
+    /// Creates a new sqrt tree from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log log n)
+    pub fn from_slice(v: &[S]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Sets the value at index `i` to `x`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(√n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn set(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.v[i] = x;
+        self.update(0, 0, self.n, 0, i);
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    #[inline]
+    pub fn get(&self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        self.v[i].clone()
+    }
+
+    /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+    ///
+    /// Returns `S::id()` if the range is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_fold(&self, range: impl std::ops::RangeBounds<usize>) -> S {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        if l == r {
+            return S::id();
+        }
+        self.query(l, r - 1, 0, 0)
+    }
+
+    /// Returns `op(a[0], a[1], ..., a[n-1])`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn all_fold(&self) -> S {
+        self.range_fold(..)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the sqrt tree is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the fold of the closed range `l..=r`.
+    fn query(&self, l: usize, r: usize, between_offset: usize, base: usize) -> S {
+        if l == r {
+            return self.v[l].clone();
+        }
+        if l + 1 == r {
+            return S::op(&self.v[l], &self.v[r]);
+        }
+        let layer = self.on_layer[self.bit_len[(l - base) ^ (r - base)]];
+        let b_size_log = (self.layers[layer] + 1) >> 1;
+        let b_count_log = self.layers[layer] >> 1;
+        let l_bound = (((l - base) >> self.layers[layer]) << self.layers[layer]) + base;
+        // Blocks strictly between the ones containing `l` and `r` are `l_block..r_block`.
+        let l_block = ((l - l_bound) >> b_size_log) + 1;
+        let r_block = (r - l_bound) >> b_size_log;
+        let mut res = self.suf[layer][l].clone();
+        if l_block < r_block {
+            let r_block = r_block - 1;
+            let mid = if layer == 0 {
+                self.query(
+                    self.n + l_block,
+                    self.n + r_block,
+                    (1 << self.lg) - self.n,
+                    self.n,
+                )
+            } else {
+                self.between[layer - 1]
+                    [between_offset + l_bound + (l_block << b_count_log) + r_block]
+                    .clone()
+            };
+            res = S::op(&res, &mid);
+        }
+        S::op(&res, &self.pref[layer][r])
+    }
+
+    /// Recomputes the prefix and suffix folds of the block `l..r` on `layer`.
+    fn build_block(&mut self, layer: usize, l: usize, r: usize) {
+        let (pref, suf, v) = (&mut self.pref[layer], &mut self.suf[layer], &self.v);
+        pref[l] = v[l].clone();
+        for i in l + 1..r {
+            pref[i] = S::op(&pref[i - 1], &v[i]);
+        }
+        suf[r - 1] = v[r - 1].clone();
+        for i in (l..r - 1).rev() {
+            suf[i] = S::op(&v[i], &suf[i + 1]);
+        }
+    }
+
+    /// Recomputes the folds between every pair of blocks of `l_bound..r_bound` on `layer`.
+    fn build_between(&mut self, layer: usize, l_bound: usize, r_bound: usize, offset: usize) {
+        let b_size_log = (self.layers[layer] + 1) >> 1;
+        let b_count_log = self.layers[layer] >> 1;
+        let b_size = 1 << b_size_log;
+        let b_count = (r_bound - l_bound + b_size - 1) >> b_size_log;
+        for i in 0..b_count {
+            let mut acc = S::id();
+            for j in i..b_count {
+                acc = S::op(&acc, &self.suf[layer][l_bound + (j << b_size_log)]);
+                self.between[layer - 1][offset + l_bound + (i << b_count_log) + j] = acc.clone();
+            }
+        }
+    }
+
+    fn build_between_zero(&mut self) {
+        let b_size_log = (self.lg + 1) >> 1;
+        for i in 0..self.index_size {
+            self.v[self.n + i] = self.suf[0][i << b_size_log].clone();
+        }
+        self.build(1, self.n, self.n + self.index_size, (1 << self.lg) - self.n);
+    }
+
+    fn update_between_zero(&mut self, block: usize) {
+        let b_size_log = (self.lg + 1) >> 1;
+        self.v[self.n + block] = self.suf[0][block << b_size_log].clone();
+        self.update(
+            1,
+            self.n,
+            self.n + self.index_size,
+            (1 << self.lg) - self.n,
+            self.n + block,
+        );
+    }
+
+    fn build(&mut self, layer: usize, l_bound: usize, r_bound: usize, offset: usize) {
+        if layer >= self.layers.len() {
+            return;
+        }
+        let b_size = 1 << ((self.layers[layer] + 1) >> 1);
+        let mut l = l_bound;
+        while l < r_bound {
+            let r = (l + b_size).min(r_bound);
+            self.build_block(layer, l, r);
+            self.build(layer + 1, l, r, offset);
+            l += b_size;
+        }
+        if layer == 0 {
+            self.build_between_zero();
+        } else {
+            self.build_between(layer, l_bound, r_bound, offset);
+        }
+    }
+
+    fn update(&mut self, layer: usize, l_bound: usize, r_bound: usize, offset: usize, x: usize) {
+        if layer >= self.layers.len() {
+            return;
+        }
+        let b_size_log = (self.layers[layer] + 1) >> 1;
+        let b_size = 1 << b_size_log;
+        let block = (x - l_bound) >> b_size_log;
+        let l = l_bound + (block << b_size_log);
+        let r = (l + b_size).min(r_bound);
+        self.build_block(layer, l, r);
+        if layer == 0 {
+            self.update_between_zero(block);
+        } else {
+            self.build_between(layer, l_bound, r_bound, offset);
+        }
+        self.update(layer + 1, l, r, offset, x);
+    }
+}
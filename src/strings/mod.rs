@@ -0,0 +1,337 @@
+//! Linear-time string matching primitives over slices of any `T: Eq`.
+//!
+//! These complement [`SuffixArray`](crate::suffix_array::SuffixArray),
+//! [`SuffixAutomaton`](crate::suffix_automaton::SuffixAutomaton) and
+//! [`AhoCorasick`](crate::aho_corasick::AhoCorasick) when a single pattern or a single pass is
+//! enough.
+
+use crate::algebra::Monoid;
+use crate::monoids::DoubleRangeHash;
+
+/// Returns the Z-array of `s`: `z[i]` is the length of the longest common prefix of `s` and
+/// `s[i..]`, with `z[0] = n`.
+///
+/// # Time complexity
+///
+/// O(n)
+pub fn z_function<T: Eq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // `s[l..r]` is the match of a prefix reaching farthest to the right.
+    let (mut l, mut r) = (0, 0);
+    for i in 1..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while i + k < n && s[k] == s[i + k] {
+            k += 1;
+        }
+        z[i] = k;
+        if i + k > r {
+            (l, r) = (i, i + k);
+        }
+    }
+    z
+}
+
+/// Returns the prefix function of `s`: `pi[i]` is the length of the longest proper prefix of
+/// `s[..=i]` that is also its suffix.
+///
+/// # Time complexity
+///
+/// O(n)
+pub fn prefix_function<T: Eq>(s: &[T]) -> Vec<usize> {
+    let mut pi = vec![0; s.len()];
+    for i in 1..s.len() {
+        let mut k = pi[i - 1];
+        while k > 0 && s[i] != s[k] {
+            k = pi[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        pi[i] = k;
+    }
+    pi
+}
+
+/// Returns the lengths of the longest palindromes of `s` at all `2n - 1` centers, by
+/// Manacher's algorithm.
+///
+/// `res[2i]` is the length of the longest palindrome centered at `s[i]`, which is odd, and
+/// `res[2i + 1]` that of the longest palindrome centered between `s[i]` and `s[i + 1]`, which
+/// is even. The palindrome at center `c` is `s[(c + 1 - res[c]) / 2..(c + 1 + res[c]) / 2]`.
+///
+/// # Time complexity
+///
+/// O(n)
+pub fn manacher<T: Eq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    // Radii over `s` interleaved with separators, at the odd positions, which match anything:
+    // `rad[c]` is the number of positions on each side of `c` within the palindrome.
+    let m = 2 * n - 1;
+    let mut rad = vec![0; m];
+    let (mut center, mut right) = (0, 0);
+    for c in 0..m {
+        let mut k = if c < right {
+            rad[2 * center - c].min(right - c)
+        } else {
+            0
+        };
+        while c > k && c + k + 1 < m {
+            let (a, b) = (c - k - 1, c + k + 1);
+            if a % 2 == 0 && s[a / 2] != s[b / 2] {
+                break;
+            }
+            k += 1;
+        }
+        rad[c] = k;
+        if c + k > right {
+            (center, right) = (c, c + k);
+        }
+    }
+    // The interleaved palindrome of radius `k` at `c` spans `2k + 1` positions, whose
+    // characters of `s` are those at even positions.
+    rad.iter()
+        .enumerate()
+        .map(|(c, &k)| {
+            let (l, r) = (c - k, c + k);
+            r / 2 + 1 - l.div_ceil(2)
+        })
+        .collect()
+}
+
+/// A Knuth–Morris–Pratt matcher for a single pattern.
+///
+/// Given a pattern `p` of length `m`, this data structure supports:
+/// - Search: `find_iter(h)` iterates over the starts of the occurrences of `p` in `h`
+/// - Streaming: `next_state(state, &c)` advances the KMP automaton, whose state is the length
+///   of the longest prefix of `p` that is a suffix of the text read so far, and reaches `m`
+///   at every occurrence
+///
+/// Built in O(m) time. A search runs in O(n) time, and a stream in O(1) amortized time per
+/// element.
+#[derive(Clone, Debug)]
+pub struct KmpMatcher<T> {
+    pattern: Vec<T>,
+    pi: Vec<usize>,
+}
+
+impl<T: Eq + Clone> KmpMatcher<T> {
+    /// Creates a new matcher for `pattern`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn new(pattern: &[T]) -> Self {
+        Self {
+            pattern: pattern.to_vec(),
+            pi: prefix_function(pattern),
+        }
+    }
+
+    /// Returns the state after reading `c` in `state`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1) amortized over a stream
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state > m`.
+    pub fn next_state(&self, mut state: usize, c: &T) -> usize {
+        let m = self.pattern.len();
+        if state == m {
+            if m == 0 {
+                return 0;
+            }
+            state = self.pi[m - 1];
+        }
+        while state > 0 && self.pattern[state] != *c {
+            state = self.pi[state - 1];
+        }
+        if self.pattern[state] == *c {
+            state + 1
+        } else {
+            0
+        }
+    }
+
+    /// Returns an iterator over the starts of the occurrences of the pattern in `haystack`,
+    /// in ascending order, including overlapping ones.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n) for a full traversal
+    pub fn find_iter<'a>(&'a self, haystack: &'a [T]) -> impl Iterator<Item = usize> + 'a {
+        let m = self.pattern.len();
+        let (mut i, mut state) = (0, 0);
+        // The empty pattern also occurs before the first element.
+        let mut empty = m == 0;
+        std::iter::from_fn(move || {
+            if std::mem::take(&mut empty) {
+                return Some(0);
+            }
+            while i < haystack.len() {
+                state = self.next_state(state, &haystack[i]);
+                i += 1;
+                if state == m {
+                    return Some(i - m);
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns the pattern.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn pattern(&self) -> &[T] {
+        &self.pattern
+    }
+}
+
+/// A polynomial hash of a string, as a [`Monoid`] under concatenation, that can also be
+/// stripped of a prefix.
+///
+/// Implemented by [`RangeHash`](crate::monoids::RangeHash) and
+/// [`DoubleRangeHash`], so that the hashes computed by
+/// [`RollingHash`] can be stored in, and compared with folds of, any tree of this crate.
+pub trait PolyHash: Monoid + Eq {
+    /// Returns the hash of the single character `c`.
+    fn new(c: u64) -> Self;
+
+    /// Returns the hash of `t`, given the hash `self` of `s + t`, the hash `prefix` of `s`,
+    /// and the hash `len` of any string of the same length as `t`.
+    fn strip_prefix(&self, prefix: &Self, len: &Self) -> Self;
+}
+
+/// Substring hashes of a static string.
+///
+/// Given a string `s` of length `n` and a [`PolyHash`] `H`, this data structure supports:
+/// - Substring hash: `hash(l..r)` returns the hash of `s[l..r]`
+/// - Longest common prefix: `lcp(i, j)` returns the length of the longest common prefix of
+///   `s[i..]` and `s[j..]`
+///
+/// Built in O(n) time, and hashes are returned in O(1) time. The hashes of the prefixes of
+/// `s` also carry the powers of the base, so no separate power table is needed. The default
+/// [`DoubleRangeHash`] hashes modulo `2^61 - 1` with two fixed bases; hashes of
+/// [`RangeHash`](crate::monoids::RangeHash) with a random `BASE` resist crafted inputs.
+///
+/// Hashes compose with [`op`](crate::algebra::Semigroup::op), so
+/// `hash(a..b).op(&hash(b..c)) == hash(a..c)`.
+#[derive(Clone, Debug)]
+pub struct RollingHash<H = DoubleRangeHash> {
+    /// Hash of every prefix `s[..i]`.
+    prefix: Vec<H>,
+}
+
+impl<H: PolyHash> RollingHash<H> {
+    /// Creates the substring hashes of `s`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn new<T: Copy + Into<u64>>(s: &[T]) -> Self {
+        let mut prefix = Vec::with_capacity(s.len() + 1);
+        prefix.push(H::id());
+        for &c in s {
+            let h = prefix.last().unwrap().op(&H::new(c.into()));
+            prefix.push(h);
+        }
+        Self { prefix }
+    }
+
+    /// Returns the hash of the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn hash(&self, range: impl std::ops::RangeBounds<usize>) -> H {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        self.prefix[r].strip_prefix(&self.prefix[l], &self.prefix[r - l])
+    }
+
+    /// Returns the length of the longest common prefix of the suffixes starting at `i` and
+    /// `j`, up to hash collisions.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > len()` or `j > len()` in debug builds.
+    pub fn lcp(&self, i: usize, j: usize) -> usize {
+        debug_assert!(
+            i <= self.len() && j <= self.len(),
+            "index out of bounds: i={}, j={}, len={}",
+            i,
+            j,
+            self.len(),
+        );
+        let (mut ok, mut ng) = (0, self.len() - i.max(j) + 1);
+        while ng - ok > 1 {
+            let k = ok + (ng - ok) / 2;
+            if self.hash(i..i + k) == self.hash(j..j + k) {
+                ok = k;
+            } else {
+                ng = k;
+            }
+        }
+        ok
+    }
+
+    /// Returns the length of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    /// Returns `true` if the string is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,278 @@
+/// Marker of an unfilled slot of the suffix array during induced sorting.
+const NONE: usize = usize::MAX;
+
+/// The suffix array of a string, with its LCP array.
+///
+/// Given a string `s` of length `n` over a totally ordered type `T`, this data structure
+/// provides:
+/// - Suffix array: `sa()[i]` is the start of the `i`-th smallest suffix, and `rank()` is its
+///   inverse
+/// - LCP array: `lcp()[i]` is the length of the longest common prefix of the suffixes
+///   starting at `sa()[i]` and `sa()[i + 1]`
+/// - Search: `find(p)` returns the range of suffix array indices of the suffixes starting
+///   with `p`, in O(m log n) time for a pattern of length `m`
+///
+/// The suffix array is built by SA-IS in O(n) time after compressing `T` to integers in
+/// O(n log n) time, or in O(n + upper) time by [`suffix_array`] for a string of integers
+/// already less than `upper`. The LCP array is built by Kasai's algorithm in O(n) time.
+///
+/// The longest common prefix of the suffixes starting at `i` and `j` is the minimum of
+/// `lcp()[rank()[i]..rank()[j]]` for `rank()[i] < rank()[j]`, so a
+/// [`SparseTable`](crate::sparse_table::SparseTable) of [`Min`](crate::monoids::Min) over the
+/// LCP array answers it in O(1) time.
+#[derive(Clone, Debug)]
+pub struct SuffixArray<T> {
+    s: Vec<T>,
+    sa: Vec<usize>,
+    rank: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl<T: Ord + Clone> SuffixArray<T> {
+    /// Creates the suffix array of `s`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n) to compress the alphabet, and O(n) otherwise
+    pub fn new(s: &[T]) -> Self {
+        let mut values: Vec<&T> = s.iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        let ids: Vec<usize> = s
+            .iter()
+            .map(|x| values.binary_search(&x).unwrap())
+            .collect();
+        let sa = suffix_array(&ids, values.len());
+        let mut rank = vec![0; s.len()];
+        for (i, &p) in sa.iter().enumerate() {
+            rank[p] = i;
+        }
+        let lcp = kasai(&ids, &sa, &rank);
+        Self {
+            s: s.to_vec(),
+            sa,
+            rank,
+            lcp,
+        }
+    }
+
+    /// Returns the range of suffix array indices of the suffixes starting with `pattern`.
+    ///
+    /// The number of occurrences of `pattern` is the length of the range, and their starts
+    /// are `sa()[range]`. The empty pattern gives `0..n`, one index per nonempty suffix.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log n)
+    pub fn find(&self, pattern: &[T]) -> std::ops::Range<usize> {
+        let prefix = |p: usize| &self.s[p..(p + pattern.len()).min(self.s.len())];
+        let l = self.sa.partition_point(|&p| prefix(p) < pattern);
+        let r = l + self.sa[l..].partition_point(|&p| prefix(p) == pattern);
+        l..r
+    }
+
+    /// Returns `true` if `pattern` occurs in the string.
+    ///
+    /// The empty pattern occurs in every string, including the empty one.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log n)
+    #[inline]
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        pattern.is_empty() || !self.find(pattern).is_empty()
+    }
+
+    /// Returns the suffix array: the starts of the suffixes in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn sa(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// Returns the inverse of the suffix array: the index of every suffix in it.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn rank(&self) -> &[usize] {
+        &self.rank
+    }
+
+    /// Returns the LCP array, of length `n - 1`: the length of the longest common prefix of
+    /// every two adjacent suffixes in the suffix array.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    /// Returns the length of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.s.len()
+    }
+
+    /// Returns `true` if the string is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Returns the suffix array of `s`, whose values are less than `upper`, built by SA-IS.
+///
+/// # Time complexity
+///
+/// O(n + upper)
+///
+/// # Panics
+///
+/// Panics if a value is at least `upper` in debug builds.
+pub fn suffix_array(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    if let Some(&x) = s.iter().max() {
+        debug_assert!(x < upper, "value out of bounds: x={}, upper={}", x, upper);
+    }
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    // `ls[i]` is `true` if the suffix at `i` is S-type, smaller than the suffix at `i + 1`.
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+    // Start of the L-type and S-type buckets of every value.
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if ls[i] {
+            sum_l[s[i] + 1] += 1;
+        } else {
+            sum_s[s[i]] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+    let mut sa = vec![NONE; n];
+    // Sorts all suffixes by induction from the LMS suffixes in the given order.
+    let induce = |sa: &mut [usize], lms: &[usize]| {
+        sa.fill(NONE);
+        let mut buf = sum_s.clone();
+        for &d in lms {
+            if d != n {
+                sa[buf[s[d]]] = d;
+                buf[s[d]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != NONE && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != NONE && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+    };
+    // Index of every LMS position among them, an S-type position after an L-type one.
+    let mut lms_map = vec![NONE; n + 1];
+    let mut lms = Vec::new();
+    for i in 1..n {
+        if !ls[i - 1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let m = lms.len();
+    induce(&mut sa, &lms);
+    if m > 0 {
+        // Name the LMS substrings in sorted order, and sort the LMS suffixes recursively.
+        let mut sorted_lms: Vec<usize> =
+            sa.iter().copied().filter(|&v| lms_map[v] != NONE).collect();
+        let mut rec_s = vec![0; m];
+        let mut rec_upper = 0;
+        rec_s[lms_map[sorted_lms[0]]] = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = lms.get(lms_map[l] + 1).copied().unwrap_or(n);
+            let end_r = lms.get(lms_map[r] + 1).copied().unwrap_or(n);
+            let mut same = true;
+            if end_l - l != end_r - r {
+                same = false;
+            } else {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || s[l] != s[r] {
+                    same = false;
+                }
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = suffix_array(&rec_s, rec_upper + 1);
+        for (x, &i) in sorted_lms.iter_mut().zip(&rec_sa) {
+            *x = lms[i];
+        }
+        induce(&mut sa, &sorted_lms);
+    }
+    sa
+}
+
+/// Returns the LCP array of `s` by Kasai's algorithm, given its suffix array and inverse.
+fn kasai(s: &[usize], sa: &[usize], rank: &[usize]) -> Vec<usize> {
+    let n = s.len();
+    let mut lcp = vec![0; n.saturating_sub(1)];
+    let mut h: usize = 0;
+    for i in 0..n {
+        h = h.saturating_sub(1);
+        if rank[i] == 0 {
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && s[i + h] == s[j + h] {
+            h += 1;
+        }
+        lcp[rank[i] - 1] = h;
+    }
+    lcp
+}
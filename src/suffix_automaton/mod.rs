@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+
+/// Marker of the missing suffix link of the root.
+const NIL: u32 = u32::MAX;
+
+/// A state of a suffix automaton, the class of substrings sharing their end positions.
+#[derive(Clone, Debug)]
+struct State<T> {
+    /// Length of the longest substring of the class.
+    len: usize,
+    /// State of the longest suffix outside the class, or `NIL` for the root.
+    link: u32,
+    next: BTreeMap<T, u32>,
+    /// Whether the state was split off another one, so that it ends no prefix.
+    cloned: bool,
+}
+
+/// The suffix automaton of a string, built online.
+///
+/// Given a string `s` of length `n` over a totally ordered type `T`, this data structure
+/// supports:
+/// - Online construction: `push(c)` appends `c` to the string
+/// - States: `max_len(v)`, `link(v)`, `next_state(v, &c)` and `transitions(v)` expose the
+///   minimal automaton accepting the suffixes of `s`, with at most `2n - 1` states
+/// - Queries: `contains(p)` and `count_distinct_substrings()`, and `endpos_sizes()` returns
+///   the number of occurrences of the substrings of every state
+///
+/// `push` runs in O(log σ) amortized time, for an alphabet of size `σ`. Every state is the
+/// class of the substrings ending at the same set of positions; they are the suffixes of its
+/// longest one down to a length of `max_len(link(v)) + 1`.
+///
+/// State 0 is the root, the class of the empty string.
+#[derive(Clone, Debug)]
+pub struct SuffixAutomaton<T> {
+    states: Vec<State<T>>,
+    /// State of the whole string.
+    last: u32,
+    /// Number of distinct nonempty substrings.
+    distinct: u64,
+}
+
+impl<T: Ord + Clone> SuffixAutomaton<T> {
+    /// Creates the suffix automaton of the empty string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            states: vec![State {
+                len: 0,
+                link: NIL,
+                next: BTreeMap::new(),
+                cloned: false,
+            }],
+            last: 0,
+            distinct: 0,
+        }
+    }
+
+    /// Creates the suffix automaton of `s`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log σ)
+    pub fn from_slice(s: &[T]) -> Self {
+        let mut res = Self::new();
+        res.states.reserve(2 * s.len());
+        for c in s {
+            res.push(c.clone());
+        }
+        res
+    }
+
+    /// Appends `c` to the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ) amortized
+    pub fn push(&mut self, c: T) {
+        let cur = self.states.len() as u32;
+        self.states.push(State {
+            len: self.states[self.last as usize].len + 1,
+            link: 0,
+            next: BTreeMap::new(),
+            cloned: false,
+        });
+        let mut p = self.last;
+        while p != NIL && !self.states[p as usize].next.contains_key(&c) {
+            self.states[p as usize].next.insert(c.clone(), cur);
+            p = self.states[p as usize].link;
+        }
+        if p != NIL {
+            let q = self.states[p as usize].next[&c];
+            if self.states[p as usize].len + 1 == self.states[q as usize].len {
+                self.states[cur as usize].link = q;
+            } else {
+                // Split the shorter substrings of `q` off into a clone.
+                let clone = self.states.len() as u32;
+                self.states.push(State {
+                    len: self.states[p as usize].len + 1,
+                    link: self.states[q as usize].link,
+                    next: self.states[q as usize].next.clone(),
+                    cloned: true,
+                });
+                while p != NIL && self.states[p as usize].next.get(&c) == Some(&q) {
+                    self.states[p as usize].next.insert(c.clone(), clone);
+                    p = self.states[p as usize].link;
+                }
+                self.states[q as usize].link = clone;
+                self.states[cur as usize].link = clone;
+            }
+        }
+        let link = self.states[cur as usize].link as usize;
+        self.distinct += (self.states[cur as usize].len - self.states[link].len) as u64;
+        self.last = cur;
+    }
+
+    /// Returns `true` if `pattern` is a substring of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    pub fn contains(&self, pattern: &[T]) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    /// Returns the state of `pattern`, or `None` if it is not a substring of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    pub fn find(&self, pattern: &[T]) -> Option<usize> {
+        pattern.iter().try_fold(0, |v, c| self.next_state(v, c))
+    }
+
+    /// Returns the number of distinct nonempty substrings of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn count_distinct_substrings(&self) -> u64 {
+        self.distinct
+    }
+
+    /// Returns the number of end positions of the substrings of every state, which is the
+    /// number of occurrences of each of them.
+    ///
+    /// The root gets `n + 1`, for the occurrences of the empty string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn endpos_sizes(&self) -> Vec<usize> {
+        let n = self.len();
+        // Sort the states by decreasing length, so that links come after their states.
+        let mut start = vec![0; n + 2];
+        for state in &self.states {
+            start[state.len + 1] += 1;
+        }
+        for i in 0..=n {
+            start[i + 1] += start[i];
+        }
+        let mut order = vec![0; self.states.len()];
+        for (v, state) in self.states.iter().enumerate() {
+            order[start[state.len]] = v;
+            start[state.len] += 1;
+        }
+        let mut res: Vec<usize> = self
+            .states
+            .iter()
+            .map(|state| usize::from(!state.cloned))
+            .collect();
+        for &v in order.iter().rev() {
+            let link = self.states[v].link;
+            if link != NIL {
+                res[link as usize] += res[v];
+            }
+        }
+        res
+    }
+
+    /// Returns the state reached from `state` by reading `c`, or `None` if there is no
+    /// transition.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn next_state(&self, state: usize, c: &T) -> Option<usize> {
+        self.states[state].next.get(c).map(|&v| v as usize)
+    }
+
+    /// Returns an iterator over the transitions of `state`, in ascending order of symbols.
+    ///
+    /// # Time complexity
+    ///
+    /// O(number of transitions) for a full traversal
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    pub fn transitions(&self, state: usize) -> impl Iterator<Item = (&T, usize)> {
+        self.states[state]
+            .next
+            .iter()
+            .map(|(c, &v)| (c, v as usize))
+    }
+
+    /// Returns the suffix link of `state`, the state of the longest suffix of its substrings
+    /// in another state, or `None` for the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn link(&self, state: usize) -> Option<usize> {
+        match self.states[state].link {
+            NIL => None,
+            link => Some(link as usize),
+        }
+    }
+
+    /// Returns the length of the longest substring of `state`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state >= num_states()`.
+    #[inline]
+    pub fn max_len(&self, state: usize) -> usize {
+        self.states[state].len
+    }
+
+    /// Returns the state of the whole string, whose suffix links lead through the states of
+    /// all its suffixes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn last(&self) -> usize {
+        self.last as usize
+    }
+
+    /// Returns the number of states, including the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the length of the string.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.states[self.last as usize].len
+    }
+
+    /// Returns `true` if the string is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord + Clone> Default for SuffixAutomaton<T> {
+    /// Creates the suffix automaton of the empty string.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for SuffixAutomaton<T> {
+    /// Creates the suffix automaton of the string of an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log σ)
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for c in iter {
+            res.push(c);
+        }
+        res
+    }
+}
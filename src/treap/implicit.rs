@@ -0,0 +1,678 @@
+use crate::algebra::{Action, Monoid};
+use crate::treap::priority;
+
+/// The pending action of a node: `()` for trees without actions, `Option<F>` otherwise.
+trait Pending<S>: Clone {
+    /// Returns the identity action.
+    fn none() -> Self;
+
+    /// Returns `true` if this is the identity action.
+    fn is_none(&self) -> bool;
+
+    /// Applies the action to `s`.
+    fn act(&self, s: &S) -> S;
+
+    /// Returns the action applying `inner` first, then `self`.
+    fn after(&self, inner: &Self) -> Self;
+}
+
+impl<S: Clone> Pending<S> for () {
+    #[inline(always)]
+    fn none() -> Self {}
+
+    #[inline(always)]
+    fn is_none(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn act(&self, s: &S) -> S {
+        s.clone()
+    }
+
+    #[inline(always)]
+    fn after(&self, _: &Self) -> Self {}
+}
+
+impl<S: Monoid, F: Action<S>> Pending<S> for Option<F> {
+    #[inline(always)]
+    fn none() -> Self {
+        None
+    }
+
+    #[inline(always)]
+    fn is_none(&self) -> bool {
+        self.is_none()
+    }
+
+    #[inline(always)]
+    fn act(&self, s: &S) -> S {
+        match self {
+            Some(f) => f.act(s),
+            None => s.clone(),
+        }
+    }
+
+    #[inline(always)]
+    fn after(&self, inner: &Self) -> Self {
+        match (self, inner) {
+            (Some(f), Some(g)) => Some(F::op(f, g)),
+            (Some(_), None) => self.clone(),
+            (None, _) => inner.clone(),
+        }
+    }
+}
+
+type Link<S, L> = Option<Box<Node<S, L>>>;
+
+/// A node of a treap, keyed by its position in the in-order traversal.
+#[derive(Clone, Debug)]
+struct Node<S, L> {
+    val: S,
+    /// `op` of the subtree in order, with `lazy` and `rev` already applied.
+    sum: S,
+    /// `op` of the subtree in reverse order, with `lazy` and `rev` already applied.
+    rsum: S,
+    /// Action pending to be propagated to the children.
+    lazy: L,
+    /// Whether the children are pending to be swapped and reversed.
+    rev: bool,
+    size: usize,
+    pri: u64,
+    left: Link<S, L>,
+    right: Link<S, L>,
+}
+
+#[inline(always)]
+fn size<S, L>(t: &Link<S, L>) -> usize {
+    t.as_ref().map_or(0, |t| t.size)
+}
+
+impl<S: Monoid, L: Pending<S>> Node<S, L> {
+    fn new(x: S) -> Box<Self> {
+        Box::new(Self {
+            sum: x.clone(),
+            rsum: x.clone(),
+            val: x,
+            lazy: L::none(),
+            rev: false,
+            size: 1,
+            pri: priority(),
+            left: None,
+            right: None,
+        })
+    }
+
+    /// Recomputes the size and folds from the children.
+    #[inline]
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+        let (mut sum, mut rsum) = (self.val.clone(), self.val.clone());
+        if let Some(l) = &self.left {
+            sum = S::op(&l.sum, &sum);
+            rsum = S::op(&rsum, &l.rsum);
+        }
+        if let Some(r) = &self.right {
+            sum = S::op(&sum, &r.sum);
+            rsum = S::op(&r.rsum, &rsum);
+        }
+        self.sum = sum;
+        self.rsum = rsum;
+    }
+
+    /// Reverses the subtree, deferring it for the children.
+    #[inline]
+    fn toggle(&mut self) {
+        std::mem::swap(&mut self.sum, &mut self.rsum);
+        self.rev ^= true;
+    }
+
+    /// Applies `f` to the subtree, deferring it for the children.
+    #[inline]
+    fn all_apply(&mut self, f: &L) {
+        self.val = f.act(&self.val);
+        self.sum = f.act(&self.sum);
+        self.rsum = f.act(&self.rsum);
+        self.lazy = f.after(&self.lazy);
+    }
+
+    /// Propagates the pending reversal and action to the children.
+    #[inline]
+    fn push(&mut self) {
+        if std::mem::take(&mut self.rev) {
+            std::mem::swap(&mut self.left, &mut self.right);
+            for c in [&mut self.left, &mut self.right].into_iter().flatten() {
+                c.toggle();
+            }
+        }
+        if !self.lazy.is_none() {
+            let f = std::mem::replace(&mut self.lazy, L::none());
+            for c in [&mut self.left, &mut self.right].into_iter().flatten() {
+                c.all_apply(&f);
+            }
+        }
+    }
+}
+
+fn merge<S: Monoid, L: Pending<S>>(a: Link<S, L>, b: Link<S, L>) -> Link<S, L> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.pri > b.pri {
+                a.push();
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.push();
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+/// Splits `t` into its first `k` elements and the rest.
+fn split<S: Monoid, L: Pending<S>>(t: Link<S, L>, k: usize) -> (Link<S, L>, Link<S, L>) {
+    let Some(mut t) = t else {
+        return (None, None);
+    };
+    t.push();
+    let ls = size(&t.left);
+    if k <= ls {
+        let (a, b) = split(t.left.take(), k);
+        t.left = b;
+        t.update();
+        (a, Some(t))
+    } else {
+        let (a, b) = split(t.right.take(), k - ls - 1);
+        t.right = a;
+        t.update();
+        (Some(t), b)
+    }
+}
+
+/// The operations shared by both treaps, over the root of the tree.
+#[derive(Clone, Debug)]
+struct Core<S, L> {
+    root: Link<S, L>,
+}
+
+impl<S: Monoid, L: Pending<S>> Core<S, L> {
+    /// Builds the treap as a Cartesian tree of random priorities, in O(n) time.
+    fn from_vec(v: Vec<S>) -> Self {
+        // The right spine of the tree built so far, from the root down.
+        let mut spine: Vec<Box<Node<S, L>>> = Vec::new();
+        for x in v {
+            let mut node = Node::new(x);
+            let mut last: Link<S, L> = None;
+            while spine.last().is_some_and(|t| t.pri < node.pri) {
+                let mut t = spine.pop().unwrap();
+                t.right = last;
+                t.update();
+                last = Some(t);
+            }
+            node.left = last;
+            spine.push(node);
+        }
+        let mut last = None;
+        while let Some(mut t) = spine.pop() {
+            t.right = last;
+            t.update();
+            last = Some(t);
+        }
+        Self { root: last }
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+
+    fn insert(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i <= self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let (a, b) = split(self.root.take(), i);
+        self.root = merge(merge(a, Some(Node::new(x))), b);
+    }
+
+    fn remove(&mut self, i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let (a, b) = split(self.root.take(), i);
+        let (m, b) = split(b, 1);
+        self.root = merge(a, b);
+        m.unwrap().val
+    }
+
+    /// Walks down to the element at index `i`, applying the pending reversals and actions of
+    /// its ancestors on the fly.
+    fn get(&self, mut i: usize) -> S {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        let mut t = self.root.as_deref();
+        // Whether the subtree of `t` is to be mirrored, and the action to be applied to it.
+        let mut flip = false;
+        let mut acc = L::none();
+        while let Some(node) = t {
+            let flip_children = flip ^ node.rev;
+            let (l, r) = if flip_children {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            let ls = size(l);
+            if i == ls {
+                return acc.act(&node.val);
+            }
+            if i < ls {
+                t = l.as_deref();
+            } else {
+                i -= ls + 1;
+                t = r.as_deref();
+            }
+            flip = flip_children;
+            acc = acc.after(&node.lazy);
+        }
+        unreachable!()
+    }
+
+    fn set(&mut self, i: usize, x: S) {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len(),
+        );
+        fn rec<S: Monoid, L: Pending<S>>(t: &mut Node<S, L>, i: usize, x: S) {
+            t.push();
+            let ls = size(&t.left);
+            if i < ls {
+                rec(t.left.as_mut().unwrap(), i, x);
+            } else if i == ls {
+                t.val = x;
+            } else {
+                rec(t.right.as_mut().unwrap(), i - ls - 1, x);
+            }
+            t.update();
+        }
+        rec(self.root.as_mut().unwrap(), i, x);
+    }
+
+    /// Runs `f` on the subtree holding the given range, which may be empty.
+    fn with_range<R>(
+        &mut self,
+        range: impl std::ops::RangeBounds<usize>,
+        f: impl FnOnce(&mut Link<S, L>) -> R,
+    ) -> R {
+        let (l, r) = self.bounds(range);
+        let (a, b) = split(self.root.take(), r);
+        let (a, mut m) = split(a, l);
+        let res = f(&mut m);
+        self.root = merge(merge(a, m), b);
+        res
+    }
+
+    fn to_vec(&self) -> Vec<S> {
+        fn rec<S: Monoid, L: Pending<S>>(t: &Link<S, L>, flip: bool, acc: &L, out: &mut Vec<S>) {
+            let Some(node) = t else {
+                return;
+            };
+            let flip_children = flip ^ node.rev;
+            let (l, r) = if flip_children {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            let inner = acc.after(&node.lazy);
+            rec(l, flip_children, &inner, out);
+            out.push(acc.act(&node.val));
+            rec(r, flip_children, &inner, out);
+        }
+        let mut out = Vec::with_capacity(self.len());
+        rec(&self.root, false, &L::none(), &mut out);
+        out
+    }
+}
+
+/// Implements the operations shared by both treaps.
+macro_rules! impl_treap {
+    ([$($g:tt)*] $t:ty) => {
+        impl<$($g)*> $t {
+            /// Creates a new empty sequence.
+            ///
+            /// # Time complexity
+            ///
+            /// O(1)
+            pub fn new() -> Self {
+                Self {
+                    core: Core { root: None },
+                }
+            }
+
+            /// Creates a new sequence from a vec.
+            ///
+            /// # Time complexity
+            ///
+            /// O(n)
+            pub fn from_vec(v: Vec<S>) -> Self {
+                Self {
+                    core: Core::from_vec(v),
+                }
+            }
+
+            /// Creates a new sequence from a slice.
+            ///
+            /// # Time complexity
+            ///
+            /// O(n)
+            pub fn from_slice(v: &[S]) -> Self {
+                Self::from_vec(v.to_vec())
+            }
+
+            /// Creates a new sequence with `n` elements, where the element at index `i` is
+            /// `f(i)`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(n)
+            pub fn from_fn<G>(n: usize, f: G) -> Self
+            where
+                G: FnMut(usize) -> S,
+            {
+                Self::from_vec((0..n).map(f).collect())
+            }
+
+            /// Inserts `x` at index `i`, shifting the elements after it to the right.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if `i > len()` in debug builds.
+            #[inline]
+            pub fn insert(&mut self, i: usize, x: S) {
+                self.core.insert(i, x);
+            }
+
+            /// Appends `x` at the end.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            #[inline]
+            pub fn push(&mut self, x: S) {
+                self.core.insert(self.len(), x);
+            }
+
+            /// Removes and returns the element at index `i`, shifting the elements after it
+            /// to the left.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if `i >= len()` in debug builds.
+            #[inline]
+            pub fn remove(&mut self, i: usize) -> S {
+                self.core.remove(i)
+            }
+
+            /// Returns the value at index `i`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if `i >= len()` in debug builds.
+            #[inline]
+            pub fn get(&self, i: usize) -> S {
+                self.core.get(i)
+            }
+
+            /// Sets the value at index `i` to `x`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if `i >= len()` in debug builds.
+            #[inline]
+            pub fn set(&mut self, i: usize, x: S) {
+                self.core.set(i, x);
+            }
+
+            /// Returns `op(a[l], a[l+1], ..., a[r-1])` for the given range.
+            ///
+            /// Returns `S::id()` if the range is empty. The range is split off and merged
+            /// back, so this takes `&mut self`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if the range is invalid or out of bounds in debug builds.
+            #[inline]
+            pub fn range_fold(&mut self, range: impl std::ops::RangeBounds<usize>) -> S {
+                self.core
+                    .with_range(range, |t| t.as_ref().map_or_else(S::id, |t| t.sum.clone()))
+            }
+
+            /// Returns `op(a[0], a[1], ..., a[n-1])`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(1)
+            #[inline]
+            pub fn all_fold(&self) -> S {
+                self.core.root.as_ref().map_or_else(S::id, |t| t.sum.clone())
+            }
+
+            /// Reverses the elements in the given range.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if the range is invalid or out of bounds in debug builds.
+            #[inline]
+            pub fn reverse(&mut self, range: impl std::ops::RangeBounds<usize>) {
+                self.core.with_range(range, |t| {
+                    if let Some(t) = t {
+                        t.toggle();
+                    }
+                });
+            }
+
+            /// Splits the sequence into its first `at` elements and the rest.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            ///
+            /// # Panics
+            ///
+            /// Panics if `at > len()` in debug builds.
+            #[inline]
+            pub fn split(self, at: usize) -> (Self, Self) {
+                debug_assert!(
+                    at <= self.len(),
+                    "index out of bounds: at={}, len={}",
+                    at,
+                    self.len(),
+                );
+                let (a, b) = split(self.core.root, at);
+                (Self { core: Core { root: a } }, Self { core: Core { root: b } })
+            }
+
+            /// Concatenates two sequences, `a` followed by `b`.
+            ///
+            /// # Time complexity
+            ///
+            /// O(log n) expected
+            #[inline]
+            pub fn merge(a: Self, b: Self) -> Self {
+                Self {
+                    core: Core {
+                        root: merge(a.core.root, b.core.root),
+                    },
+                }
+            }
+
+            /// Returns the elements as a vec.
+            ///
+            /// # Time complexity
+            ///
+            /// O(n)
+            #[inline]
+            pub fn to_vec(&self) -> Vec<S> {
+                self.core.to_vec()
+            }
+
+            /// Returns the number of elements.
+            ///
+            /// # Time complexity
+            ///
+            /// O(1)
+            #[inline(always)]
+            pub fn len(&self) -> usize {
+                self.core.len()
+            }
+
+            /// Returns `true` if the sequence is empty.
+            ///
+            /// # Time complexity
+            ///
+            /// O(1)
+            #[inline(always)]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+
+        impl<$($g)*> Default for $t {
+            /// Creates a new empty sequence.
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<$($g)*> FromIterator<S> for $t {
+            /// Creates a new sequence from an iterator.
+            ///
+            /// # Time complexity
+            ///
+            /// O(n)
+            fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+                Self::from_vec(iter.into_iter().collect())
+            }
+        }
+    };
+}
+
+/// A sequence supporting insertion, deletion and range folds at arbitrary positions.
+///
+/// Given a monoid `(S, op, id)`, this data structure supports:
+/// - Insertion and deletion: `insert(i, x)` and `remove(i)` at any index
+/// - Point update: `set(i, x)` sets `a[i] = x`
+/// - Range query: `range_fold(l..r)` returns `op(a[l], ..., a[r-1])`
+/// - Range reversal: `reverse(l..r)` reverses `a[l..r]`
+/// - Split and concatenation: `split(at)` and `merge(a, b)`
+///
+/// All operations run in O(log n) expected time. This is a treap keyed by position: each
+/// node stores the size and the folds in both directions of its subtree, so that `op` need
+/// not be commutative, even under reversal. [`LazyImplicitTreap`] additionally supports
+/// range actions.
+#[derive(Clone, Debug)]
+pub struct ImplicitTreap<S: Monoid> {
+    core: Core<S, ()>,
+}
+
+impl_treap!([S: Monoid] ImplicitTreap<S>);
+
+/// A sequence supporting insertion, deletion, range actions and range folds at arbitrary
+/// positions.
+///
+/// Given a monoid `(S, op, id)` and an action monoid `(F, compose, id)`, this data structure
+/// supports all operations of [`ImplicitTreap`], and:
+/// - Range action: `range_apply(l..r, f)` applies `f` to all elements in range
+///
+/// All operations run in O(log n) expected time.
+#[derive(Clone, Debug)]
+pub struct LazyImplicitTreap<S: Monoid, F: Action<S>> {
+    core: Core<S, Option<F>>,
+}
+
+impl_treap!([S: Monoid, F: Action<S>] LazyImplicitTreap<S, F>);
+
+impl<S: Monoid, F: Action<S>> LazyImplicitTreap<S, F> {
+    /// Applies action `f` to all elements in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    #[inline]
+    pub fn range_apply(&mut self, range: impl std::ops::RangeBounds<usize>, f: F) {
+        self.core.with_range(range, |t| {
+            if let Some(t) = t {
+                t.all_apply(&Some(f));
+            }
+        });
+    }
+}
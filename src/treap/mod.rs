@@ -0,0 +1,22 @@
+pub mod implicit;
+pub mod ordered;
+pub mod persistent;
+
+pub use implicit::{ImplicitTreap, LazyImplicitTreap};
+pub use ordered::{TreapMap, TreapSet};
+pub use persistent::PersistentTreapMap;
+
+/// Returns a pseudo-random priority for a new treap node.
+///
+/// Priorities only need to be independent of the operations performed, so a global
+/// splitmix64 sequence suffices.
+fn priority() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static STATE: AtomicU64 = AtomicU64::new(0);
+    let mut z = STATE
+        .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+        .wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
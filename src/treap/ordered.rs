@@ -0,0 +1,582 @@
+use crate::treap::priority;
+use std::cmp::Ordering;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// A node of a treap, ordered by key.
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    val: V,
+    size: usize,
+    pri: u64,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[inline(always)]
+fn size<K, V>(t: &Link<K, V>) -> usize {
+    t.as_ref().map_or(0, |t| t.size)
+}
+
+impl<K, V> Node<K, V> {
+    #[inline]
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn merge<K, V>(a: Link<K, V>, b: Link<K, V>) -> Link<K, V> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.pri > b.pri {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+/// Splits `t` into the keys less than `key` and the rest.
+fn split<K: Ord, V>(t: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    let Some(mut t) = t else {
+        return (None, None);
+    };
+    if *key <= t.key {
+        let (a, b) = split(t.left.take(), key);
+        t.left = b;
+        t.update();
+        (a, Some(t))
+    } else {
+        let (a, b) = split(t.right.take(), key);
+        t.right = a;
+        t.update();
+        (Some(t), b)
+    }
+}
+
+fn remove<K: Ord, V>(t: &mut Link<K, V>, key: &K) -> Option<V> {
+    let node = t.as_mut()?;
+    let res = match key.cmp(&node.key) {
+        Ordering::Less => remove(&mut node.left, key),
+        Ordering::Greater => remove(&mut node.right, key),
+        Ordering::Equal => {
+            let node = t.take().unwrap();
+            *t = merge(node.left, node.right);
+            return Some(node.val);
+        }
+    };
+    if res.is_some() {
+        node.size -= 1;
+    }
+    res
+}
+
+/// An in-order iterator over the entries of a treap whose keys lie in a range.
+struct Range<'a, K, V, R> {
+    /// The nodes whose key and right subtree are yet to be visited, the next one on top.
+    stack: Vec<&'a Node<K, V>>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: std::ops::RangeBounds<K>> Range<'a, K, V, R> {
+    fn new(root: &'a Link<K, V>, range: R) -> Self {
+        let mut stack = Vec::new();
+        let mut t = root.as_deref();
+        while let Some(node) = t {
+            let after_start = match range.start_bound() {
+                std::ops::Bound::Unbounded => true,
+                std::ops::Bound::Included(x) => *x <= node.key,
+                std::ops::Bound::Excluded(x) => *x < node.key,
+            };
+            if after_start {
+                stack.push(node);
+                t = node.left.as_deref();
+            } else {
+                t = node.right.as_deref();
+            }
+        }
+        Self { stack, range }
+    }
+}
+
+impl<'a, K: Ord, V, R: std::ops::RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        let before_end = match self.range.end_bound() {
+            std::ops::Bound::Unbounded => true,
+            std::ops::Bound::Included(x) => node.key <= *x,
+            std::ops::Bound::Excluded(x) => node.key < *x,
+        };
+        if !before_end {
+            self.stack.clear();
+            return None;
+        }
+        let mut t = node.right.as_deref();
+        while let Some(c) = t {
+            self.stack.push(c);
+            t = c.left.as_deref();
+        }
+        Some((&node.key, &node.val))
+    }
+}
+
+/// An ordered map supporting order statistics.
+///
+/// Given keys of a totally ordered type `K`, this data structure supports:
+/// - Insertion and deletion: `insert(key, value)` and `remove(&key)`
+/// - Lookup: `get(&key)` returns the value of `key`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest entry, and `rank(&key)` returns
+///   the number of keys less than `key`
+/// - Range iteration: `range(lo..hi)` iterates over the entries with keys in `lo..hi`
+/// - Split and concatenation: `split_at_key(&key)` and `merge(a, b)`
+///
+/// All operations run in O(log n) expected time, and iteration takes O(log n) expected time
+/// plus O(1) amortized time per entry. This is a treap storing the size of every subtree,
+/// which [`BTreeMap`](std::collections::BTreeMap) does not provide.
+#[derive(Clone)]
+pub struct TreapMap<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord, V> TreapMap<K, V> {
+    /// Creates a new empty map.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Inserts `value` at `key`, and returns the previous value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(v) = self.get_mut(&key) {
+            return Some(std::mem::replace(v, value));
+        }
+        let (a, b) = split(self.root.take(), &key);
+        let node = Box::new(Node {
+            key,
+            val: value,
+            size: 1,
+            pri: priority(),
+            left: None,
+            right: None,
+        });
+        self.root = merge(merge(a, Some(node)), b);
+        None
+    }
+
+    /// Removes `key`, and returns its value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        remove(&mut self.root, key)
+    }
+
+    /// Returns the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            t = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.val),
+            };
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut t = self.root.as_deref_mut();
+        while let Some(node) = t {
+            t = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.val),
+            };
+        }
+        None
+    }
+
+    /// Returns `true` if the map contains `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest entry, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn kth(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            let ls = size(&node.left);
+            match k.cmp(&ls) {
+                Ordering::Less => t = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.val)),
+                Ordering::Greater => {
+                    k -= ls + 1;
+                    t = node.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of keys less than `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn rank(&self, key: &K) -> usize {
+        let mut res = 0;
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            if *key <= node.key {
+                t = node.left.as_deref();
+            } else {
+                res += size(&node.left) + 1;
+                t = node.right.as_deref();
+            }
+        }
+        res
+    }
+
+    /// Returns the entry with the smallest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.kth(0)
+    }
+
+    /// Returns the entry with the largest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.kth(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the entries with keys in the given range, in ascending order
+    /// of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected, plus O(1) amortized per entry
+    #[inline]
+    pub fn range(&self, range: impl std::ops::RangeBounds<K>) -> impl Iterator<Item = (&K, &V)> {
+        Range::new(&self.root, range)
+    }
+
+    /// Returns an iterator over the entries, in ascending order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.range(..)
+    }
+
+    /// Splits the map into the keys less than `key` and the rest.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn split_at_key(self, key: &K) -> (Self, Self) {
+        let (a, b) = split(self.root, key);
+        (Self { root: a }, Self { root: b })
+    }
+
+    /// Concatenates two maps, where every key of `a` is less than every key of `b`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key of `a` is not less than a key of `b` in debug builds.
+    pub fn merge(a: Self, b: Self) -> Self {
+        debug_assert!(
+            match (a.last(), b.first()) {
+                (Some((x, _)), Some((y, _))) => x < y,
+                _ => true,
+            },
+            "keys of the left map must be less than keys of the right map",
+        );
+        Self {
+            root: merge(a.root, b.root),
+        }
+    }
+
+    /// Returns the number of entries.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Ord, V> Default for TreapMap<K, V> {
+    /// Creates a new empty map.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for TreapMap<K, V> {
+    /// Creates a new map from an iterator, keeping the last value of duplicate keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n) expected
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for (k, v) in iter {
+            res.insert(k, v);
+        }
+        res
+    }
+}
+
+impl<K: Ord + std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for TreapMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An ordered set supporting order statistics.
+///
+/// This is a [`TreapMap`] without values, with the same operations and complexities.
+#[derive(Clone)]
+pub struct TreapSet<K> {
+    map: TreapMap<K, ()>,
+}
+
+impl<K: Ord> TreapSet<K> {
+    /// Creates a new empty set.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            map: TreapMap::new(),
+        }
+    }
+
+    /// Inserts `key`, and returns `true` if it was not present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    /// Removes `key`, and returns `true` if it was present.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    /// Returns `true` if the set contains `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest key, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn kth(&self, k: usize) -> Option<&K> {
+        self.map.kth(k).map(|(k, _)| k)
+    }
+
+    /// Returns the number of keys less than `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn rank(&self, key: &K) -> usize {
+        self.map.rank(key)
+    }
+
+    /// Returns the smallest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn first(&self) -> Option<&K> {
+        self.map.first().map(|(k, _)| k)
+    }
+
+    /// Returns the largest key, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn last(&self) -> Option<&K> {
+        self.map.last().map(|(k, _)| k)
+    }
+
+    /// Returns an iterator over the keys in the given range, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected, plus O(1) amortized per key
+    #[inline]
+    pub fn range(&self, range: impl std::ops::RangeBounds<K>) -> impl Iterator<Item = &K> {
+        self.map.range(range).map(|(k, _)| k)
+    }
+
+    /// Returns an iterator over the keys, in ascending order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        self.range(..)
+    }
+
+    /// Splits the set into the keys less than `key` and the rest.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn split_at_key(self, key: &K) -> (Self, Self) {
+        let (a, b) = self.map.split_at_key(key);
+        (Self { map: a }, Self { map: b })
+    }
+
+    /// Concatenates two sets, where every key of `a` is less than every key of `b`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key of `a` is not less than a key of `b` in debug builds.
+    #[inline]
+    pub fn merge(a: Self, b: Self) -> Self {
+        Self {
+            map: TreapMap::merge(a.map, b.map),
+        }
+    }
+
+    /// Returns the number of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Ord> Default for TreapSet<K> {
+    /// Creates a new empty set.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for TreapSet<K> {
+    /// Creates a new set from an iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n) expected
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        Self {
+            map: iter.into_iter().map(|k| (k, ())).collect(),
+        }
+    }
+}
+
+impl<K: Ord + std::fmt::Debug> std::fmt::Debug for TreapSet<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
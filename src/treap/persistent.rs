@@ -0,0 +1,317 @@
+use crate::treap::priority;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+type Link<K, V> = Option<Rc<Node<K, V>>>;
+
+/// An immutable node of a treap, ordered by key and shared between versions.
+struct Node<K, V> {
+    key: K,
+    val: V,
+    size: usize,
+    pri: u64,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[inline(always)]
+fn size<K, V>(t: &Link<K, V>) -> usize {
+    t.as_ref().map_or(0, |t| t.size)
+}
+
+impl<K: Clone, V: Clone> Node<K, V> {
+    /// Returns a copy of this node with the given children.
+    #[inline]
+    fn with_children(&self, left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+        Some(Rc::new(Self {
+            key: self.key.clone(),
+            val: self.val.clone(),
+            size: 1 + size(&left) + size(&right),
+            pri: self.pri,
+            left,
+            right,
+        }))
+    }
+}
+
+fn merge<K: Clone, V: Clone>(a: &Link<K, V>, b: &Link<K, V>) -> Link<K, V> {
+    match (a, b) {
+        (None, b) => b.clone(),
+        (a, None) => a.clone(),
+        (Some(x), Some(y)) => {
+            if x.pri > y.pri {
+                x.with_children(x.left.clone(), merge(&x.right, b))
+            } else {
+                y.with_children(merge(a, &y.left), y.right.clone())
+            }
+        }
+    }
+}
+
+/// Splits `t`, which does not contain `key`, into the keys less than `key` and the rest.
+fn split<K: Ord + Clone, V: Clone>(t: &Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    let Some(node) = t else {
+        return (None, None);
+    };
+    if *key < node.key {
+        let (a, b) = split(&node.left, key);
+        (a, node.with_children(b, node.right.clone()))
+    } else {
+        let (a, b) = split(&node.right, key);
+        (node.with_children(node.left.clone(), a), b)
+    }
+}
+
+/// Returns `t` with `key` inserted, where `key` is not in `t`.
+fn insert<K: Ord + Clone, V: Clone>(t: &Link<K, V>, key: K, val: V, pri: u64) -> Link<K, V> {
+    match t {
+        Some(node) if node.pri >= pri => {
+            if key < node.key {
+                node.with_children(insert(&node.left, key, val, pri), node.right.clone())
+            } else {
+                node.with_children(node.left.clone(), insert(&node.right, key, val, pri))
+            }
+        }
+        _ => {
+            let (left, right) = split(t, &key);
+            Some(Rc::new(Node {
+                size: 1 + size(&left) + size(&right),
+                key,
+                val,
+                pri,
+                left,
+                right,
+            }))
+        }
+    }
+}
+
+/// Returns `t` with the value of `key` replaced, where `key` is in `t`.
+fn replace<K: Ord + Clone, V: Clone>(t: &Link<K, V>, key: &K, val: V) -> Link<K, V> {
+    let node = t.as_ref().unwrap();
+    match key.cmp(&node.key) {
+        Ordering::Less => node.with_children(replace(&node.left, key, val), node.right.clone()),
+        Ordering::Greater => node.with_children(node.left.clone(), replace(&node.right, key, val)),
+        Ordering::Equal => Some(Rc::new(Node {
+            key: node.key.clone(),
+            val,
+            size: node.size,
+            pri: node.pri,
+            left: node.left.clone(),
+            right: node.right.clone(),
+        })),
+    }
+}
+
+/// Returns `t` with `key` removed, where `key` is in `t`.
+fn remove<K: Ord + Clone, V: Clone>(t: &Link<K, V>, key: &K) -> Link<K, V> {
+    let node = t.as_ref().unwrap();
+    match key.cmp(&node.key) {
+        Ordering::Less => node.with_children(remove(&node.left, key), node.right.clone()),
+        Ordering::Greater => node.with_children(node.left.clone(), remove(&node.right, key)),
+        Ordering::Equal => merge(&node.left, &node.right),
+    }
+}
+
+/// A persistent ordered map supporting order statistics.
+///
+/// Every update leaves the map unchanged and returns a new version, so that all versions
+/// remain available. Given keys of a totally ordered type `K`, this data structure supports:
+/// - Insertion and deletion: `insert(key, value)` and `remove(&key)` return a new version
+/// - Lookup: `get(&key)` returns the value of `key`
+/// - Order statistics: `kth(k)` returns the `k`-th smallest entry, and `rank(&key)` returns
+///   the number of keys less than `key`
+///
+/// All operations run in O(log n) expected time, and cloning a version in O(1) time. This is
+/// a treap of reference-counted nodes: an update copies the O(log n) expected nodes on the
+/// paths it modifies and shares the rest with the previous version.
+pub struct PersistentTreapMap<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K, V> Clone for PersistentTreapMap<K, V> {
+    /// Returns the same version, sharing every node.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentTreapMap<K, V> {
+    /// Creates a new empty map.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns a new version with `value` at `key`, replacing the previous value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[must_use]
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let root = if self.contains_key(&key) {
+            replace(&self.root, &key, value)
+        } else {
+            insert(&self.root, key, value, priority())
+        };
+        Self { root }
+    }
+
+    /// Returns a new version without `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[must_use]
+    pub fn remove(&self, key: &K) -> Self {
+        if !self.contains_key(key) {
+            return self.clone();
+        }
+        Self {
+            root: remove(&self.root, key),
+        }
+    }
+
+    /// Returns the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            t = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.val),
+            };
+        }
+        None
+    }
+
+    /// Returns `true` if the map contains `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest entry, or `None` if `k >= len()`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn kth(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            let ls = size(&node.left);
+            match k.cmp(&ls) {
+                Ordering::Less => t = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.val)),
+                Ordering::Greater => {
+                    k -= ls + 1;
+                    t = node.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of keys less than `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) expected
+    pub fn rank(&self, key: &K) -> usize {
+        let mut res = 0;
+        let mut t = self.root.as_deref();
+        while let Some(node) = t {
+            if *key <= node.key {
+                t = node.left.as_deref();
+            } else {
+                res += size(&node.left) + 1;
+                t = node.right.as_deref();
+            }
+        }
+        res
+    }
+
+    /// Returns an iterator over the entries, in ascending order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let mut stack = Vec::new();
+        let mut t = self.root.as_deref();
+        std::iter::from_fn(move || {
+            while let Some(node) = t {
+                stack.push(node);
+                t = node.left.as_deref();
+            }
+            let node = stack.pop()?;
+            t = node.right.as_deref();
+            Some((&node.key, &node.val))
+        })
+    }
+
+    /// Returns the number of entries.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Default for PersistentTreapMap<K, V> {
+    /// Creates a new empty map.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> FromIterator<(K, V)> for PersistentTreapMap<K, V> {
+    /// Creates a new map from an iterator, keeping the last value of duplicate keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log n) expected
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |map, (k, v)| map.insert(k, v))
+    }
+}
+
+impl<K: Ord + Clone + std::fmt::Debug, V: Clone + std::fmt::Debug> std::fmt::Debug
+    for PersistentTreapMap<K, V>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+/// Sentinel parent of the root.
+const NONE: usize = usize::MAX;
+
+/// An Euler tour of a rooted tree, flattening every subtree into a contiguous range.
+///
+/// Every vertex `v` is entered at time `tin(v)` and left at time `tout(v)`, so that the
+/// subtree of `v` is exactly the vertices with entry times in `tin(v)..tout(v)`. Storing the
+/// value of vertex `v` at index `tin(v)`:
+/// - Subtree fold: fold `subtree_range(v)` in a `FenwickTree` or `SegmentTree`
+/// - Subtree update: apply to `subtree_range(v)` in a `LazySegmentTree`
+/// - Path to root: for a value `x` on each vertex `v`, operating `x` at `tin(v)` and its
+///   inverse at `tout(v)` in a `FenwickTree` makes the prefix fold up to `tin(u) + 1` the
+///   fold over the ancestors of `u`
+///
+/// Built in O(n) time.
+#[derive(Clone, Debug)]
+pub struct EulerTour {
+    parent: Box<[usize]>,
+    depth: Box<[usize]>,
+    tin: Box<[usize]>,
+    tout: Box<[usize]>,
+    /// `order[t]` is the vertex entered at time `t`.
+    order: Box<[usize]>,
+}
+
+impl EulerTour {
+    /// Creates an Euler tour of the tree with `n` vertices and the given edges, rooted at
+    /// `root`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root >= n`, if an endpoint is out of bounds, or if the edges do not form a
+    /// tree, in debug builds.
+    pub fn from_edges(n: usize, edges: &[(usize, usize)], root: usize) -> Self {
+        debug_assert!(root < n, "index out of bounds: root={}, len={}", root, n);
+        debug_assert_eq!(
+            edges.len() + 1,
+            n,
+            "a tree with n vertices has n - 1 edges: n={}, edges={}",
+            n,
+            edges.len(),
+        );
+        // Adjacency lists in compressed form.
+        let mut start = vec![0; n + 1];
+        for &(u, v) in edges {
+            debug_assert!(
+                u < n && v < n,
+                "index out of bounds: u={}, v={}, len={}",
+                u,
+                v,
+                n
+            );
+            start[u + 1] += 1;
+            start[v + 1] += 1;
+        }
+        for i in 0..n {
+            start[i + 1] += start[i];
+        }
+        let mut adj = vec![0; edges.len() << 1];
+        let mut fill = start.clone();
+        for &(u, v) in edges {
+            adj[fill[u]] = v;
+            fill[u] += 1;
+            adj[fill[v]] = u;
+            fill[v] += 1;
+        }
+
+        let mut parent = vec![NONE; n];
+        let mut depth = vec![0; n];
+        let mut tin = vec![0; n];
+        let mut tout = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        // `next[v]` is the next adjacency index of `v` to visit.
+        let mut next = start.clone();
+        let mut stack = vec![root];
+        order.push(root);
+        while let Some(&v) = stack.last() {
+            if next[v] == start[v + 1] {
+                tout[v] = order.len();
+                stack.pop();
+                continue;
+            }
+            let c = adj[next[v]];
+            next[v] += 1;
+            if c != parent[v] {
+                parent[c] = v;
+                depth[c] = depth[v] + 1;
+                tin[c] = order.len();
+                order.push(c);
+                stack.push(c);
+            }
+        }
+        debug_assert_eq!(order.len(), n, "the edges do not form a connected tree");
+
+        Self {
+            parent: parent.into_boxed_slice(),
+            depth: depth.into_boxed_slice(),
+            tin: tin.into_boxed_slice(),
+            tout: tout.into_boxed_slice(),
+            order: order.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the entry time of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn tin(&self, v: usize) -> usize {
+        self.tin[v]
+    }
+
+    /// Returns the exit time of `v`, i.e. `tin(v)` plus the size of the subtree of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn tout(&self, v: usize) -> usize {
+        self.tout[v]
+    }
+
+    /// Returns the vertex entered at time `t`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn vertex(&self, t: usize) -> usize {
+        self.order[t]
+    }
+
+    /// Returns the parent of `v`, or `None` if `v` is the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        Some(self.parent[v]).filter(|&p| p != NONE)
+    }
+
+    /// Returns the depth of `v`, where the root has depth `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    /// Returns the range of entry times covering the subtree of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn subtree_range(&self, v: usize) -> Range<usize> {
+        self.tin[v]..self.tout[v]
+    }
+
+    /// Returns the number of vertices in the subtree of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn subtree_size(&self, v: usize) -> usize {
+        self.tout[v] - self.tin[v]
+    }
+
+    /// Returns `true` if `u` is an ancestor of `v`, including `u == v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tin[v] < self.tout[u]
+    }
+
+    /// Returns the number of vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.tin.len()
+    }
+
+    /// Returns `true` if the tree has no vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
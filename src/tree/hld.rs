@@ -0,0 +1,305 @@
+use std::ops::Range;
+
+/// Sentinel parent of the root.
+const NONE: usize = usize::MAX;
+
+/// A heavy-light decomposition of a rooted tree.
+///
+/// Vertices are laid out in a base array so that every heavy path and every subtree occupies
+/// a contiguous range of positions. This supports:
+/// - Path decomposition: `path_ranges(u, v)` returns O(log n) ranges covering the path
+/// - Subtree range: `subtree_range(v)` returns the range covering the subtree of `v`
+/// - Lowest common ancestor: `lca(u, v)`
+///
+/// Storing the value of vertex `v` at `pos(v)` in a `SegmentTree` or `LazySegmentTree`, path
+/// queries and updates take O(log² n) time, and subtree queries and updates O(log n) time.
+/// For values on edges, store each edge at the position of its deeper endpoint and use
+/// `edge_path_ranges`.
+///
+/// Built in O(n) time.
+#[derive(Clone, Debug)]
+pub struct Hld {
+    parent: Box<[usize]>,
+    depth: Box<[usize]>,
+    /// `head[v]` is the topmost vertex of the heavy path containing `v`.
+    head: Box<[usize]>,
+    /// `pos[v]` is the position of `v` in the base array.
+    pos: Box<[usize]>,
+    /// `order[p]` is the vertex at position `p`.
+    order: Box<[usize]>,
+    size: Box<[usize]>,
+}
+
+impl Hld {
+    /// Creates a heavy-light decomposition of the tree with `n` vertices and the given edges,
+    /// rooted at `root`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root >= n`, if an endpoint is out of bounds, or if the edges do not form a
+    /// tree, in debug builds.
+    pub fn from_edges(n: usize, edges: &[(usize, usize)], root: usize) -> Self {
+        debug_assert!(root < n, "index out of bounds: root={}, len={}", root, n);
+        debug_assert_eq!(
+            edges.len() + 1,
+            n,
+            "a tree with n vertices has n - 1 edges: n={}, edges={}",
+            n,
+            edges.len(),
+        );
+        // Adjacency lists in compressed form.
+        let mut start = vec![0; n + 1];
+        for &(u, v) in edges {
+            debug_assert!(
+                u < n && v < n,
+                "index out of bounds: u={}, v={}, len={}",
+                u,
+                v,
+                n
+            );
+            start[u + 1] += 1;
+            start[v + 1] += 1;
+        }
+        for i in 0..n {
+            start[i + 1] += start[i];
+        }
+        let mut adj = vec![0; edges.len() << 1];
+        let mut fill = start.clone();
+        for &(u, v) in edges {
+            adj[fill[u]] = v;
+            fill[u] += 1;
+            adj[fill[v]] = u;
+            fill[v] += 1;
+        }
+
+        let mut parent = vec![NONE; n];
+        let mut depth = vec![0; n];
+        let mut preorder = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            preorder.push(v);
+            for &c in &adj[start[v]..start[v + 1]] {
+                if c != parent[v] {
+                    parent[c] = v;
+                    depth[c] = depth[v] + 1;
+                    stack.push(c);
+                }
+            }
+        }
+        debug_assert_eq!(preorder.len(), n, "the edges do not form a connected tree");
+
+        let mut size = vec![1; n];
+        let mut heavy = vec![NONE; n];
+        for &v in preorder.iter().rev() {
+            let p = parent[v];
+            if p != NONE {
+                size[p] += size[v];
+                if heavy[p] == NONE || size[heavy[p]] < size[v] {
+                    heavy[p] = v;
+                }
+            }
+        }
+
+        // Visit the heavy child right after its parent so heavy paths are contiguous.
+        let mut head = vec![root; n];
+        let mut pos = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        stack.push(root);
+        while let Some(v) = stack.pop() {
+            pos[v] = order.len();
+            order.push(v);
+            for &c in &adj[start[v]..start[v + 1]] {
+                if c != parent[v] && c != heavy[v] {
+                    head[c] = c;
+                    stack.push(c);
+                }
+            }
+            if heavy[v] != NONE {
+                head[heavy[v]] = head[v];
+                stack.push(heavy[v]);
+            }
+        }
+
+        Self {
+            parent: parent.into_boxed_slice(),
+            depth: depth.into_boxed_slice(),
+            head: head.into_boxed_slice(),
+            pos: pos.into_boxed_slice(),
+            order: order.into_boxed_slice(),
+            size: size.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the position of vertex `v` in the base array.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn pos(&self, v: usize) -> usize {
+        self.pos[v]
+    }
+
+    /// Returns the vertex at position `p` in the base array.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn vertex(&self, p: usize) -> usize {
+        self.order[p]
+    }
+
+    /// Returns the parent of `v`, or `None` if `v` is the root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        Some(self.parent[v]).filter(|&p| p != NONE)
+    }
+
+    /// Returns the depth of `v`, where the root has depth `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    /// Returns the number of vertices in the subtree of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn subtree_size(&self, v: usize) -> usize {
+        self.size[v]
+    }
+
+    /// Returns the lowest common ancestor of `u` and `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] > self.depth[self.head[v]] {
+                u = self.parent[self.head[u]];
+            } else {
+                v = self.parent[self.head[v]];
+            }
+        }
+        if self.depth[u] < self.depth[v] { u } else { v }
+    }
+
+    /// Returns the number of edges on the path between `u` and `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - (self.depth[self.lca(u, v)] << 1)
+    }
+
+    /// Returns `true` if `u` is an ancestor of `v`, including `u == v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.pos[u] <= self.pos[v] && self.pos[v] < self.pos[u] + self.size[u]
+    }
+
+    /// Returns the ranges of positions covering the vertices on the path from `u` to `v`.
+    ///
+    /// The ranges are listed in order along the path. A range paired with `true` is walked
+    /// from its last position to its first, i.e. upwards towards the root; for
+    /// non-commutative folds, its elements must be combined in reverse, e.g. with
+    /// `range_fold_rev`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn path_ranges(&self, u: usize, v: usize) -> Vec<(Range<usize>, bool)> {
+        self.path(u, v, false)
+    }
+
+    /// Returns the ranges of positions covering the edges on the path from `u` to `v`, where
+    /// every edge is stored at the position of its deeper endpoint.
+    ///
+    /// The ranges are ordered as in [`path_ranges`](Self::path_ranges).
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    #[inline]
+    pub fn edge_path_ranges(&self, u: usize, v: usize) -> Vec<(Range<usize>, bool)> {
+        self.path(u, v, true)
+    }
+
+    /// Returns the range of positions covering the subtree of `v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline]
+    pub fn subtree_range(&self, v: usize) -> Range<usize> {
+        self.pos[v]..self.pos[v] + self.size[v]
+    }
+
+    /// Returns the number of vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    /// Returns `true` if the tree has no vertices.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn path(&self, mut u: usize, mut v: usize, edge: bool) -> Vec<(Range<usize>, bool)> {
+        let mut up = vec![];
+        let mut down = vec![];
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] >= self.depth[self.head[v]] {
+                up.push((self.pos[self.head[u]]..self.pos[u] + 1, true));
+                u = self.parent[self.head[u]];
+            } else {
+                down.push((self.pos[self.head[v]]..self.pos[v] + 1, false));
+                v = self.parent[self.head[v]];
+            }
+        }
+        // The shallower of `u` and `v` is the lowest common ancestor.
+        let skip = edge as usize;
+        if self.pos[u] >= self.pos[v] {
+            if self.pos[v] + skip <= self.pos[u] {
+                up.push((self.pos[v] + skip..self.pos[u] + 1, true));
+            }
+        } else {
+            down.push((self.pos[u] + skip..self.pos[v] + 1, false));
+        }
+        up.extend(down.into_iter().rev());
+        up
+    }
+}
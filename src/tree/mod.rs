@@ -0,0 +1,7 @@
+pub mod euler_tour;
+pub mod hld;
+pub mod succinct;
+
+pub use euler_tour::EulerTour;
+pub use hld::Hld;
+pub use succinct::SuccinctTree;
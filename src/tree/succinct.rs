@@ -0,0 +1,499 @@
+use crate::bit_vector::RankSelectBitVec;
+
+/// Number of parentheses covered by a leaf of the excess tree.
+const BLOCK: usize = 512;
+
+/// Sentinel parent of the root.
+const NONE: usize = usize::MAX;
+
+/// Excess tables of the 256 bytes, read from the lowest bit, where an opening parenthesis
+/// counts `+1` and a closing one `-1`.
+struct ByteExcess {
+    /// Total excess of the byte.
+    total: [i8; 256],
+    /// Minimum excess of a nonempty prefix of the byte.
+    min_prefix: [i8; 256],
+    /// Maximum excess of a nonempty suffix of the byte.
+    max_suffix: [i8; 256],
+}
+
+const BYTE_EXCESS: ByteExcess = {
+    let mut res = ByteExcess {
+        total: [0; 256],
+        min_prefix: [0; 256],
+        max_suffix: [0; 256],
+    };
+    let mut b = 0;
+    while b < 256 {
+        let (mut e, mut min) = (0, i8::MAX);
+        let mut j = 0;
+        while j < 8 {
+            e += if (b >> j) & 1 == 1 { 1 } else { -1 };
+            if e < min {
+                min = e;
+            }
+            j += 1;
+        }
+        let (mut s, mut max) = (0, i8::MIN);
+        while j > 0 {
+            j -= 1;
+            s += if (b >> j) & 1 == 1 { 1 } else { -1 };
+            if s > max {
+                max = s;
+            }
+        }
+        res.total[b] = e;
+        res.min_prefix[b] = min;
+        res.max_suffix[b] = max;
+        b += 1;
+    }
+    res
+};
+
+/// A static ordered tree in about 2.75 bits per node, as balanced parentheses.
+///
+/// Every node is written as an opening parenthesis, the parentheses of its children in order,
+/// then a closing parenthesis, and is identified by its preorder index. This supports:
+/// - Navigation: `parent(v)`, `first_child(v)`, `next_sibling(v)` and `children(v)`
+/// - Subtree size: `subtree_size(v)`, and `is_ancestor(u, v)` from it
+/// - Depth: `depth(v)`
+///
+/// The parentheses take 2 bits per node and a [`RankSelectBitVec`] over them a quarter more.
+/// The excess of a position, the number of opening minus closing parentheses before it, is
+/// the depth there, so that the closing parenthesis of a node is the first position after it
+/// where the excess falls back, and the parent the last position before it where the excess
+/// is one less. Both are found with a minimum segment tree over the excess of blocks of 512
+/// parentheses, adding about a quarter bit per node, and byte lookup tables within a block.
+/// Built in O(n) time, and all queries run in O(log n) time.
+///
+/// Several trees written one after the other form a forest, whose roots are siblings.
+#[derive(Clone, Debug)]
+pub struct SuccinctTree {
+    /// The parentheses, with a one for every opening parenthesis.
+    bits: RankSelectBitVec,
+    /// A minimum segment tree over the blocks, whose leaf for block `b` holds the minimum
+    /// excess at the positions `b * BLOCK..=(b + 1) * BLOCK` within `0..=2n`.
+    mins: Box<[u32]>,
+}
+
+impl SuccinctTree {
+    /// Creates a tree from its balanced parentheses, `true` being an opening parenthesis.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parentheses are not balanced in debug builds.
+    pub fn from_parens(parens: impl IntoIterator<Item = bool>) -> Self {
+        let bits = RankSelectBitVec::from_bits(parens);
+        debug_assert!(
+            bits.len() as u64 <= u32::MAX as u64,
+            "too many nodes: {}",
+            bits.len() / 2,
+        );
+        let blocks = bits.len() / BLOCK + 1;
+        let size = blocks.next_power_of_two();
+        let mut mins = vec![u32::MAX; size << 1];
+        let mut e: i64 = 0;
+        mins[size] = 0;
+        for i in 0..bits.len() {
+            e += if bits.get(i) { 1 } else { -1 };
+            debug_assert!(e >= 0, "unbalanced parentheses at {}", i);
+            let (b, e) = (size + (i + 1) / BLOCK, e as u32);
+            mins[b] = mins[b].min(e);
+            if (i + 1) % BLOCK == 0 {
+                mins[b - 1] = mins[b - 1].min(e);
+            }
+        }
+        debug_assert_eq!(e, 0, "unbalanced parentheses: excess={}", e);
+        for i in (1..size).rev() {
+            mins[i] = mins[i << 1].min(mins[(i << 1) | 1]);
+        }
+        Self {
+            bits,
+            mins: mins.into_boxed_slice(),
+        }
+    }
+
+    /// Creates the tree with `n` vertices and the given edges, rooted at `root`.
+    ///
+    /// Vertices become nodes numbered in preorder, where the children of a vertex are visited
+    /// in the order of the edges.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `root >= n`, if an endpoint is out of bounds, or if the edges do not form a
+    /// tree, in debug builds.
+    pub fn from_edges(n: usize, edges: &[(usize, usize)], root: usize) -> Self {
+        debug_assert!(root < n, "index out of bounds: root={}, len={}", root, n);
+        debug_assert_eq!(
+            edges.len() + 1,
+            n,
+            "a tree with n vertices has n - 1 edges: n={}, edges={}",
+            n,
+            edges.len(),
+        );
+        // Adjacency lists in compressed form.
+        let mut start = vec![0; n + 1];
+        for &(u, v) in edges {
+            debug_assert!(
+                u < n && v < n,
+                "index out of bounds: u={}, v={}, len={}",
+                u,
+                v,
+                n
+            );
+            start[u + 1] += 1;
+            start[v + 1] += 1;
+        }
+        for i in 0..n {
+            start[i + 1] += start[i];
+        }
+        let mut adj = vec![0; edges.len() << 1];
+        let mut fill = start.clone();
+        for &(u, v) in edges {
+            adj[fill[u]] = v;
+            fill[u] += 1;
+            adj[fill[v]] = u;
+            fill[v] += 1;
+        }
+
+        let mut parent = vec![NONE; n];
+        let mut parens = Vec::with_capacity(n << 1);
+        // `next[v]` is the next adjacency index of `v` to visit.
+        let mut next = start.clone();
+        let mut stack = vec![root];
+        parens.push(true);
+        while let Some(&v) = stack.last() {
+            if next[v] == start[v + 1] {
+                parens.push(false);
+                stack.pop();
+                continue;
+            }
+            let c = adj[next[v]];
+            next[v] += 1;
+            if c != parent[v] {
+                parent[c] = v;
+                parens.push(true);
+                stack.push(c);
+            }
+        }
+        debug_assert_eq!(
+            parens.len(),
+            n << 1,
+            "the edges do not form a connected tree"
+        );
+        Self::from_parens(parens)
+    }
+
+    /// Returns the parent of `v`, or `None` if `v` is a root.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        let p = self.open(v);
+        let d = self.excess(p);
+        if d == 0 {
+            return None;
+        }
+        // The last position before `p` with excess `d - 1` opens the parent.
+        Some(self.bits.rank1(self.search_bwd(p, d - 1)?))
+    }
+
+    /// Returns the first child of `v`, or `None` if `v` is a leaf.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    #[inline]
+    pub fn first_child(&self, v: usize) -> Option<usize> {
+        let p = self.open(v);
+        self.bits.get(p + 1).then_some(v + 1)
+    }
+
+    /// Returns the next sibling of `v`, or `None` if `v` is a last child.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    pub fn next_sibling(&self, v: usize) -> Option<usize> {
+        let p = self.open(v);
+        let (next, size) = self.close(p, v);
+        (next < self.bits.len() && self.bits.get(next)).then_some(v + size)
+    }
+
+    /// Returns an iterator over the children of `v`, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n) per item
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    pub fn children(&self, v: usize) -> impl Iterator<Item = usize> {
+        std::iter::successors(self.first_child(v), |&c| self.next_sibling(c))
+    }
+
+    /// Returns the number of nodes in the subtree of `v`, which are `v..v + subtree_size(v)`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    #[inline]
+    pub fn subtree_size(&self, v: usize) -> usize {
+        self.close(self.open(v), v).1
+    }
+
+    /// Returns the depth of `v`, where a root has depth `0`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    #[inline]
+    pub fn depth(&self, v: usize) -> usize {
+        // `v` opening and `p - v` closing parentheses come before `p`.
+        (v << 1) - self.open(v)
+    }
+
+    /// Returns `true` if `v` has no children.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v >= len()` in debug builds.
+    #[inline]
+    pub fn is_leaf(&self, v: usize) -> bool {
+        self.first_child(v).is_none()
+    }
+
+    /// Returns `true` if `u` is an ancestor of `v`, including `u == v`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u >= len()` or `v >= len()` in debug builds.
+    #[inline]
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        debug_assert!(
+            v < self.len(),
+            "index out of bounds: v={}, len={}",
+            v,
+            self.len(),
+        );
+        u <= v && v < u + self.subtree_size(u)
+    }
+
+    /// Returns the number of nodes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.bits.len() >> 1
+    }
+
+    /// Returns `true` if the tree has no nodes.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position of the opening parenthesis of `v`.
+    #[inline]
+    fn open(&self, v: usize) -> usize {
+        debug_assert!(
+            v < self.len(),
+            "index out of bounds: v={}, len={}",
+            v,
+            self.len(),
+        );
+        self.bits.select1(v).unwrap()
+    }
+
+    /// Returns the position after the closing parenthesis of `v`, opened at `p`, and the size
+    /// of its subtree.
+    #[inline]
+    fn close(&self, p: usize, v: usize) -> (usize, usize) {
+        let next = self.search_fwd(p, self.excess(p)).unwrap();
+        (next, self.bits.rank1(next) - v)
+    }
+
+    /// Returns the excess at position `i`.
+    #[inline(always)]
+    fn excess(&self, i: usize) -> usize {
+        (self.bits.rank1(i) << 1) - i
+    }
+
+    /// Returns the first position after `i` with excess at most `t`, if any.
+    fn search_fwd(&self, i: usize, t: usize) -> Option<usize> {
+        let (n, t) = (self.bits.len(), t as isize);
+        let b = i / BLOCK;
+        let end = ((b + 1) * BLOCK).min(n);
+        if let Some(j) = self.scan_fwd(i, end, self.excess(i) as isize, t) {
+            return Some(j);
+        }
+        let size = self.mins.len() >> 1;
+        let mut k = size + b + 1;
+        if k == self.mins.len() {
+            return None;
+        }
+        // The first block to the right of `b` with excess at most `t`.
+        loop {
+            if self.mins[k] as isize <= t {
+                while k < size {
+                    k <<= 1;
+                    if self.mins[k] as isize > t {
+                        k |= 1;
+                    }
+                }
+                break;
+            }
+            while k & 1 == 1 {
+                k >>= 1;
+            }
+            if k == 0 {
+                return None;
+            }
+            k += 1;
+        }
+        let s = (k - size) * BLOCK;
+        self.scan_fwd(s, (s + BLOCK).min(n), self.excess(s) as isize, t)
+    }
+
+    /// Returns the last position before `i` with excess at most `t`, if any.
+    fn search_bwd(&self, i: usize, t: usize) -> Option<usize> {
+        let t = t as isize;
+        let b = i / BLOCK;
+        if let Some(j) = self.scan_bwd(i, b * BLOCK, self.excess(i) as isize, t) {
+            return Some(j);
+        }
+        if b == 0 {
+            return None;
+        }
+        let size = self.mins.len() >> 1;
+        let mut k = size + b - 1;
+        // The last block to the left of `b` with excess at most `t`.
+        loop {
+            if self.mins[k] as isize <= t {
+                while k < size {
+                    k = (k << 1) | 1;
+                    if self.mins[k] as isize > t {
+                        k ^= 1;
+                    }
+                }
+                break;
+            }
+            while k & 1 == 0 {
+                k >>= 1;
+            }
+            if k == 1 {
+                return None;
+            }
+            k -= 1;
+        }
+        let s = (k - size) * BLOCK;
+        self.scan_bwd(s + BLOCK, s, self.excess(s + BLOCK) as isize, t)
+    }
+
+    /// Returns the first position in `i + 1..=end` with excess at most `t`, given the excess
+    /// `e` at `i`.
+    fn scan_fwd(&self, mut i: usize, end: usize, mut e: isize, t: isize) -> Option<usize> {
+        let words = self.bits.words();
+        while i < end {
+            if i & 7 == 0 && i + 8 <= end {
+                let byte = (words[i >> 6] >> (i & 63)) as u8 as usize;
+                if e + BYTE_EXCESS.min_prefix[byte] as isize > t {
+                    e += BYTE_EXCESS.total[byte] as isize;
+                    i += 8;
+                    continue;
+                }
+            }
+            e += if (words[i >> 6] >> (i & 63)) & 1 == 1 {
+                1
+            } else {
+                -1
+            };
+            i += 1;
+            if e <= t {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Returns the last position in `start..i` with excess at most `t`, given the excess `e`
+    /// at `i`.
+    fn scan_bwd(&self, mut i: usize, start: usize, mut e: isize, t: isize) -> Option<usize> {
+        let words = self.bits.words();
+        while i > start {
+            if i & 7 == 0 && i >= start + 8 {
+                let byte = (words[(i - 8) >> 6] >> ((i - 8) & 63)) as u8 as usize;
+                if e - (BYTE_EXCESS.max_suffix[byte] as isize) > t {
+                    e -= BYTE_EXCESS.total[byte] as isize;
+                    i -= 8;
+                    continue;
+                }
+            }
+            i -= 1;
+            e -= if (words[i >> 6] >> (i & 63)) & 1 == 1 {
+                1
+            } else {
+                -1
+            };
+            if e <= t {
+                return Some(i);
+            }
+        }
+        None
+    }
+}
+
+impl FromIterator<bool> for SuccinctTree {
+    /// Creates a tree from its balanced parentheses, `true` being an opening parenthesis.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n)
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self::from_parens(iter)
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+/// A node of a trie.
+#[derive(Clone, Debug)]
+struct Node<K, V> {
+    children: BTreeMap<K, u32>,
+    value: Option<V>,
+    /// Number of keys in the subtrie.
+    count: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Self {
+            children: BTreeMap::new(),
+            value: None,
+            count: 0,
+        }
+    }
+}
+
+/// A map from sequences of tokens to values, organized by common prefixes.
+///
+/// Given keys that are slices of a totally ordered token type `K`, such as bytes, chars or
+/// compressed symbols, this data structure supports:
+/// - Update: `insert(key, value)` and `remove(key)`
+/// - Lookup: `get(key)` returns the value of `key`
+/// - Prefix queries: `longest_prefix(s)` returns the longest key that is a prefix of `s`,
+///   `count_prefix(p)` returns the number of keys starting with `p`, and `iter_prefix(p)`
+///   iterates over them
+///
+/// All operations on a key of length `m` run in O(m log σ) time, for an alphabet of size `σ`.
+/// The nodes live in an arena and keep their children in a [`BTreeMap`], so iteration follows
+/// the lexicographic order of the keys, and removed nodes are reused.
+#[derive(Clone)]
+pub struct Trie<K, V> {
+    /// Arena of all nodes; node 0 is the root, the empty key.
+    nodes: Vec<Node<K, V>>,
+    /// Indices of the removed nodes, to be reused.
+    free: Vec<u32>,
+}
+
+impl<K: Ord + Clone, V> Trie<K, V> {
+    /// Creates a new empty trie.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::new()],
+            free: Vec::new(),
+        }
+    }
+
+    /// Inserts `value` at `key`, and returns the previous value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    pub fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        let mut t = 0;
+        let mut path = Vec::with_capacity(key.len() + 1);
+        for k in key {
+            path.push(t);
+            t = match self.nodes[t as usize].children.get(k) {
+                Some(&c) => c,
+                None => {
+                    let c = self.alloc();
+                    self.nodes[t as usize].children.insert(k.clone(), c);
+                    c
+                }
+            };
+        }
+        let res = self.nodes[t as usize].value.replace(value);
+        if res.is_none() {
+            path.push(t);
+            for u in path {
+                self.nodes[u as usize].count += 1;
+            }
+        }
+        res
+    }
+
+    /// Removes `key`, and returns its value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    pub fn remove(&mut self, key: &[K]) -> Option<V> {
+        let mut path = Vec::with_capacity(key.len() + 1);
+        let mut t = 0;
+        path.push(t);
+        for k in key {
+            t = *self.nodes[t as usize].children.get(k)?;
+            path.push(t);
+        }
+        let res = self.nodes[t as usize].value.take()?;
+        for &u in &path {
+            self.nodes[u as usize].count -= 1;
+        }
+        // Free the nodes left without keys, from the deepest.
+        for i in (1..path.len()).rev() {
+            if self.nodes[path[i] as usize].count > 0 {
+                break;
+            }
+            self.nodes[path[i - 1] as usize]
+                .children
+                .remove(&key[i - 1]);
+            self.free.push(path[i]);
+        }
+        Some(res)
+    }
+
+    /// Returns the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn get(&self, key: &[K]) -> Option<&V> {
+        self.find(key)
+            .and_then(|t| self.nodes[t as usize].value.as_ref())
+    }
+
+    /// Returns a mutable reference to the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn get_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        self.find(key)
+            .and_then(|t| self.nodes[t as usize].value.as_mut())
+    }
+
+    /// Returns `true` if the trie contains `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn contains_key(&self, key: &[K]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the length and the value of the longest key that is a prefix of `s`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    pub fn longest_prefix(&self, s: &[K]) -> Option<(usize, &V)> {
+        let mut t = 0;
+        let mut res = self.nodes[0].value.as_ref().map(|v| (0, v));
+        for (i, k) in s.iter().enumerate() {
+            match self.nodes[t as usize].children.get(k) {
+                Some(&c) => t = c,
+                None => break,
+            }
+            if let Some(v) = &self.nodes[t as usize].value {
+                res = Some((i + 1, v));
+            }
+        }
+        res
+    }
+
+    /// Returns the number of keys starting with `prefix`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ)
+    #[inline]
+    pub fn count_prefix(&self, prefix: &[K]) -> usize {
+        self.find(prefix)
+            .map_or(0, |t| self.nodes[t as usize].count)
+    }
+
+    /// Returns an iterator over the entries whose key starts with `prefix`, in lexicographic
+    /// order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m log σ) to start, and O(total length of the keys) for a full traversal
+    pub fn iter_prefix(&self, prefix: &[K]) -> impl Iterator<Item = (Vec<K>, &V)> {
+        let mut key = prefix.to_vec();
+        let start = self.find(prefix).map(|t| &self.nodes[t as usize]);
+        let mut pending = start.and_then(|node| node.value.as_ref());
+        let mut stack: Vec<_> = start.map(|node| node.children.iter()).into_iter().collect();
+        std::iter::from_fn(move || {
+            if let Some(v) = pending.take() {
+                return Some((key.clone(), v));
+            }
+            loop {
+                match stack.last_mut()?.next() {
+                    Some((k, &c)) => {
+                        let node = &self.nodes[c as usize];
+                        key.push(k.clone());
+                        stack.push(node.children.iter());
+                        if let Some(v) = &node.value {
+                            return Some((key.clone(), v));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        if !stack.is_empty() {
+                            key.pop();
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over the entries, in lexicographic order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(total length of the keys) for a full traversal
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<K>, &V)> {
+        self.iter_prefix(&[])
+    }
+
+    /// Returns the number of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    /// Returns `true` if the trie is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the node of `key`, if any.
+    fn find(&self, key: &[K]) -> Option<u32> {
+        let mut t = 0;
+        for k in key {
+            t = *self.nodes[t as usize].children.get(k)?;
+        }
+        Some(t)
+    }
+
+    fn alloc(&mut self) -> u32 {
+        if let Some(t) = self.free.pop() {
+            self.nodes[t as usize] = Node::new();
+            return t;
+        }
+        debug_assert!(
+            self.nodes.len() < u32::MAX as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(Node::new());
+        (self.nodes.len() - 1) as u32
+    }
+}
+
+impl<K: Ord + Clone, V> Default for Trie<K, V> {
+    /// Creates a new empty trie.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V, Q: AsRef<[K]>> FromIterator<(Q, V)> for Trie<K, V> {
+    /// Creates a new trie from an iterator, keeping the last value of duplicate keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(total length of the keys · log σ)
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for (key, value) in iter {
+            res.insert(key.as_ref(), value);
+        }
+        res
+    }
+}
+
+impl<K: Ord + Clone + std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for Trie<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod core;
+pub mod radix;
+
+pub use core::Trie;
+pub use radix::RadixTrie;
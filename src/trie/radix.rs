@@ -0,0 +1,407 @@
+/// A node of a radix trie.
+#[derive(Clone, Debug)]
+struct Node<V> {
+    /// Label of the edge from the parent, empty only at the root.
+    label: Box<[u8]>,
+    /// Children, sorted by the first byte of their label.
+    children: Vec<(u8, u32)>,
+    value: Option<V>,
+    /// Number of keys in the subtrie.
+    count: usize,
+}
+
+impl<V> Node<V> {
+    fn new(label: Box<[u8]>, value: Option<V>, count: usize) -> Self {
+        Self {
+            label,
+            children: Vec::new(),
+            value,
+            count,
+        }
+    }
+}
+
+/// Returns the length of the longest common prefix of `a` and `b`.
+#[inline]
+fn lcp(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// A map from byte strings to values, as a path-compressed trie.
+///
+/// Given byte string keys, such as URLs or file paths, this data structure supports:
+/// - Update: `insert(key, value)` and `remove(key)`
+/// - Lookup: `get(key)` returns the value of `key`
+/// - Prefix queries: `longest_prefix(s)` returns the longest key that is a prefix of `s`,
+///   `count_prefix(p)` returns the number of keys starting with `p`, and `iter_prefix(p)`
+///   iterates over them
+///
+/// All operations on a key of length `m` run in O(m) time. Unlike [`Trie`](super::Trie),
+/// which has a node for every byte, every chain of nodes with a single child and no value is
+/// merged into one edge labeled with a byte string, so that there are fewer than `2n` nodes
+/// for `n` keys however long they are. Children are kept sorted by the first byte of their
+/// label, so iteration follows the lexicographic order of the keys, and removed nodes are
+/// reused.
+#[derive(Clone)]
+pub struct RadixTrie<V> {
+    /// Arena of all nodes; node 0 is the root, the empty key.
+    nodes: Vec<Node<V>>,
+    /// Indices of the removed nodes, to be reused.
+    free: Vec<u32>,
+}
+
+impl<V> RadixTrie<V> {
+    /// Creates a new empty trie.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::new(Box::default(), None, 0)],
+            free: Vec::new(),
+        }
+    }
+
+    /// Inserts `value` at `key`, and returns the previous value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let mut path = Vec::new();
+        let (mut t, mut i) = (0, 0);
+        loop {
+            path.push(t);
+            if i == key.len() {
+                let res = self.nodes[t as usize].value.replace(value);
+                if res.is_none() {
+                    for u in path {
+                        self.nodes[u as usize].count += 1;
+                    }
+                }
+                return res;
+            }
+            let children = &self.nodes[t as usize].children;
+            let pos = match children.binary_search_by_key(&key[i], |&(b, _)| b) {
+                Ok(pos) => pos,
+                Err(pos) => {
+                    let c = self.alloc(Node::new(key[i..].into(), Some(value), 1));
+                    self.nodes[t as usize].children.insert(pos, (key[i], c));
+                    break;
+                }
+            };
+            let c = children[pos].1;
+            let label = &self.nodes[c as usize].label;
+            let l = lcp(label, &key[i..]);
+            if l == label.len() {
+                t = c;
+                i += l;
+                continue;
+            }
+            // Split the edge to `c` after `l` bytes.
+            let (head, tail): (Box<[u8]>, Box<[u8]>) = (label[..l].into(), label[l..].into());
+            let b = tail[0];
+            let count = self.nodes[c as usize].count + 1;
+            self.nodes[c as usize].label = tail;
+            let m = self.alloc(Node::new(head, None, count));
+            self.nodes[m as usize].children.push((b, c));
+            self.nodes[t as usize].children[pos].1 = m;
+            if i + l == key.len() {
+                self.nodes[m as usize].value = Some(value);
+            } else {
+                let leaf = self.alloc(Node::new(key[i + l..].into(), Some(value), 1));
+                let children = &mut self.nodes[m as usize].children;
+                let pos = (key[i + l] > b) as usize;
+                children.insert(pos, (key[i + l], leaf));
+            }
+            break;
+        }
+        for u in path {
+            self.nodes[u as usize].count += 1;
+        }
+        None
+    }
+
+    /// Removes `key`, and returns its value, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let mut path = vec![0];
+        let mut i = 0;
+        while i < key.len() {
+            let c = self.child(*path.last().unwrap(), key[i])?;
+            let label = &self.nodes[c as usize].label;
+            if !key[i..].starts_with(label) {
+                return None;
+            }
+            i += label.len();
+            path.push(c);
+        }
+        let t = *path.last().unwrap();
+        let res = self.nodes[t as usize].value.take()?;
+        for &u in &path {
+            self.nodes[u as usize].count -= 1;
+        }
+        if t == 0 {
+            return Some(res);
+        }
+        // Restore the invariant that every node but the root has a value or two children.
+        match self.nodes[t as usize].children.len() {
+            0 => {
+                let p = path[path.len() - 2];
+                let b = self.nodes[t as usize].label[0];
+                let children = &mut self.nodes[p as usize].children;
+                let pos = children.binary_search_by_key(&b, |&(b, _)| b).unwrap();
+                children.remove(pos);
+                self.release(t);
+                let node = &self.nodes[p as usize];
+                if p != 0 && node.value.is_none() && node.children.len() == 1 {
+                    self.merge(p);
+                }
+            }
+            1 => self.merge(t),
+            _ => {}
+        }
+        Some(res)
+    }
+
+    /// Returns the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    #[inline]
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.find(key)
+            .and_then(|t| self.nodes[t as usize].value.as_ref())
+    }
+
+    /// Returns a mutable reference to the value of `key`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    #[inline]
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        self.find(key)
+            .and_then(|t| self.nodes[t as usize].value.as_mut())
+    }
+
+    /// Returns `true` if the trie contains `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    #[inline]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the length and the value of the longest key that is a prefix of `s`, if any.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    pub fn longest_prefix(&self, s: &[u8]) -> Option<(usize, &V)> {
+        let (mut t, mut i) = (0, 0);
+        let mut res = self.nodes[0].value.as_ref().map(|v| (0, v));
+        while i < s.len()
+            && let Some(c) = self.child(t, s[i])
+            && s[i..].starts_with(&self.nodes[c as usize].label)
+        {
+            t = c;
+            i += self.nodes[c as usize].label.len();
+            if let Some(v) = &self.nodes[c as usize].value {
+                res = Some((i, v));
+            }
+        }
+        res
+    }
+
+    /// Returns the number of keys starting with `prefix`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m)
+    #[inline]
+    pub fn count_prefix(&self, prefix: &[u8]) -> usize {
+        self.find_prefix(prefix)
+            .map_or(0, |(t, _)| self.nodes[t as usize].count)
+    }
+
+    /// Returns an iterator over the entries whose key starts with `prefix`, in lexicographic
+    /// order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(m) to start, and O(total length of the keys) for a full traversal
+    pub fn iter_prefix(&self, prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, &V)> {
+        let mut key = prefix.to_vec();
+        let start = self.find_prefix(prefix).map(|(t, j)| {
+            let node = &self.nodes[t as usize];
+            key.extend_from_slice(&node.label[j..]);
+            node
+        });
+        let mut pending = start.and_then(|node| node.value.as_ref());
+        // Every frame holds the remaining children of a node and the length of its key.
+        let mut stack: Vec<_> = start
+            .map(|node| (node.children.iter(), key.len()))
+            .into_iter()
+            .collect();
+        std::iter::from_fn(move || {
+            if let Some(v) = pending.take() {
+                return Some((key.clone(), v));
+            }
+            loop {
+                let (children, len) = stack.last_mut()?;
+                match children.next() {
+                    Some(&(_, c)) => {
+                        let node = &self.nodes[c as usize];
+                        key.truncate(*len);
+                        key.extend_from_slice(&node.label);
+                        stack.push((node.children.iter(), key.len()));
+                        if let Some(v) = &node.value {
+                            return Some((key.clone(), v));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over the entries, in lexicographic order of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(total length of the keys) for a full traversal
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, &V)> {
+        self.iter_prefix(&[])
+    }
+
+    /// Returns the number of keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    /// Returns `true` if the trie is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the child of `t` whose label starts with `b`, if any.
+    #[inline]
+    fn child(&self, t: u32, b: u8) -> Option<u32> {
+        let children = &self.nodes[t as usize].children;
+        let pos = children.binary_search_by_key(&b, |&(b, _)| b).ok()?;
+        Some(children[pos].1)
+    }
+
+    /// Returns the node of `key`, if any.
+    #[inline]
+    fn find(&self, key: &[u8]) -> Option<u32> {
+        self.find_prefix(key)
+            .filter(|&(t, j)| j == self.nodes[t as usize].label.len())
+            .map(|(t, _)| t)
+    }
+
+    /// Returns the highest node whose key starts with `prefix`, if any, and the number of
+    /// bytes of its label within `prefix`.
+    fn find_prefix(&self, prefix: &[u8]) -> Option<(u32, usize)> {
+        let (mut t, mut i) = (0, 0);
+        while i < prefix.len() {
+            let c = self.child(t, prefix[i])?;
+            let label = &self.nodes[c as usize].label;
+            let l = lcp(label, &prefix[i..]);
+            if i + l == prefix.len() {
+                return Some((c, l));
+            }
+            if l < label.len() {
+                return None;
+            }
+            t = c;
+            i += l;
+        }
+        Some((t, self.nodes[t as usize].label.len()))
+    }
+
+    /// Merges the node `t`, which has no value, with its only child.
+    fn merge(&mut self, t: u32) {
+        let c = self.nodes[t as usize].children[0].1;
+        let child = std::mem::replace(
+            &mut self.nodes[c as usize],
+            Node::new(Box::default(), None, 0),
+        );
+        let node = &mut self.nodes[t as usize];
+        node.label = [&node.label[..], &child.label[..]]
+            .concat()
+            .into_boxed_slice();
+        node.children = child.children;
+        node.value = child.value;
+        self.free.push(c);
+    }
+
+    fn alloc(&mut self, node: Node<V>) -> u32 {
+        if let Some(t) = self.free.pop() {
+            self.nodes[t as usize] = node;
+            return t;
+        }
+        debug_assert!(
+            self.nodes.len() < u32::MAX as usize,
+            "too many nodes: {}",
+            self.nodes.len(),
+        );
+        self.nodes.push(node);
+        (self.nodes.len() - 1) as u32
+    }
+
+    /// Frees the node `t`, dropping its label.
+    fn release(&mut self, t: u32) {
+        self.nodes[t as usize] = Node::new(Box::default(), None, 0);
+        self.free.push(t);
+    }
+}
+
+impl<V> Default for RadixTrie<V> {
+    /// Creates a new empty trie.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, Q: AsRef<[u8]>> FromIterator<(Q, V)> for RadixTrie<V> {
+    /// Creates a new trie from an iterator, keeping the last value of duplicate keys.
+    ///
+    /// # Time complexity
+    ///
+    /// O(total length of the keys)
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut res = Self::new();
+        for (key, value) in iter {
+            res.insert(key.as_ref(), value);
+        }
+        res
+    }
+}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for RadixTrie<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,301 @@
+use crate::bit_vector::RankSelectBitVec;
+
+/// A wavelet matrix over a sequence of non-negative integers.
+///
+/// Given a sequence `a` of `n` values less than `2^bits`, this data structure supports:
+/// - Access: `get(i)` returns `a[i]`
+/// - Rank: `rank(x, r)` returns the number of occurrences of `x` in `a[..r]`
+/// - Select: `select(x, k)` returns the position of the `k`-th occurrence of `x`
+/// - Quantile: `quantile(l..r, k)` returns the `k`-th smallest value in `a[l..r]`
+/// - Range frequency: `range_freq(l..r, lo..hi)` returns the number of values in `lo..hi`
+///
+/// Built in O(n log σ) time, where σ is the maximum value plus one. All queries run in
+/// O(log σ) time, except `select` which runs in O(log σ log n).
+#[derive(Clone, Debug)]
+pub struct WaveletMatrix {
+    /// `levels[d]` stores bit `bits - 1 - d` of every value, in the order after `d` stable
+    /// partitions by the higher bits.
+    levels: Box<[RankSelectBitVec]>,
+    /// `zeros[d]` is the number of zeros in `levels[d]`.
+    zeros: Box<[usize]>,
+    n: usize,
+}
+
+impl WaveletMatrix {
+    /// Creates a new wavelet matrix from a slice.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log σ)
+    pub fn from_slice(v: &[u64]) -> Self {
+        Self::from_vec(v.to_vec())
+    }
+
+    /// Creates a new wavelet matrix from a vec.
+    ///
+    /// # Time complexity
+    ///
+    /// O(n log σ)
+    pub fn from_vec(mut v: Vec<u64>) -> Self {
+        let n = v.len();
+        let bits = (u64::BITS - v.iter().max().copied().unwrap_or(0).leading_zeros()) as usize;
+        let mut levels = Vec::with_capacity(bits);
+        let mut zeros = Vec::with_capacity(bits);
+        let mut ones = Vec::with_capacity(n);
+        for d in (0..bits).rev() {
+            let level = RankSelectBitVec::from_bits(v.iter().map(|&x| (x >> d) & 1 == 1));
+            let mut k = 0;
+            ones.clear();
+            for i in 0..n {
+                if level.get(i) {
+                    ones.push(v[i]);
+                } else {
+                    v[k] = v[i];
+                    k += 1;
+                }
+            }
+            v[k..].copy_from_slice(&ones);
+            levels.push(level);
+            zeros.push(k);
+        }
+        Self {
+            levels: levels.into_boxed_slice(),
+            zeros: zeros.into_boxed_slice(),
+            n,
+        }
+    }
+
+    /// Returns the value at index `i`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()` in debug builds.
+    pub fn get(&self, mut i: usize) -> u64 {
+        debug_assert!(
+            i < self.len(),
+            "index out of bounds: i={}, len={}",
+            i,
+            self.len()
+        );
+        let mut x = 0;
+        for (level, &zeros) in self.levels.iter().zip(self.zeros.iter()) {
+            x <<= 1;
+            if level.get(i) {
+                x |= 1;
+                i = zeros + level.rank1(i);
+            } else {
+                i = level.rank0(i);
+            }
+        }
+        x
+    }
+
+    /// Returns the number of occurrences of `x` in `a[0..r]`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r > len()` in debug builds.
+    pub fn rank(&self, x: u64, r: usize) -> usize {
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len()
+        );
+        if self.out_of_range(x) {
+            return 0;
+        }
+        let (l, r) = self.descend(x, 0, r);
+        r - l
+    }
+
+    /// Returns the position of the `k`-th (0-indexed) occurrence of `x`.
+    ///
+    /// Returns `None` if `x` occurs at most `k` times.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ log n)
+    pub fn select(&self, x: u64, k: usize) -> Option<usize> {
+        if self.out_of_range(x) {
+            return None;
+        }
+        let (l, r) = self.descend(x, 0, self.n);
+        if r - l <= k {
+            return None;
+        }
+        let mut i = l + k;
+        for d in (0..self.bits()).rev() {
+            let level = &self.levels[d];
+            i = if (x >> (self.bits() - 1 - d)) & 1 == 1 {
+                level.select1(i - self.zeros[d]).unwrap()
+            } else {
+                level.select0(i).unwrap()
+            };
+        }
+        Some(i)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest value in the given range.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds, or `k` is not less than the length
+    /// of the range, in debug builds.
+    pub fn quantile(&self, range: impl std::ops::RangeBounds<usize>, mut k: usize) -> u64 {
+        let (mut l, mut r) = self.bounds(range);
+        debug_assert!(
+            k < r - l,
+            "k must be less than the length of the range: k={}, len={}",
+            k,
+            r - l
+        );
+        let mut x = 0;
+        for (level, &zeros) in self.levels.iter().zip(self.zeros.iter()) {
+            x <<= 1;
+            let (l0, r0) = (level.rank0(l), level.rank0(r));
+            if k < r0 - l0 {
+                (l, r) = (l0, r0);
+            } else {
+                k -= r0 - l0;
+                x |= 1;
+                (l, r) = (zeros + l - l0, zeros + r - r0);
+            }
+        }
+        x
+    }
+
+    /// Returns the number of indices `i` in the given range with `a[i]` in `values`.
+    ///
+    /// # Time complexity
+    ///
+    /// O(log σ)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid or out of bounds in debug builds.
+    pub fn range_freq(
+        &self,
+        range: impl std::ops::RangeBounds<usize>,
+        values: impl std::ops::RangeBounds<u64>,
+    ) -> usize {
+        let (l, r) = self.bounds(range);
+        let lo = match values.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => self.count_less(l, r, x),
+            std::ops::Bound::Excluded(&x) => {
+                x.checked_add(1).map_or(r - l, |x| self.count_less(l, r, x))
+            }
+        };
+        let hi = match values.end_bound() {
+            std::ops::Bound::Unbounded => r - l,
+            std::ops::Bound::Included(&x) => {
+                x.checked_add(1).map_or(r - l, |x| self.count_less(l, r, x))
+            }
+            std::ops::Bound::Excluded(&x) => self.count_less(l, r, x),
+        };
+        hi.saturating_sub(lo)
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Returns `true` if the wavelet matrix is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// O(1)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    fn bits(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns `true` if `x` does not fit in `bits()` bits.
+    #[inline(always)]
+    fn out_of_range(&self, x: u64) -> bool {
+        x.checked_shr(self.bits() as u32).unwrap_or(0) != 0
+    }
+
+    /// Follows `x` down every level, mapping `l..r` to the range of its occurrences.
+    #[inline]
+    fn descend(&self, x: u64, mut l: usize, mut r: usize) -> (usize, usize) {
+        for (d, (level, &zeros)) in self.levels.iter().zip(self.zeros.iter()).enumerate() {
+            if (x >> (self.bits() - 1 - d)) & 1 == 1 {
+                (l, r) = (zeros + level.rank1(l), zeros + level.rank1(r));
+            } else {
+                (l, r) = (level.rank0(l), level.rank0(r));
+            }
+        }
+        (l, r)
+    }
+
+    /// Returns the number of indices `i` in `l..r` with `a[i] < x`.
+    #[inline]
+    fn count_less(&self, mut l: usize, mut r: usize, x: u64) -> usize {
+        if self.out_of_range(x) {
+            return r - l;
+        }
+        let mut res = 0;
+        for (d, (level, &zeros)) in self.levels.iter().zip(self.zeros.iter()).enumerate() {
+            let (l0, r0) = (level.rank0(l), level.rank0(r));
+            if (x >> (self.bits() - 1 - d)) & 1 == 1 {
+                res += r0 - l0;
+                (l, r) = (zeros + l - l0, zeros + r - r0);
+            } else {
+                (l, r) = (l0, r0);
+            }
+        }
+        res
+    }
+
+    #[inline]
+    fn bounds(&self, range: impl std::ops::RangeBounds<usize>) -> (usize, usize) {
+        let l = match range.start_bound() {
+            std::ops::Bound::Unbounded => 0,
+            std::ops::Bound::Included(&x) => x,
+            std::ops::Bound::Excluded(&x) => x + 1,
+        };
+        let r = match range.end_bound() {
+            std::ops::Bound::Unbounded => self.len(),
+            std::ops::Bound::Included(&x) => x + 1,
+            std::ops::Bound::Excluded(&x) => x,
+        };
+        debug_assert!(
+            l <= r,
+            "left bound must be less than or equal to right bound: l={}, r={}",
+            l,
+            r,
+        );
+        debug_assert!(
+            r <= self.len(),
+            "index out of bounds: r={}, len={}",
+            r,
+            self.len(),
+        );
+        (l, r)
+    }
+}